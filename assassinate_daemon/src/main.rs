@@ -61,6 +61,8 @@ impl Daemon {
         let stats_interval = Duration::from_secs(60);
 
         while !self.shutdown.load(Ordering::Relaxed) {
+            self.ring_buffer.stamp_heartbeat();
+
             match self.ring_buffer.try_read() {
                 Ok(data) => {
                     self.request_count.fetch_add(1, Ordering::Relaxed);
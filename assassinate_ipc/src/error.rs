@@ -0,0 +1,31 @@
+/// Error types for the IPC layer
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum IpcError {
+    #[error("Shared memory error: {0}")]
+    SharedMemory(String),
+
+    #[error("Ring buffer full (capacity: {0} bytes)")]
+    RingBufferFull(usize),
+
+    #[error("Ring buffer empty")]
+    RingBufferEmpty,
+
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+
+    #[error("Deserialization error: {0}")]
+    Deserialization(String),
+
+    #[error("Timeout after {0}ms")]
+    Timeout(u64),
+
+    #[error("Consumer appears dead (no heartbeat within the staleness threshold)")]
+    ConsumerDead,
+
+    #[error("Protocol version mismatch: we speak {ours}, peer speaks {theirs}")]
+    VersionMismatch { ours: u32, theirs: u32 },
+}
+
+pub type Result<T> = std::result::Result<T, IpcError>;
@@ -0,0 +1,95 @@
+//! Passing an open file descriptor (e.g. a `memfd_create`d shared memory
+//! region) across processes over a Unix domain socket, via an
+//! `SCM_RIGHTS` ancillary message - the mechanism `SharedMemory::send_fd`/
+//! `recv_fd` use so a parent can hand a child a live memfd without either
+//! side ever touching `/dev/shm`.
+
+use crate::error::{IpcError, Result};
+use std::mem::size_of;
+use std::os::unix::io::RawFd;
+
+/// Send `fd` across `sock` as an `SCM_RIGHTS` ancillary message, along
+/// with one byte of real data - a zero-length `sendmsg` can silently drop
+/// ancillary data on some platforms, so a real payload byte is mandatory
+/// rather than just defensive.
+pub fn send_fd(sock: RawFd, fd: RawFd) -> Result<()> {
+    let data = [0u8; 1];
+    let iov = libc::iovec {
+        iov_base: data.as_ptr() as *mut libc::c_void,
+        iov_len: data.len(),
+    };
+
+    let mut cmsg_buf = vec![0u8; unsafe { libc::CMSG_SPACE(size_of::<RawFd>() as u32) as usize }];
+
+    let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+    msg.msg_iov = &iov as *const libc::iovec as *mut libc::iovec;
+    msg.msg_iovlen = 1;
+    msg.msg_control = cmsg_buf.as_mut_ptr() as *mut libc::c_void;
+    msg.msg_controllen = cmsg_buf.len() as _;
+
+    unsafe {
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        if cmsg.is_null() {
+            return Err(IpcError::SharedMemory(
+                "failed to build SCM_RIGHTS control message".to_string(),
+            ));
+        }
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(size_of::<RawFd>() as u32) as _;
+        std::ptr::write(libc::CMSG_DATA(cmsg) as *mut RawFd, fd);
+    }
+
+    let sent = unsafe { libc::sendmsg(sock, &msg, 0) };
+    if sent < 0 {
+        return Err(IpcError::SharedMemory(format!(
+            "sendmsg failed: {}",
+            std::io::Error::last_os_error()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Receive a single fd sent by `send_fd` over `sock`. Checks `MSG_CTRUNC`
+/// so a control buffer too small to hold the ancillary data is reported
+/// as an error instead of silently handing back a truncated message.
+pub fn recv_fd(sock: RawFd) -> Result<RawFd> {
+    let mut data = [0u8; 1];
+    let mut iov = libc::iovec {
+        iov_base: data.as_mut_ptr() as *mut libc::c_void,
+        iov_len: data.len(),
+    };
+
+    let mut cmsg_buf = vec![0u8; unsafe { libc::CMSG_SPACE(size_of::<RawFd>() as u32) as usize }];
+
+    let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = cmsg_buf.as_mut_ptr() as *mut libc::c_void;
+    msg.msg_controllen = cmsg_buf.len() as _;
+
+    let received = unsafe { libc::recvmsg(sock, &mut msg, 0) };
+    if received < 0 {
+        return Err(IpcError::SharedMemory(format!(
+            "recvmsg failed: {}",
+            std::io::Error::last_os_error()
+        )));
+    }
+
+    if msg.msg_flags & libc::MSG_CTRUNC != 0 {
+        return Err(IpcError::SharedMemory(
+            "recvmsg control buffer was truncated (MSG_CTRUNC)".to_string(),
+        ));
+    }
+
+    unsafe {
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        if cmsg.is_null() || (*cmsg).cmsg_level != libc::SOL_SOCKET || (*cmsg).cmsg_type != libc::SCM_RIGHTS {
+            return Err(IpcError::SharedMemory(
+                "no SCM_RIGHTS control message received".to_string(),
+            ));
+        }
+        Ok(std::ptr::read(libc::CMSG_DATA(cmsg) as *const RawFd))
+    }
+}
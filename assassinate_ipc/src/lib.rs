@@ -32,6 +32,8 @@
 //! ```
 
 pub mod error;
+#[cfg(unix)]
+mod fd_passing;
 pub mod ring_buffer;
 pub mod shm;
 
@@ -53,28 +55,206 @@ pub const DEFAULT_BUFFER_SIZE: usize = 64 * 1024 * 1024;
 /// Default shared memory name
 pub const DEFAULT_SHM_NAME: &str = "/assassinate_msf_ipc";
 
+/// How long `call_async` waits for a response before giving up and
+/// returning `IpcError::Timeout`.
+pub const DEFAULT_CALL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Wire protocol version spoken by this build. Bumped whenever the frame
+/// layout or `msf.capnp` schema changes in a way older/newer builds can't
+/// safely interoperate with - see `RingBuffer::handshake`.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// How long `IpcClient::new`/`open` wait for the peer's Hello during the
+/// handshake before giving up on it. Kept short since, absent a peer
+/// (e.g. a lone client in a test), this is pure added latency on every
+/// construction.
+pub const HANDSHAKE_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Capability bit: this side can safely share the ring buffer between
+/// multiple concurrent producers (see `RingBuffer::claim`).
+pub const CAP_MULTI_PRODUCER: u32 = 1 << 0;
+
+/// Capability bit: this side understands `Notification` frames and the
+/// `__subscribe`/`__unsubscribe` control methods (see `IpcClient::subscribe`).
+pub const CAP_NOTIFICATIONS: u32 = 1 << 1;
+
+/// Capability bit: this side can read frames without copying the payload
+/// out of shared memory first. Reserved - `RingBuffer::try_read` currently
+/// always copies - but declared now so both ends compile against a single
+/// source of truth once zero-copy reads land.
+pub const CAP_ZERO_COPY_READ: u32 = 1 << 2;
+
+/// The capabilities this build actually supports, offered during the
+/// handshake.
+pub const OUR_CAPABILITIES: u32 = CAP_MULTI_PRODUCER | CAP_NOTIFICATIONS;
+
+/// The peer's negotiated protocol version, as learned during the
+/// handshake performed in `IpcClient::new`/`open`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolVersion(pub u32);
+
+/// The capability bitset both sides agreed on (the intersection of ours
+/// and the peer's), as learned during the handshake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities(pub u32);
+
+impl Capabilities {
+    /// Whether this negotiated set includes `bit` (one of the `CAP_*`
+    /// constants above).
+    pub fn supports(&self, bit: u32) -> bool {
+        self.0 & bit != 0
+    }
+}
+
+/// Calls awaiting a response, keyed by call id - shared between `IpcClient`
+/// and its background reader task.
+type PendingCalls = std::sync::Arc<std::sync::Mutex<rustc_hash::FxHashMap<u64, tokio::sync::oneshot::Sender<Vec<u8>>>>>;
+
+/// Live subscriptions, keyed by topic - shared between `IpcClient` and its
+/// background reader task the same way `PendingCalls` is, except routed by
+/// topic instead of call id since notifications aren't responses to any
+/// particular call.
+type Subscriptions = std::sync::Arc<std::sync::Mutex<rustc_hash::FxHashMap<String, tokio::sync::mpsc::UnboundedSender<serde_json::Value>>>>;
+
 /// IPC client for sending MSF requests
 pub struct IpcClient {
-    ring_buffer: RingBuffer,
+    ring_buffer: std::sync::Arc<RingBuffer>,
     next_call_id: std::sync::atomic::AtomicU64,
+    pending: PendingCalls,
+    subscriptions: Subscriptions,
+    protocol_version: ProtocolVersion,
+    capabilities: Capabilities,
 }
 
 impl IpcClient {
     /// Create a new IPC client
     pub fn new(name: &str, capacity: usize) -> Result<Self> {
-        let ring_buffer = RingBuffer::create(name, capacity)?;
-        Ok(Self {
-            ring_buffer,
-            next_call_id: std::sync::atomic::AtomicU64::new(1),
-        })
+        let ring_buffer = std::sync::Arc::new(RingBuffer::create(name, capacity)?);
+        Self::with_ring_buffer(ring_buffer)
     }
 
     /// Open an existing IPC connection
     pub fn open(name: &str, capacity: usize) -> Result<Self> {
-        let ring_buffer = RingBuffer::open(name, capacity)?;
+        let ring_buffer = std::sync::Arc::new(RingBuffer::open(name, capacity)?);
+        Self::with_ring_buffer(ring_buffer)
+    }
+
+    /// Shared constructor for `new`/`open`: performs the protocol
+    /// handshake, then starts the background reader that `call_async`
+    /// depends on, if (and only if) we're being constructed on a thread
+    /// already driven by a Tokio runtime - plain `call`/`try_recv` users
+    /// never touch `pending`, so they don't need one, and there'd be
+    /// nowhere to `spawn` it for them anyway.
+    ///
+    /// A peer that never shows up within `HANDSHAKE_TIMEOUT` (e.g. a lone
+    /// client with nothing on the other end yet) is tolerated - we just
+    /// fall back to our own version/capabilities rather than failing the
+    /// whole construction over it. A peer that *does* show up speaking a
+    /// different major version is not tolerated, since continuing would
+    /// silently corrupt frames.
+    fn with_ring_buffer(ring_buffer: std::sync::Arc<RingBuffer>) -> Result<Self> {
+        let (protocol_version, capabilities) = match ring_buffer.handshake(OUR_CAPABILITIES, HANDSHAKE_TIMEOUT) {
+            Ok((version, capabilities)) => (ProtocolVersion(version), Capabilities(capabilities)),
+            Err(IpcError::Timeout(_)) => (ProtocolVersion(PROTOCOL_VERSION), Capabilities(OUR_CAPABILITIES)),
+            Err(e) => return Err(e),
+        };
+
+        let pending: PendingCalls = std::sync::Arc::new(std::sync::Mutex::new(rustc_hash::FxHashMap::default()));
+        let subscriptions: Subscriptions = std::sync::Arc::new(std::sync::Mutex::new(rustc_hash::FxHashMap::default()));
+
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let reader_ring = std::sync::Arc::clone(&ring_buffer);
+            let reader_pending = std::sync::Arc::clone(&pending);
+            let reader_subscriptions = std::sync::Arc::clone(&subscriptions);
+            handle.spawn(Self::reader_loop(reader_ring, reader_pending, reader_subscriptions));
+        }
+
         Ok(Self {
             ring_buffer,
             next_call_id: std::sync::atomic::AtomicU64::new(1),
+            pending,
+            subscriptions,
+            protocol_version,
+            capabilities,
+        })
+    }
+
+    /// The peer's protocol version, as negotiated during construction (or
+    /// our own, if no peer responded in time - see `with_ring_buffer`).
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        self.protocol_version
+    }
+
+    /// The negotiated capability set - the intersection of what we offer
+    /// and what the peer offered (or just our own, if no peer responded
+    /// in time).
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Background task driving `call_async` and `subscribe`: loops on the
+    /// ring buffer, and for each frame either routes it to the pending
+    /// call waiting on its call id (via `protocol::peek_call_id`, without
+    /// fully decoding it) or, for a `Notification` frame, decodes it and
+    /// hands the payload to whichever subscription stream is registered
+    /// for its topic. A frame with no pending call or no subscriber
+    /// (already timed out / unsubscribed, or a stray frame) is dropped.
+    async fn reader_loop(ring_buffer: std::sync::Arc<RingBuffer>, pending: PendingCalls, subscriptions: Subscriptions) {
+        loop {
+            match ring_buffer.try_read() {
+                Ok(data) => match protocol::is_notification(&data) {
+                    Ok(true) => {
+                        if let Ok((topic, payload)) = protocol::decode_notification(&data) {
+                            let sender = subscriptions.lock().unwrap().get(&topic).cloned();
+                            if let Some(tx) = sender {
+                                let _ = tx.send(payload);
+                            }
+                        }
+                    }
+                    Ok(false) => match protocol::peek_call_id(&data) {
+                        Ok(call_id) => {
+                            let sender = pending.lock().unwrap().remove(&call_id);
+                            if let Some(tx) = sender {
+                                let _ = tx.send(data);
+                            }
+                        }
+                        Err(_) => {
+                            // Not a frame we can route - nothing sensible to
+                            // do but drop it and keep reading.
+                        }
+                    },
+                    Err(_) => {}
+                },
+                Err(IpcError::RingBufferEmpty) => {
+                    tokio::time::sleep(std::time::Duration::from_micros(50)).await;
+                }
+                Err(_) => {
+                    tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+                }
+            }
+        }
+    }
+
+    /// Subscribe to MSF events published under `topic` (e.g. "session
+    /// opened", "job completed"), returning a `futures::Stream` of their
+    /// payloads. Sends a `__subscribe` control call so the daemon starts
+    /// routing that topic's events to us; dropping the returned stream
+    /// sends the matching `__unsubscribe` call and deregisters it.
+    pub fn subscribe(&self, topic: &str) -> Result<SubscriptionStream> {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        self.subscriptions.lock().unwrap().insert(topic.to_string(), tx);
+
+        let msg = protocol::serialize_subscribe(0, topic)?;
+        if let Err(e) = self.ring_buffer.try_write(&msg) {
+            self.subscriptions.lock().unwrap().remove(topic);
+            return Err(e);
+        }
+
+        Ok(SubscriptionStream {
+            topic: topic.to_string(),
+            rx,
+            ring_buffer: std::sync::Arc::clone(&self.ring_buffer),
+            subscriptions: std::sync::Arc::clone(&self.subscriptions),
         })
     }
 
@@ -90,16 +270,110 @@ impl IpcClient {
         Ok(call_id)
     }
 
+    /// Send an MSF method call and await its specific response, correlated
+    /// by call id instead of requiring the caller to poll `try_recv` and
+    /// match ids by hand. Waits up to `DEFAULT_CALL_TIMEOUT` - see
+    /// `call_async_timeout` for an explicit deadline.
+    pub async fn call_async(&self, method: &str, args: Vec<serde_json::Value>) -> Result<serde_json::Value> {
+        self.call_async_timeout(method, args, DEFAULT_CALL_TIMEOUT).await
+    }
+
+    /// Like `call_async`, but with an explicit `timeout` instead of
+    /// `DEFAULT_CALL_TIMEOUT`. Whether the timeout elapses or the response
+    /// arrives, the pending entry is always removed - a response that
+    /// shows up after the deadline is dropped by `reader_loop` rather than
+    /// finding a sender that's been `await`ed away.
+    pub async fn call_async_timeout(
+        &self,
+        method: &str,
+        args: Vec<serde_json::Value>,
+        timeout: std::time::Duration,
+    ) -> Result<serde_json::Value> {
+        let call_id = self.next_call_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.pending.lock().unwrap().insert(call_id, tx);
+
+        let msg = protocol::serialize_call(call_id, method, args)?;
+        if let Err(e) = self.ring_buffer.try_write(&msg) {
+            self.pending.lock().unwrap().remove(&call_id);
+            return Err(e);
+        }
+
+        let response = match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(bytes)) => bytes,
+            _ => {
+                self.pending.lock().unwrap().remove(&call_id);
+                return Err(IpcError::Timeout(timeout.as_millis() as u64));
+            }
+        };
+
+        protocol::deserialize_response(&response).map(|(_call_id, result)| result)
+    }
+
     /// Try to read a response (non-blocking)
     pub fn try_recv(&self) -> Result<Vec<u8>> {
         let data = self.ring_buffer.try_read()?;
         Ok(data.to_vec())
     }
 
+    /// Like `try_recv`, but blocks up to `timeout` instead of failing
+    /// immediately when nothing is available, parking on the ring
+    /// buffer's futex rather than busy-polling. See
+    /// `RingBuffer::read_blocking` for the spin/park strategy.
+    pub fn recv_timeout(&self, timeout: std::time::Duration) -> Result<Vec<u8>> {
+        self.ring_buffer.read_blocking(timeout)
+    }
+
+    /// `recv_timeout` with no deadline - blocks until a frame arrives.
+    pub fn recv(&self) -> Result<Vec<u8>> {
+        self.recv_timeout(std::time::Duration::MAX)
+    }
+
     /// Get buffer utilization
     pub fn utilization(&self) -> f64 {
         self.ring_buffer.utilization()
     }
+
+    /// Whether the consumer (e.g. `assassinate_daemon`) has stamped its
+    /// heartbeat within `max_staleness`. `call`/`try_write` already use
+    /// `ring_buffer::DEFAULT_HEARTBEAT_STALENESS` internally to turn a full
+    /// buffer into `IpcError::ConsumerDead` - this is for callers that want
+    /// to check proactively, e.g. before reconnecting.
+    pub fn consumer_alive(&self, max_staleness: std::time::Duration) -> bool {
+        self.ring_buffer.consumer_alive(max_staleness)
+    }
+}
+
+/// A live subscription created by `IpcClient::subscribe`. Yields each
+/// notification payload published for its topic, in the order the
+/// background reader saw them. Dropping it sends an `__unsubscribe`
+/// control call and deregisters the topic so the reader stops forwarding
+/// to it.
+pub struct SubscriptionStream {
+    topic: String,
+    rx: tokio::sync::mpsc::UnboundedReceiver<serde_json::Value>,
+    ring_buffer: std::sync::Arc<RingBuffer>,
+    subscriptions: Subscriptions,
+}
+
+impl futures::Stream for SubscriptionStream {
+    type Item = serde_json::Value;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.get_mut().rx.poll_recv(cx)
+    }
+}
+
+impl Drop for SubscriptionStream {
+    fn drop(&mut self) {
+        self.subscriptions.lock().unwrap().remove(&self.topic);
+        if let Ok(msg) = protocol::serialize_unsubscribe(0, &self.topic) {
+            let _ = self.ring_buffer.try_write(&msg);
+        }
+    }
 }
 
 #[cfg(test)]
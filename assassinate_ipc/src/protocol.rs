@@ -5,6 +5,96 @@ use crate::msf_capnp;
 use capnp::message::{Builder, ReaderOptions};
 use capnp::serialize;
 
+/// Fill in a Cap'n Proto `Value` union from a `serde_json::Value`,
+/// recursing into `Array`/`Object` so nested JSON round-trips intact.
+/// `Number` splits on `is_i64` so whole numbers survive as `int64` instead
+/// of losing precision through a `float64` round-trip.
+fn json_to_capnp(mut builder: msf_capnp::value::Builder<'_>, value: &serde_json::Value) -> Result<()> {
+    match value {
+        serde_json::Value::Null => builder.set_void(()),
+        serde_json::Value::Bool(b) => builder.set_bool(*b),
+        serde_json::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                builder.set_int64(i);
+            } else if let Some(f) = n.as_f64() {
+                builder.set_float64(f);
+            } else {
+                return Err(IpcError::Serialization(format!("number out of range: {}", n)));
+            }
+        }
+        serde_json::Value::String(s) => builder.set_text(s),
+        serde_json::Value::Array(items) => {
+            let mut list = builder.init_list(items.len() as u32);
+            for (i, item) in items.iter().enumerate() {
+                json_to_capnp(list.reborrow().get(i as u32), item)?;
+            }
+        }
+        serde_json::Value::Object(map) => {
+            let mut entries = builder.init_map(map.len() as u32);
+            for (i, (key, val)) in map.iter().enumerate() {
+                let mut entry = entries.reborrow().get(i as u32);
+                entry.set_key(key);
+                json_to_capnp(entry.init_value(), val)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Inverse of `json_to_capnp`: read whichever `Value` union member is set
+/// and rebuild the equivalent `serde_json::Value`, recursing for `list`/
+/// `map`. `data` has no `serde_json::Value` counterpart (our own encoder
+/// never produces it) but is still decoded, as a JSON array of byte
+/// values, so a peer that does send raw bytes doesn't get dropped.
+fn capnp_to_json(reader: msf_capnp::value::Reader<'_>) -> Result<serde_json::Value> {
+    use msf_capnp::value::Which;
+
+    let json = match reader.which().map_err(|e| IpcError::Deserialization(e.to_string()))? {
+        Which::Void(()) => serde_json::Value::Null,
+        Which::Bool(b) => serde_json::Value::Bool(b),
+        Which::Int64(i) => serde_json::Value::Number(i.into()),
+        Which::Float64(f) => serde_json::Number::from_f64(f)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        Which::Text(t) => {
+            let t = t.map_err(|e| IpcError::Deserialization(e.to_string()))?;
+            serde_json::Value::String(t.to_string().map_err(|e| IpcError::Deserialization(e.to_string()))?)
+        }
+        Which::Data(d) => {
+            let d = d.map_err(|e| IpcError::Deserialization(e.to_string()))?;
+            serde_json::Value::Array(d.iter().map(|b| serde_json::Value::Number((*b).into())).collect())
+        }
+        Which::List(items) => {
+            let items = items.map_err(|e| IpcError::Deserialization(e.to_string()))?;
+            let mut out = Vec::with_capacity(items.len() as usize);
+            for item in items.iter() {
+                out.push(capnp_to_json(item)?);
+            }
+            serde_json::Value::Array(out)
+        }
+        Which::Map(entries) => {
+            let entries = entries.map_err(|e| IpcError::Deserialization(e.to_string()))?;
+            let mut out = serde_json::Map::with_capacity(entries.len() as usize);
+            for entry in entries.iter() {
+                let key = entry
+                    .get_key()
+                    .map_err(|e| IpcError::Deserialization(e.to_string()))?
+                    .to_string()
+                    .map_err(|e| IpcError::Deserialization(e.to_string()))?;
+                let value = capnp_to_json(
+                    entry
+                        .get_value()
+                        .map_err(|e| IpcError::Deserialization(e.to_string()))?,
+                )?;
+                out.insert(key, value);
+            }
+            serde_json::Value::Object(out)
+        }
+    };
+
+    Ok(json)
+}
+
 /// Serialize an MSF call to bytes using Cap'n Proto
 pub fn serialize_call(
     call_id: u64,
@@ -20,9 +110,10 @@ pub fn serialize_call(
     let mut request = call.init_request();
     request.set_method(method);
 
-    // TODO: Convert args to Cap'n Proto Value list
-    // For now, just create empty list
-    let _args_list = request.init_args(args.len() as u32);
+    let mut args_list = request.init_args(args.len() as u32);
+    for (i, arg) in args.iter().enumerate() {
+        json_to_capnp(args_list.reborrow().get(i as u32), arg)?;
+    }
 
     // Serialize to bytes
     let mut buf = Vec::new();
@@ -53,8 +144,12 @@ pub fn deserialize_call(data: &[u8]) -> Result<(u64, String, Vec<serde_json::Val
                 .to_string()
                 .map_err(|e| IpcError::Deserialization(e.to_string()))?;
 
-            // TODO: Convert Cap'n Proto args to serde_json::Value
-            let args = Vec::new();
+            let args = request
+                .get_args()
+                .map_err(|e| IpcError::Deserialization(e.to_string()))?
+                .iter()
+                .map(capnp_to_json)
+                .collect::<Result<Vec<_>>>()?;
 
             Ok((call_id, method, args))
         }
@@ -64,6 +159,151 @@ pub fn deserialize_call(data: &[u8]) -> Result<(u64, String, Vec<serde_json::Val
         Ok(msf_capnp::msf_call::Which::Error(_)) => {
             Err(IpcError::Deserialization("Expected request, got error".to_string()))
         }
+        Ok(msf_capnp::msf_call::Which::Notification(_)) => {
+            Err(IpcError::Deserialization("Expected request, got notification".to_string()))
+        }
+        Err(e) => Err(IpcError::Deserialization(format!("Unknown message type: {:?}", e))),
+    }
+}
+
+/// Read just the call id out of a serialized `MsfCall` frame, without
+/// decoding the `Request`/`Response`/`Error` union it carries - the
+/// background reader in `IpcClient::call_async` needs only this to route a
+/// frame to the pending call waiting on it.
+pub fn peek_call_id(data: &[u8]) -> Result<u64> {
+    let message = serialize::read_message(data, ReaderOptions::default())
+        .map_err(|e| IpcError::Deserialization(e.to_string()))?;
+
+    let call = message
+        .get_root::<msf_capnp::msf_call::Reader>()
+        .map_err(|e| IpcError::Deserialization(e.to_string()))?;
+
+    Ok(call.get_call_id())
+}
+
+/// Decode a `Response`/`Error` frame into `(call_id, result)` - the
+/// consumer-side counterpart to `serialize_response`/`serialize_error`,
+/// used once `peek_call_id` has routed a frame to the right pending call.
+/// An `Error` frame decodes to `Err`, carrying its code and message.
+pub fn deserialize_response(data: &[u8]) -> Result<(u64, serde_json::Value)> {
+    let message = serialize::read_message(data, ReaderOptions::default())
+        .map_err(|e| IpcError::Deserialization(e.to_string()))?;
+
+    let call = message
+        .get_root::<msf_capnp::msf_call::Reader>()
+        .map_err(|e| IpcError::Deserialization(e.to_string()))?;
+
+    let call_id = call.get_call_id();
+
+    match call.which() {
+        Ok(msf_capnp::msf_call::Which::Response(response)) => {
+            let response = response.map_err(|e| IpcError::Deserialization(e.to_string()))?;
+            let result = capnp_to_json(
+                response
+                    .get_result()
+                    .map_err(|e| IpcError::Deserialization(e.to_string()))?,
+            )?;
+            Ok((call_id, result))
+        }
+        Ok(msf_capnp::msf_call::Which::Error(error)) => {
+            let error = error.map_err(|e| IpcError::Deserialization(e.to_string()))?;
+            let code = error
+                .get_code()
+                .map_err(|e| IpcError::Deserialization(e.to_string()))?
+                .to_string()
+                .map_err(|e| IpcError::Deserialization(e.to_string()))?;
+            let message = error
+                .get_message()
+                .map_err(|e| IpcError::Deserialization(e.to_string()))?
+                .to_string()
+                .map_err(|e| IpcError::Deserialization(e.to_string()))?;
+            Err(IpcError::Deserialization(format!("{}: {}", code, message)))
+        }
+        Ok(msf_capnp::msf_call::Which::Request(_)) => {
+            Err(IpcError::Deserialization("Expected response, got request".to_string()))
+        }
+        Ok(msf_capnp::msf_call::Which::Notification(_)) => {
+            Err(IpcError::Deserialization("Expected response, got notification".to_string()))
+        }
+        Err(e) => Err(IpcError::Deserialization(format!("Unknown message type: {:?}", e))),
+    }
+}
+
+/// Serialize a `__subscribe` control call: an ordinary `Request` the
+/// daemon dispatches by method name, carrying `topic` as its sole arg.
+/// `call_id` doesn't correlate to a response the way `serialize_call`'s
+/// does - subscriptions are identified by `topic`, not call id - so
+/// callers that don't need one can pass `0`.
+pub fn serialize_subscribe(call_id: u64, topic: &str) -> Result<Vec<u8>> {
+    serialize_call(call_id, "__subscribe", vec![serde_json::Value::String(topic.to_string())])
+}
+
+/// The `__unsubscribe` counterpart to `serialize_subscribe`, sent when a
+/// `SubscriptionStream` is dropped.
+pub fn serialize_unsubscribe(call_id: u64, topic: &str) -> Result<Vec<u8>> {
+    serialize_call(call_id, "__unsubscribe", vec![serde_json::Value::String(topic.to_string())])
+}
+
+/// Serialize a server-pushed event for `topic` - the daemon's side of a
+/// subscription, read back by a client's `IpcClient::subscribe` stream via
+/// `decode_notification`.
+pub fn serialize_notification(topic: &str, payload: serde_json::Value) -> Result<Vec<u8>> {
+    let mut message = Builder::new_default();
+    let mut call = message.init_root::<msf_capnp::msf_call::Builder>();
+
+    call.set_call_id(0);
+
+    let mut notification = call.init_notification();
+    notification.set_topic(topic);
+    json_to_capnp(notification.reborrow().init_payload(), &payload)?;
+
+    let mut buf = Vec::new();
+    serialize::write_message(&mut buf, &message)
+        .map_err(|e| IpcError::Serialization(e.to_string()))?;
+
+    Ok(buf)
+}
+
+/// Whether a serialized `MsfCall` frame is a `Notification` - the
+/// background reader in `IpcClient` checks this before deciding whether to
+/// route a frame to a pending call (`deserialize_response`) or a
+/// subscription stream (`decode_notification`).
+pub fn is_notification(data: &[u8]) -> Result<bool> {
+    let message = serialize::read_message(data, ReaderOptions::default())
+        .map_err(|e| IpcError::Deserialization(e.to_string()))?;
+
+    let call = message
+        .get_root::<msf_capnp::msf_call::Reader>()
+        .map_err(|e| IpcError::Deserialization(e.to_string()))?;
+
+    Ok(matches!(call.which(), Ok(msf_capnp::msf_call::Which::Notification(_))))
+}
+
+/// Decode a `Notification` frame into `(topic, payload)`.
+pub fn decode_notification(data: &[u8]) -> Result<(String, serde_json::Value)> {
+    let message = serialize::read_message(data, ReaderOptions::default())
+        .map_err(|e| IpcError::Deserialization(e.to_string()))?;
+
+    let call = message
+        .get_root::<msf_capnp::msf_call::Reader>()
+        .map_err(|e| IpcError::Deserialization(e.to_string()))?;
+
+    match call.which() {
+        Ok(msf_capnp::msf_call::Which::Notification(notification)) => {
+            let notification = notification.map_err(|e| IpcError::Deserialization(e.to_string()))?;
+            let topic = notification
+                .get_topic()
+                .map_err(|e| IpcError::Deserialization(e.to_string()))?
+                .to_string()
+                .map_err(|e| IpcError::Deserialization(e.to_string()))?;
+            let payload = capnp_to_json(
+                notification
+                    .get_payload()
+                    .map_err(|e| IpcError::Deserialization(e.to_string()))?,
+            )?;
+            Ok((topic, payload))
+        }
+        Ok(_) => Err(IpcError::Deserialization("Expected notification".to_string())),
         Err(e) => Err(IpcError::Deserialization(format!("Unknown message type: {:?}", e))),
     }
 }
@@ -76,8 +316,8 @@ pub fn serialize_response(call_id: u64, result: serde_json::Value) -> Result<Vec
     call.set_call_id(call_id);
 
     // Build response
-    let _response = call.init_response();
-    // TODO: Convert result to Cap'n Proto Value
+    let mut response = call.init_response();
+    json_to_capnp(response.reborrow().init_result(), &result)?;
 
     // Serialize to bytes
     let mut buf = Vec::new();
@@ -124,4 +364,22 @@ mod tests {
         assert_eq!(parsed_method, method);
         assert_eq!(parsed_args, args);
     }
+
+    #[test]
+    fn test_nested_args_round_trip() {
+        let args = vec![
+            serde_json::json!(null),
+            serde_json::json!(true),
+            serde_json::json!(-7),
+            serde_json::json!(3.5),
+            serde_json::json!("hello"),
+            serde_json::json!([1, "two", [3, false]]),
+            serde_json::json!({"a": 1, "b": {"c": [2, 3]}}),
+        ];
+
+        let bytes = serialize_call(1, "set_option", args.clone()).unwrap();
+        let (_call_id, _method, parsed_args) = deserialize_call(&bytes).unwrap();
+
+        assert_eq!(parsed_args, args);
+    }
 }
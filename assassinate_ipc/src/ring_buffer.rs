@@ -0,0 +1,619 @@
+//! Lock-free MPSC (multi-producer/single-consumer) ring buffer laid out
+//! inside a `SharedMemory` region - the zero-syscall transport the
+//! `protocol` module's Cap'n Proto frames ride over instead of being
+//! re-serialized into a fresh `Vec<u8>` per call.
+//!
+//! # Layout
+//! ```text
+//! [write_pos: AtomicU64][pad to 64 bytes][read_pos: AtomicU64][pad to 64 bytes]
+//! [consumer_heartbeat: AtomicU64][pad][creator_hello: AtomicU64][pad][opener_hello: AtomicU64][pad]
+//! [data: capacity bytes]
+//! ```
+//! Each header slot gets its own 64-byte cache line, so the data area
+//! starts at `DATA_OFFSET` (5 cache lines in). `write_pos` and `read_pos`
+//! each get their own 64-byte cache line so a
+//! producer updating one and the consumer updating the other never
+//! invalidate each other's cache line (false sharing). Both are
+//! free-running `u64` counters - they only ever increase, and are masked
+//! to `capacity` (rounded up to a power of two) on every access, so
+//! wraparound is `& (capacity - 1)` instead of a modulo branch.
+//!
+//! # Frame format (Aeron-style)
+//! Every frame is `[length: u32][msg_type: u32][payload][padding to align]`,
+//! with the whole frame rounded up to a 64-byte (`CACHE_LINE`) boundary so
+//! frames never straddle each other's cache lines either. `length` is the
+//! *payload's* byte length, written last with `Release` ordering once the
+//! rest of the frame is in place - `length == 0` means "claimed but not
+//! yet committed," so a consumer that catches up to an in-flight write
+//! just sees an empty buffer rather than a torn frame.
+//!
+//! A producer reserves room with a `compare_exchange` loop on `write_pos`
+//! rather than a plain `fetch_add`, because a claim that would straddle
+//! the end of the data area needs special handling: it instead claims just
+//! the remainder of the buffer, stamps it as a `MSG_TYPE_PADDING` frame
+//! whose length covers that whole remainder, and loops back around to claim the real frame from offset 0. This
+//! is what lets any number of producers share one buffer safely - each
+//! walks away with a disjoint `[start, start + frame_len)` byte range to
+//! write into before anyone touches `write_pos` again, and frames never
+//! wrap mid-payload, so there's no split-copy path to worry about on
+//! either the write or read side.
+//!
+//! The consumer side remains single-reader: `read_pos` is only ever
+//! advanced by whichever one thread calls `try_read`/`read_blocking`, so
+//! no further coordination is needed there.
+//!
+//! `write_pos`/`read_pos` are read through raw pointers into the mapped
+//! region rather than `SharedMemory::as_slice_mut`, which takes `&mut
+//! self` - meaningless here since producers and the consumer are
+//! different processes, each holding its own `SharedMemory` over the same
+//! region, and all of them need concurrent shared access guarded only by
+//! the atomics themselves.
+
+use crate::error::{IpcError, Result};
+use crate::shm::SharedMemory;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+
+/// A cross-process futex parked on `write_pos`, so `read_blocking` can
+/// block without spinning and `try_write` can wake it the instant new data
+/// lands. `write_pos` lives in the shared segment itself, so the futex
+/// word is visible to both processes - unlike `std::thread::park`/
+/// `Condvar`, which only coordinate threads within one process.
+///
+/// `SYS_futex` operates on a 32-bit word; `write_pos` is an `AtomicU64`.
+/// The low 32 bits of a little-endian `u64` live at the same starting
+/// address as the `u64` itself, so `word_ptr` just reinterprets that
+/// prefix rather than threading a separate futex word through the header.
+/// A write that wraps past `u32::MAX` could in principle make a waiter's
+/// stale `expected` collide with a newer value, but that only costs a
+/// spurious wake, never a missed one - `read_blocking` always re-checks
+/// `write_pos` vs. `read_pos` itself after returning from the wait.
+#[cfg(target_os = "linux")]
+mod futex {
+    use std::sync::atomic::AtomicU64;
+    use std::time::Duration;
+
+    fn word_ptr(write_pos: *const AtomicU64) -> *mut u32 {
+        write_pos as *mut u32
+    }
+
+    /// Block until `*word_ptr` no longer equals `expected`, a spurious
+    /// wake occurs, or `timeout` elapses - whichever is first. The caller
+    /// is always expected to re-check the real condition afterward.
+    pub fn wait(write_pos: *const AtomicU64, expected: u32, timeout: Duration) {
+        let ts = libc::timespec {
+            tv_sec: timeout.as_secs() as libc::time_t,
+            tv_nsec: timeout.subsec_nanos() as i64,
+        };
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                word_ptr(write_pos),
+                libc::FUTEX_WAIT,
+                expected,
+                &ts as *const libc::timespec,
+                std::ptr::null::<u32>(),
+                0i32,
+            );
+        }
+        // The return value is ignored: EAGAIN (value already changed),
+        // EINTR, and a timeout are all indistinguishable from a legitimate
+        // wake at this level, and `read_blocking` handles all of them the
+        // same way - loop back around and re-check.
+    }
+
+    /// Wake every reader parked on `write_pos`, called right after a
+    /// successful `try_write` makes new data visible.
+    pub fn wake(write_pos: *const AtomicU64) {
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                word_ptr(write_pos),
+                libc::FUTEX_WAKE,
+                i32::MAX,
+                std::ptr::null::<libc::timespec>(),
+                std::ptr::null::<u32>(),
+                0i32,
+            );
+        }
+    }
+}
+
+const CACHE_LINE: usize = 64;
+const WRITE_POS_OFFSET: usize = 0;
+const READ_POS_OFFSET: usize = CACHE_LINE;
+/// Monotonic-ish timestamp (ms since `UNIX_EPOCH`) the consumer stamps on
+/// every drain loop iteration, so a stuck producer can tell "the buffer is
+/// full because the consumer is just behind" from "the buffer is full
+/// because the consumer is gone" - see `consumer_alive`.
+const CONSUMER_HEARTBEAT_OFFSET: usize = CACHE_LINE * 2;
+/// Handshake region: each side's `[protocol_version: u32][capabilities: u32]`
+/// packed into one `AtomicU64`, `0` meaning "not written yet" (see
+/// `handshake`). Two slots, one per role, since both sides map the same
+/// memory and each needs somewhere to publish its own Hello without
+/// clobbering the peer's.
+const CREATOR_HELLO_OFFSET: usize = CACHE_LINE * 3;
+const OPENER_HELLO_OFFSET: usize = CACHE_LINE * 4;
+const DATA_OFFSET: usize = CACHE_LINE * 5;
+
+/// Which side of a handshake this `RingBuffer` is: the one that called
+/// `create` (and so is listening for the `open`r's Hello), or the one that
+/// called `open` (listening for the `create`or's).
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Creator,
+    Opener,
+}
+
+/// Default staleness threshold `try_write` uses to decide a full buffer
+/// means a dead consumer rather than ordinary backpressure.
+pub const DEFAULT_HEARTBEAT_STALENESS: Duration = Duration::from_secs(5);
+
+/// `[length: u32][msg_type: u32]`, immediately followed by the payload.
+const HEADER_LEN: usize = 8;
+
+/// A filler frame a producer stamps over the unused tail of the buffer
+/// when the real frame it wants to write would straddle the end - the
+/// consumer skips it and moves on to offset 0.
+const MSG_TYPE_PADDING: u32 = 0;
+/// An ordinary payload frame.
+const MSG_TYPE_DATA: u32 = 1;
+
+/// Round `len` up to the next multiple of `CACHE_LINE`.
+fn align_up(len: usize) -> usize {
+    (len + CACHE_LINE - 1) & !(CACHE_LINE - 1)
+}
+
+/// A ring buffer segment. Any number of producers may call `try_write`
+/// concurrently; only one consumer may call `try_read`/`read_blocking` at
+/// a time.
+pub struct RingBuffer {
+    shm: SharedMemory,
+    capacity: usize,
+    role: Role,
+}
+
+impl RingBuffer {
+    /// Create a new ring buffer backed by a fresh `SharedMemory` region.
+    /// `capacity` is rounded up to the next power of two so index
+    /// wraparound can be a bitmask instead of a modulo; since it's also
+    /// rounded up to at least one `CACHE_LINE`, every aligned frame offset
+    /// this produces is itself cache-line aligned.
+    pub fn create(name: &str, capacity: usize) -> Result<Self> {
+        let capacity = capacity.next_power_of_two().max(CACHE_LINE);
+        let shm = SharedMemory::create(name, DATA_OFFSET + capacity)?;
+        let rb = Self { shm, capacity, role: Role::Creator };
+        rb.write_pos().store(0, Ordering::Relaxed);
+        rb.read_pos().store(0, Ordering::Relaxed);
+        rb.heartbeat().store(0, Ordering::Relaxed);
+        rb.my_hello().store(0, Ordering::Relaxed);
+        rb.peer_hello().store(0, Ordering::Relaxed);
+        Ok(rb)
+    }
+
+    /// Open an existing ring buffer segment created by `create` elsewhere,
+    /// via `SharedMemory::open`. On Linux that always fails, since a memfd
+    /// cannot be looked up by name.
+    pub fn open(name: &str, capacity: usize) -> Result<Self> {
+        let capacity = capacity.next_power_of_two().max(CACHE_LINE);
+        let shm = SharedMemory::open(name, DATA_OFFSET + capacity)?;
+        Ok(Self { shm, capacity, role: Role::Opener })
+    }
+
+    fn write_pos(&self) -> &AtomicU64 {
+        unsafe { &*(self.shm.as_ptr().add(WRITE_POS_OFFSET) as *const AtomicU64) }
+    }
+
+    fn read_pos(&self) -> &AtomicU64 {
+        unsafe { &*(self.shm.as_ptr().add(READ_POS_OFFSET) as *const AtomicU64) }
+    }
+
+    fn heartbeat(&self) -> &AtomicU64 {
+        unsafe { &*(self.shm.as_ptr().add(CONSUMER_HEARTBEAT_OFFSET) as *const AtomicU64) }
+    }
+
+    /// This side's handshake slot - where we publish our own Hello.
+    fn my_hello(&self) -> &AtomicU64 {
+        let offset = match self.role {
+            Role::Creator => CREATOR_HELLO_OFFSET,
+            Role::Opener => OPENER_HELLO_OFFSET,
+        };
+        unsafe { &*(self.shm.as_ptr().add(offset) as *const AtomicU64) }
+    }
+
+    /// The other side's handshake slot - where we read their Hello back
+    /// from.
+    fn peer_hello(&self) -> &AtomicU64 {
+        let offset = match self.role {
+            Role::Creator => OPENER_HELLO_OFFSET,
+            Role::Opener => CREATOR_HELLO_OFFSET,
+        };
+        unsafe { &*(self.shm.as_ptr().add(offset) as *const AtomicU64) }
+    }
+
+    /// Perform the protocol handshake: publish our protocol version and
+    /// `our_capabilities` bitset as a Hello in our handshake slot, then
+    /// wait up to `timeout` for the peer's Hello to appear in theirs.
+    /// Fails fast with `IpcError::VersionMismatch` if the peer's major
+    /// version differs from ours - the two sides would otherwise
+    /// silently disagree about frame layout - and with `IpcError::Timeout`
+    /// if no Hello shows up at all. On success, returns
+    /// `(peer_protocol_version, our_capabilities & their_capabilities)` -
+    /// the negotiated capability set is the intersection, since a feature
+    /// is only safe to use if both ends understand it.
+    pub fn handshake(&self, our_capabilities: u32, timeout: Duration) -> Result<(u32, u32)> {
+        let hello = ((crate::PROTOCOL_VERSION as u64) << 32) | our_capabilities as u64;
+        self.my_hello().store(hello, Ordering::Release);
+
+        let deadline = std::time::Instant::now().checked_add(timeout);
+        loop {
+            let peer_hello = self.peer_hello().load(Ordering::Acquire);
+            if peer_hello != 0 {
+                let peer_version = (peer_hello >> 32) as u32;
+                let peer_capabilities = peer_hello as u32;
+                if peer_version != crate::PROTOCOL_VERSION {
+                    return Err(IpcError::VersionMismatch {
+                        ours: crate::PROTOCOL_VERSION,
+                        theirs: peer_version,
+                    });
+                }
+                return Ok((peer_version, our_capabilities & peer_capabilities));
+            }
+
+            let past_deadline = matches!(deadline, Some(deadline) if std::time::Instant::now() >= deadline);
+            if past_deadline {
+                return Err(IpcError::Timeout(timeout.as_millis() as u64));
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Stamp the consumer heartbeat with the current time. The consumer
+    /// calls this once per drain loop iteration (e.g. around each
+    /// `try_read`/batch of reads) so producers calling `consumer_alive`
+    /// can tell it's still making progress.
+    pub fn stamp_heartbeat(&self) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        self.heartbeat().store(now, Ordering::Relaxed);
+    }
+
+    /// Whether the consumer has stamped a heartbeat within `max_staleness`.
+    /// A heartbeat of `0` (never stamped) is treated as "alive" rather than
+    /// "dead" - the consumer may simply not have started its drain loop
+    /// yet, which isn't the scenario this is meant to catch.
+    pub fn consumer_alive(&self, max_staleness: Duration) -> bool {
+        let last = self.heartbeat().load(Ordering::Relaxed);
+        if last == 0 {
+            return true;
+        }
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        now.saturating_sub(last) <= max_staleness.as_millis() as u64
+    }
+
+    fn data(&self) -> *mut u8 {
+        unsafe { self.shm.as_ptr().add(DATA_OFFSET) }
+    }
+
+    fn mask(&self, index: u64) -> usize {
+        (index as usize) & (self.capacity - 1)
+    }
+
+    /// The frame's length slot - the `Release`/`Acquire` publish flag
+    /// between producer and consumer. `0` means "not yet committed."
+    fn length_slot(&self, offset: usize) -> &AtomicU32 {
+        unsafe { &*(self.data().add(offset) as *const AtomicU32) }
+    }
+
+    fn msg_type_ptr(&self, offset: usize) -> *mut u32 {
+        unsafe { self.data().add(offset + 4) as *mut u32 }
+    }
+
+    fn body_ptr(&self, offset: usize) -> *mut u8 {
+        unsafe { self.data().add(offset + HEADER_LEN) }
+    }
+
+    /// Reserve `claim_len` bytes starting at free-running index `tail`,
+    /// spinning until the consumer has freed enough room - i.e. until the
+    /// claim wouldn't advance `write_pos` past `read_pos + capacity` -
+    /// or giving up with `IpcError::ConsumerDead` if the buffer stays full
+    /// long enough that the consumer's heartbeat goes stale, rather than
+    /// spinning forever against a daemon that's never coming back.
+    fn wait_for_room(&self, tail: u64, claim_len: u64) -> Result<()> {
+        while tail + claim_len - self.read_pos().load(Ordering::Acquire) > self.capacity as u64 {
+            if !self.consumer_alive(DEFAULT_HEARTBEAT_STALENESS) {
+                return Err(IpcError::ConsumerDead);
+            }
+            std::hint::spin_loop();
+        }
+        Ok(())
+    }
+
+    /// Claim `frame_len` contiguous bytes for a frame, inserting a padding
+    /// frame and retrying from offset 0 if the claim would straddle the
+    /// end of the data area. Returns the byte offset to write the frame
+    /// at. See the module docs for why this makes multiple concurrent
+    /// producers safe.
+    fn claim(&self, frame_len: usize) -> Result<usize> {
+        loop {
+            let tail = self.write_pos().load(Ordering::Relaxed);
+            let offset = self.mask(tail);
+            let remaining_to_wrap = self.capacity - offset;
+
+            let claim_len = if frame_len > remaining_to_wrap {
+                remaining_to_wrap
+            } else {
+                frame_len
+            };
+
+            self.wait_for_room(tail, claim_len as u64)?;
+
+            if self
+                .write_pos()
+                .compare_exchange(tail, tail + claim_len as u64, Ordering::Relaxed, Ordering::Relaxed)
+                .is_err()
+            {
+                // Another producer claimed first; reload write_pos and try
+                // again.
+                continue;
+            }
+
+            if claim_len != frame_len {
+                // We claimed the remainder of the buffer as padding, not
+                // the frame we actually want. Stamp it and loop back
+                // around to claim the real frame, which will now start at
+                // offset 0.
+                self.commit(offset, MSG_TYPE_PADDING, (claim_len - HEADER_LEN) as u32);
+                continue;
+            }
+
+            return Ok(offset);
+        }
+    }
+
+    /// Write `msg_type` and `len` into a claimed frame's header, `len`
+    /// last and with `Release` ordering so a consumer that `Acquire`-loads
+    /// a nonzero length is guaranteed to see everything written before it
+    /// - the msg_type and, for `try_write`, the payload bytes.
+    fn commit(&self, offset: usize, msg_type: u32, len: u32) {
+        unsafe { std::ptr::write(self.msg_type_ptr(offset), msg_type) };
+        self.length_slot(offset).store(len, Ordering::Release);
+    }
+
+    /// Push `payload` onto the ring as a `MSG_TYPE_DATA` frame.
+    pub fn try_write(&self, payload: &[u8]) -> Result<()> {
+        let frame_len = align_up(HEADER_LEN + payload.len());
+        if frame_len > self.capacity {
+            return Err(IpcError::RingBufferFull(self.capacity));
+        }
+
+        let offset = self.claim(frame_len)?;
+        unsafe {
+            std::ptr::copy_nonoverlapping(payload.as_ptr(), self.body_ptr(offset), payload.len());
+        }
+        self.commit(offset, MSG_TYPE_DATA, payload.len() as u32);
+
+        #[cfg(target_os = "linux")]
+        futex::wake(self.write_pos() as *const AtomicU64);
+        Ok(())
+    }
+
+    /// Whether `write_pos` has moved past `read_pos`, without reading. The
+    /// head frame may still be claimed but not yet committed, in which case
+    /// `try_read` still returns `RingBufferEmpty`.
+    fn has_data(&self) -> bool {
+        self.read_pos().load(Ordering::Relaxed) != self.write_pos().load(Ordering::Acquire)
+    }
+
+    /// Like `try_read`, but blocks up to `timeout` instead of failing
+    /// immediately when the buffer is empty.
+    ///
+    /// Starts with a few hundred iterations of plain spinning, since most
+    /// waits under real load are resolved within a handful of nanoseconds
+    /// by a producer that's already mid-write - parking costs a syscall
+    /// round trip in both directions and would dominate that case. Only
+    /// once the spin comes up empty does it actually park: on Linux, via
+    /// `futex::wait` on `write_pos` (woken by `try_write`'s `futex::wake`);
+    /// elsewhere, via a short sleep, since there's no portable futex to
+    /// rely on. Either way it re-checks `has_data()` itself on every lap
+    /// rather than trusting the wake to mean "there's data now" - spurious
+    /// wakes (and the non-Linux sleep, which is never woken at all) are
+    /// handled by just looping back around against the same deadline.
+    pub fn read_blocking(&self, timeout: Duration) -> Result<Vec<u8>> {
+        const SPIN_ITERS: u32 = 256;
+
+        for _ in 0..SPIN_ITERS {
+            if self.has_data() {
+                match self.try_read() {
+                    Err(IpcError::RingBufferEmpty) => {}
+                    result => return result,
+                }
+            }
+            std::hint::spin_loop();
+        }
+
+        // `timeout` may be `Duration::MAX` (`IpcClient::recv`'s "block
+        // forever"), which `Instant::now() + timeout` would overflow on -
+        // a missing deadline just means "park repeatedly with no timeout
+        // check" instead.
+        let deadline = std::time::Instant::now().checked_add(timeout);
+        loop {
+            if self.has_data() {
+                // `has_data()` only tells us a producer has bumped
+                // `write_pos` - the frame at the head may still be
+                // claimed-but-not-yet-committed (`try_read` returns
+                // `RingBufferEmpty` for that), so don't let that escape as
+                // "nothing arrived" - keep waiting against the deadline
+                // instead.
+                match self.try_read() {
+                    Err(IpcError::RingBufferEmpty) => {}
+                    result => return result,
+                }
+            }
+
+            let remaining = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(std::time::Instant::now()) {
+                    Some(remaining) if !remaining.is_zero() => remaining,
+                    _ => return Err(IpcError::Timeout(timeout.as_millis() as u64)),
+                },
+                None => Duration::from_secs(1),
+            };
+
+            self.park(remaining);
+        }
+    }
+
+    /// Block the calling thread until woken by a `try_write` or `remaining`
+    /// elapses, whichever comes first. Never mistaken for "data arrived" by
+    /// itself - `read_blocking` always re-checks `has_data()` after it
+    /// returns.
+    #[cfg(target_os = "linux")]
+    fn park(&self, remaining: Duration) {
+        let expected = self.write_pos().load(Ordering::Relaxed) as u32;
+        futex::wait(self.write_pos() as *const AtomicU64, expected, remaining);
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn park(&self, remaining: Duration) {
+        std::thread::sleep(remaining.min(Duration::from_millis(1)));
+    }
+
+    /// Pop the next payload off the ring, walking past (and discarding)
+    /// any padding frames at the head. `Acquire`-loads each frame's length
+    /// slot so a nonzero read guarantees the msg_type and payload bytes
+    /// behind it are visible too, then `Release`-stores the advanced
+    /// `read_pos` so producers' next capacity check sees the freed space.
+    pub fn try_read(&self) -> Result<Vec<u8>> {
+        loop {
+            let read = self.read_pos().load(Ordering::Relaxed);
+            let write = self.write_pos().load(Ordering::Acquire);
+            if read == write {
+                return Err(IpcError::RingBufferEmpty);
+            }
+
+            let offset = self.mask(read);
+            let len = self.length_slot(offset).load(Ordering::Acquire);
+            if len == 0 {
+                // A producer has claimed this frame but hasn't committed
+                // it yet - nothing ready at the head for now.
+                return Err(IpcError::RingBufferEmpty);
+            }
+            let msg_type = unsafe { std::ptr::read(self.msg_type_ptr(offset)) };
+            let frame_len = align_up(HEADER_LEN + len as usize) as u64;
+
+            // Reset the length slot back to "uncommitted" before freeing the
+            // frame's bytes for reuse (i.e. before advancing `read_pos`) -
+            // otherwise, once the buffer wraps and a producer re-`claim`s
+            // this offset, its nonzero length from *this* lap would still be
+            // sitting there during the window between the new producer's
+            // `claim` (which advances `write_pos`) and its `commit`, and a
+            // consumer polling in that window would pass the uncommitted-
+            // frame guard above and tear a read off the new frame's stale
+            // header/body.
+            self.length_slot(offset).store(0, Ordering::Relaxed);
+            self.read_pos().store(read + frame_len, Ordering::Release);
+
+            if msg_type == MSG_TYPE_PADDING {
+                continue;
+            }
+
+            let mut payload = vec![0u8; len as usize];
+            unsafe {
+                std::ptr::copy_nonoverlapping(self.body_ptr(offset), payload.as_mut_ptr(), len as usize);
+            }
+            return Ok(payload);
+        }
+    }
+
+    /// Fraction of `capacity` currently occupied by unread data, in
+    /// `[0.0, 1.0]`.
+    pub fn utilization(&self) -> f64 {
+        // Sample `read` first: both counters advance concurrently with this
+        // call, so sampling `write` first could catch a `read` that's moved
+        // past it by the time we load it, underflowing `write - read`.
+        // Reading in this order still leaves a (harmless) window where
+        // `write` has since moved on, so `saturating_sub` covers what
+        // ordering alone can't.
+        let read = self.read_pos().load(Ordering::Acquire);
+        let write = self.write_pos().load(Ordering::Acquire);
+        write.saturating_sub(read) as f64 / self.capacity as f64
+    }
+}
+
+// RingBuffer is Send/Sync because producers only ever touch write_pos
+// (via a CAS loop) and their own disjoint claimed byte range, the
+// consumer only ever touches read_pos, and the Acquire/Release pair
+// around each frame's length slot is what makes the handoff between them
+// safe - the same invariant `SharedMemory` itself already relies on.
+unsafe impl Send for RingBuffer {}
+unsafe impl Sync for RingBuffer {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_write_read_round_trip() {
+        let rb = RingBuffer::create("test_ring_buffer", 4096).unwrap();
+        rb.try_write(b"hello").unwrap();
+        rb.try_write(b"world").unwrap();
+
+        assert_eq!(rb.try_read().unwrap(), b"hello");
+        assert_eq!(rb.try_read().unwrap(), b"world");
+        assert!(rb.try_read().is_err());
+    }
+
+    #[test]
+    fn test_wraparound() {
+        let rb = RingBuffer::create("test_ring_buffer_wrap", 256).unwrap();
+        for _ in 0..100 {
+            rb.try_write(b"0123456789").unwrap();
+            assert_eq!(rb.try_read().unwrap(), b"0123456789");
+        }
+    }
+
+    #[test]
+    fn test_multi_producer() {
+        let rb = Arc::new(RingBuffer::create("test_ring_buffer_mpsc", 64 * 1024).unwrap());
+        let writers: Vec<_> = (0..4u8)
+            .map(|id| {
+                let rb = Arc::clone(&rb);
+                std::thread::spawn(move || {
+                    for i in 0..50u32 {
+                        let msg = format!("{id}:{i}");
+                        while rb.try_write(msg.as_bytes()).is_err() {
+                            std::hint::spin_loop();
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let mut received = 0;
+        while received < 200 {
+            match rb.try_read() {
+                Ok(_) => received += 1,
+                Err(_) => std::hint::spin_loop(),
+            }
+        }
+        assert_eq!(received, 200);
+        assert!(rb.try_read().is_err());
+    }
+
+    #[test]
+    fn test_handshake_timeout_with_no_peer() {
+        let rb = RingBuffer::create("test_ring_buffer_handshake_timeout", 4096).unwrap();
+        let result = rb.handshake(crate::OUR_CAPABILITIES, Duration::from_millis(20));
+        assert!(matches!(result, Err(IpcError::Timeout(_))));
+    }
+}
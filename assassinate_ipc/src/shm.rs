@@ -1,17 +1,60 @@
 /// Shared memory management for ultra-low-latency IPC
 ///
 /// Uses memfd_create (Linux) for tmpfs-backed shared memory with zero syscall overhead.
-
 use crate::error::{IpcError, Result};
-use std::os::unix::io::AsRawFd;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
 use std::ptr;
 
+/// Memfd seal bits `SharedMemory::seal`/`create_sealed` can apply - one
+/// field per `F_SEAL_*` the kernel supports for this use case. Plain
+/// booleans rather than a `bitflags` type since there are only three and
+/// the crate has no existing `bitflags` dependency to reach for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SealFlags {
+    pub shrink: bool,
+    pub grow: bool,
+    pub write: bool,
+}
+
+impl SealFlags {
+    /// Size is fixed, but the region can still be written in place.
+    pub const SIZE_ONLY: SealFlags = SealFlags {
+        shrink: true,
+        grow: true,
+        write: false,
+    };
+
+    /// Fully immutable: no process, including the one that created it,
+    /// can resize or write the region again.
+    pub const IMMUTABLE: SealFlags = SealFlags {
+        shrink: true,
+        grow: true,
+        write: true,
+    };
+}
+
+/// Which kind of OS-backed region a `SharedMemory` wraps. Holding the real
+/// backing object - rather than a bare `Option<RawFd>` alongside a
+/// separately-scoped mapping handle - means `Drop` always knows exactly
+/// which cleanup path applies instead of guessing from which fields
+/// happen to be set.
+enum Backing {
+    /// Linux memfd, created locally or received via `recv_fd`. `Drop`
+    /// must `munmap` `SharedMemory::ptr` itself; the `OwnedFd` then closes
+    /// the descriptor when it drops in turn.
+    Memfd(OwnedFd),
+    /// `shared_memory`-crate-backed fallback elsewhere. The `Shmem` owns
+    /// its own mapping and unmaps it on drop, so `SharedMemory::drop`
+    /// must *not* also `munmap` `ptr` here - it's the same pointer.
+    #[cfg(not(target_os = "linux"))]
+    Posix(shared_memory::Shmem),
+}
+
 /// Shared memory region
 pub struct SharedMemory {
-    name: String,
     size: usize,
     ptr: *mut u8,
-    fd: Option<std::os::unix::io::RawFd>,
+    backing: Backing,
 }
 
 impl SharedMemory {
@@ -31,6 +74,51 @@ impl SharedMemory {
         }
     }
 
+    /// Like `create`, but immediately applies `flags` as memfd seals (see
+    /// `seal`) once the region is sized, before any fd is handed to another
+    /// process - so a producer publishing a snapshot never has a window
+    /// where the region exists unsealed.
+    #[cfg(target_os = "linux")]
+    pub fn create_sealed(name: &str, size: usize, flags: SealFlags) -> Result<Self> {
+        let shm = Self::create_memfd(name, size)?;
+        shm.seal(flags)?;
+        Ok(shm)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn create_sealed(name: &str, size: usize, _flags: SealFlags) -> Result<Self> {
+        Self::create_shm(name, size)
+    }
+
+    /// Apply `flags` as memfd seals, then lock the seal set itself with
+    /// `F_SEAL_SEAL` so nothing - not even this process - can add or
+    /// remove seals afterwards. Goes through `rustix::fs::fcntl_add_seals`
+    /// rather than a raw `libc::fcntl` call, so a failed seal comes back
+    /// as a typed `io::Error` instead of a bare negative return code.
+    #[cfg(target_os = "linux")]
+    pub fn seal(&self, flags: SealFlags) -> Result<()> {
+        use rustix::fs::{fcntl_add_seals, SealFlags as RustixSeals};
+
+        let Backing::Memfd(fd) = &self.backing;
+
+        let mut bits = RustixSeals::empty();
+        if flags.shrink {
+            bits |= RustixSeals::SHRINK;
+        }
+        if flags.grow {
+            bits |= RustixSeals::GROW;
+        }
+        if flags.write {
+            bits |= RustixSeals::WRITE;
+        }
+
+        fcntl_add_seals(fd, bits).map_err(|e| IpcError::SharedMemory(format!("F_ADD_SEALS failed: {}", e)))?;
+        fcntl_add_seals(fd, RustixSeals::SEAL)
+            .map_err(|e| IpcError::SharedMemory(format!("F_SEAL_SEAL failed: {}", e)))?;
+
+        Ok(())
+    }
+
     /// Open an existing shared memory region
     pub fn open(name: &str, size: usize) -> Result<Self> {
         #[cfg(target_os = "linux")]
@@ -46,54 +134,72 @@ impl SharedMemory {
 
     #[cfg(target_os = "linux")]
     fn create_memfd(name: &str, size: usize) -> Result<Self> {
-        use memfd::MemfdOptions;
+        use rustix::fs::{ftruncate, memfd_create, MemfdFlags};
+        use rustix::mm::{mmap, MapFlags, ProtFlags};
 
-        let opts = MemfdOptions::default().allow_sealing(true);
-        let mfd = opts
-            .create(name)
+        let fd = memfd_create(name, MemfdFlags::CLOEXEC | MemfdFlags::ALLOW_SEALING)
             .map_err(|e| IpcError::SharedMemory(format!("memfd_create failed: {}", e)))?;
 
-        // Set size
-        mfd.as_file()
-            .set_len(size as u64)
-            .map_err(|e| IpcError::SharedMemory(format!("ftruncate failed: {}", e)))?;
-
-        // mmap the memory
-        let ptr = unsafe {
-            libc::mmap(
-                ptr::null_mut(),
-                size,
-                libc::PROT_READ | libc::PROT_WRITE,
-                libc::MAP_SHARED,
-                mfd.as_raw_fd(),
-                0,
-            )
-        };
-
-        if ptr == libc::MAP_FAILED {
-            return Err(IpcError::SharedMemory("mmap failed".to_string()));
-        }
+        ftruncate(&fd, size as u64).map_err(|e| IpcError::SharedMemory(format!("ftruncate failed: {}", e)))?;
+
+        let ptr = unsafe { mmap(ptr::null_mut(), size, ProtFlags::READ | ProtFlags::WRITE, MapFlags::SHARED, &fd, 0) }
+            .map_err(|e| IpcError::SharedMemory(format!("mmap failed: {}", e)))?;
 
         Ok(Self {
-            name: name.to_string(),
             size,
             ptr: ptr as *mut u8,
-            fd: Some(mfd.as_raw_fd()),
+            backing: Backing::Memfd(fd),
         })
     }
 
     #[cfg(target_os = "linux")]
     fn open_memfd(_name: &str, _size: usize) -> Result<Self> {
-        // For memfd, we need file descriptor passing via Unix socket
-        // This is a simplified version - full implementation would use SCM_RIGHTS
+        // A memfd has no path to re-open by name - the receiving side
+        // must already have the fd, handed over by the creator's
+        // `send_fd` across a Unix socket the two processes share (see
+        // `recv_fd`), not looked up here.
         Err(IpcError::SharedMemory(
-            "memfd open not yet implemented - use shared_memory crate instead".to_string(),
+            "memfd has no name to open by - use SharedMemory::recv_fd over a socket connected to the creator"
+                .to_string(),
         ))
     }
 
+    /// Send this region's underlying memfd to `sock` via `SCM_RIGHTS`, so a
+    /// process holding the other end of `sock` can `recv_fd` it and map
+    /// the same region without ever touching `/dev/shm` - see
+    /// `fd_passing`.
+    #[cfg(target_os = "linux")]
+    pub fn send_fd(&self, sock: RawFd) -> Result<()> {
+        let Backing::Memfd(fd) = &self.backing;
+        crate::fd_passing::send_fd(sock, fd.as_raw_fd())
+    }
+
+    /// Receive a memfd sent by `send_fd` over `sock` and `mmap` it
+    /// `MAP_SHARED`, `size` bytes - the real mechanism behind `open`, once
+    /// the caller has a socket connected to the process that created the
+    /// region (`open_memfd` has no way to find the fd by name alone).
+    #[cfg(target_os = "linux")]
+    pub fn recv_fd(sock: RawFd, size: usize) -> Result<Self> {
+        use rustix::mm::{mmap, MapFlags, ProtFlags};
+
+        let fd = crate::fd_passing::recv_fd(sock)?;
+        // SAFETY: `fd_passing::recv_fd` hands back a freshly-received,
+        // uniquely-owned descriptor from this `recvmsg` call.
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        let ptr = unsafe { mmap(ptr::null_mut(), size, ProtFlags::READ | ProtFlags::WRITE, MapFlags::SHARED, &fd, 0) }
+            .map_err(|e| IpcError::SharedMemory(format!("mmap failed: {}", e)))?;
+
+        Ok(Self {
+            size,
+            ptr: ptr as *mut u8,
+            backing: Backing::Memfd(fd),
+        })
+    }
+
     #[cfg(not(target_os = "linux"))]
     fn create_shm(name: &str, size: usize) -> Result<Self> {
-        use shared_memory::{Shmem, ShmemConf};
+        use shared_memory::ShmemConf;
 
         let shmem = ShmemConf::new()
             .size(size)
@@ -101,11 +207,12 @@ impl SharedMemory {
             .create()
             .map_err(|e| IpcError::SharedMemory(format!("shm_open failed: {}", e)))?;
 
+        let ptr = shmem.as_ptr() as *mut u8;
+
         Ok(Self {
-            name: name.to_string(),
             size,
-            ptr: shmem.as_ptr() as *mut u8,
-            fd: None,
+            ptr,
+            backing: Backing::Posix(shmem),
         })
     }
 
@@ -113,14 +220,14 @@ impl SharedMemory {
     fn open_shm(name: &str, size: usize) -> Result<Self> {
         use shared_memory::Shmem;
 
-        let shmem = Shmem::open(name)
-            .map_err(|e| IpcError::SharedMemory(format!("shm_open failed: {}", e)))?;
+        let shmem = Shmem::open(name).map_err(|e| IpcError::SharedMemory(format!("shm_open failed: {}", e)))?;
+
+        let ptr = shmem.as_ptr() as *mut u8;
 
         Ok(Self {
-            name: name.to_string(),
             size,
-            ptr: shmem.as_ptr() as *mut u8,
-            fd: None,
+            ptr,
+            backing: Backing::Posix(shmem),
         })
     }
 
@@ -159,10 +266,11 @@ impl SharedMemory {
 
 impl Drop for SharedMemory {
     fn drop(&mut self) {
-        if !self.ptr.is_null() {
-            unsafe {
-                libc::munmap(self.ptr as *mut libc::c_void, self.size);
-            }
+        // Only a `Memfd` backing was mapped by us - a `Posix` backing's
+        // `Shmem` owns and unmaps `ptr` itself when it drops right after
+        // this, so unmapping it here too would be a double-unmap.
+        if matches!(self.backing, Backing::Memfd(_)) && !self.ptr.is_null() {
+            let _ = unsafe { rustix::mm::munmap(self.ptr as *mut std::ffi::c_void, self.size) };
         }
     }
 }
@@ -173,6 +281,67 @@ unsafe impl Send for SharedMemory {}
 // SharedMemory is Sync because access is synchronized via atomic operations in RingBuffer
 unsafe impl Sync for SharedMemory {}
 
+/// A shared-memory region mapped `PROT_READ` only. Meant for a consumer
+/// given an fd to a region the producer sealed with `SharedMemory::seal`/
+/// `create_sealed` (e.g. received via `fd_passing::recv_fd`): on top of
+/// the kernel already rejecting writes to a `F_SEAL_WRITE`-sealed memfd,
+/// this is a type-level guarantee too - there's no `as_slice_mut` to
+/// misuse.
+pub struct ReadOnlySharedMemory {
+    size: usize,
+    ptr: *const u8,
+}
+
+impl ReadOnlySharedMemory {
+    /// Map `fd` read-only, `size` bytes. Borrows `fd` rather than taking
+    /// ownership of it - the caller (typically still holding the
+    /// `SharedMemory`/`OwnedFd` this came from) keeps it alive.
+    pub fn open_readonly(fd: RawFd, size: usize) -> Result<Self> {
+        use rustix::mm::{mmap, MapFlags, ProtFlags};
+        use std::os::fd::BorrowedFd;
+
+        // SAFETY: caller guarantees `fd` is a valid, open descriptor for
+        // the lifetime of this call.
+        let borrowed = unsafe { BorrowedFd::borrow_raw(fd) };
+
+        let ptr = unsafe { mmap(ptr::null_mut(), size, ProtFlags::READ, MapFlags::SHARED, borrowed, 0) }
+            .map_err(|e| IpcError::SharedMemory(format!("mmap failed: {}", e)))?;
+
+        Ok(Self {
+            size,
+            ptr: ptr as *const u8,
+        })
+    }
+
+    /// Get the size of the shared memory region
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Get a slice view of the shared memory
+    ///
+    /// # Safety
+    /// This is unsafe because the memory is shared with other processes.
+    /// The caller must ensure proper synchronization with any writer -
+    /// though a fully `SealFlags::IMMUTABLE`-sealed region has none.
+    #[inline]
+    pub unsafe fn as_slice(&self) -> &[u8] {
+        std::slice::from_raw_parts(self.ptr, self.size)
+    }
+}
+
+impl Drop for ReadOnlySharedMemory {
+    fn drop(&mut self) {
+        if !self.ptr.is_null() {
+            let _ = unsafe { rustix::mm::munmap(self.ptr as *mut std::ffi::c_void, self.size) };
+        }
+    }
+}
+
+unsafe impl Send for ReadOnlySharedMemory {}
+unsafe impl Sync for ReadOnlySharedMemory {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -184,4 +353,10 @@ mod tests {
         slice[0] = 42;
         assert_eq!(slice[0], 42);
     }
+
+    #[test]
+    fn test_create_sealed_size_only() {
+        let shm = SharedMemory::create_sealed("test_shm_sealed", 4096, SealFlags::SIZE_ONLY).unwrap();
+        assert_eq!(shm.size(), 4096);
+    }
 }
@@ -0,0 +1,134 @@
+//! Embeddable scripting subsystem for chaining module/session workflows
+//!
+//! One-call-at-a-time FFI forces the host language to orchestrate every
+//! step of a workflow (loop over targets, set options, `check`,
+//! conditionally `exploit`, drive the resulting `Session`) itself. This
+//! wraps a `rhai` engine with the crate's core types registered as native
+//! script types, so a resource script can do all of that in one
+//! `Framework::run_script` call instead.
+use crate::error::{AssassinateError, Result};
+use crate::framework::{DataStore, Framework, Module, PayloadGenerator, Session, SessionManager};
+use rhai::{Engine, Scope};
+
+/// A script's final value, converted out of `rhai::Dynamic` into a plain
+/// enum so callers don't need to depend on `rhai` themselves.
+#[derive(Debug, Clone)]
+pub enum ScriptResult {
+    Unit,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl Framework {
+    /// Evaluate `src` with this framework injected as the `framework`
+    /// global and the crate's core types registered as native script
+    /// types (see `register_types`), so a resource script can call e.g.
+    /// `framework.create_module("exploit/...")`, `mod.set_option(...)`,
+    /// `mod.exploit("...")`, and walk the resulting `Session` - all in one
+    /// script instead of the host language round-tripping per call.
+    pub fn run_script(&self, src: &str) -> Result<ScriptResult> {
+        self.run_script_cancellable(src, || false)
+    }
+
+    /// Like `run_script`, but `should_abort` is polled periodically during
+    /// execution (Rhai's `on_progress` hook) so a long-running script -
+    /// e.g. one iterating `list_modules("exploit")` and batch-`check()`ing
+    /// a host list - can be cancelled from outside the script instead of
+    /// always run to completion.
+    pub fn run_script_cancellable(&self, src: &str, should_abort: impl Fn() -> bool + 'static) -> Result<ScriptResult> {
+        let mut engine = Engine::new();
+        register_types(&mut engine);
+        engine.on_progress(move |_ops| if should_abort() { Some(rhai::Dynamic::UNIT) } else { None });
+
+        let mut scope = Scope::new();
+        scope.push("framework", self.clone());
+
+        let result = engine
+            .eval_with_scope::<rhai::Dynamic>(&mut scope, src)
+            .map_err(|e| AssassinateError::ScriptError(e.to_string()))?;
+
+        Ok(dynamic_to_script_result(&result))
+    }
+}
+
+fn register_types(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<Framework>("Framework")
+        .register_fn("create_module", |framework: &mut Framework, fullname: &str| {
+            framework.create_module(fullname).map_err(script_err)
+        })
+        .register_fn("list_modules", |framework: &mut Framework, module_type: &str| {
+            framework.list_modules(module_type).map_err(script_err)
+        })
+        .register_fn("search", |framework: &mut Framework, query: &str| framework.search(query).map_err(script_err))
+        .register_fn("sessions", |framework: &mut Framework| framework.sessions().map_err(script_err));
+
+    engine
+        .register_type_with_name::<Module>("Module")
+        .register_fn("fullname", |module: &mut Module| module.fullname().map_err(script_err))
+        .register_fn("datastore", |module: &mut Module| module.datastore().map_err(script_err))
+        .register_fn("has_check", |module: &mut Module| module.has_check().map_err(script_err))
+        .register_fn("check", |module: &mut Module| module.check().map_err(script_err))
+        .register_fn("exploit", |module: &mut Module, payload: &str| {
+            module.exploit(payload, None).map(|sid| sid.map(rhai::Dynamic::from).unwrap_or(rhai::Dynamic::UNIT)).map_err(script_err)
+        })
+        .register_fn("run", |module: &mut Module| module.run(None).map_err(script_err));
+
+    engine
+        .register_type_with_name::<DataStore>("DataStore")
+        .register_fn("set", |datastore: &mut DataStore, key: &str, value: &str| datastore.set(key, value).map_err(script_err))
+        .register_fn("get", |datastore: &mut DataStore, key: &str| {
+            datastore.get(key).map(|v| v.unwrap_or_default()).map_err(script_err)
+        });
+
+    engine
+        .register_type_with_name::<SessionManager>("SessionManager")
+        .register_fn("list", |manager: &mut SessionManager| manager.list().map_err(script_err));
+
+    engine
+        .register_type_with_name::<Session>("Session")
+        .register_fn("alive", |session: &mut Session| session.alive().map_err(script_err))
+        .register_fn("desc", |session: &mut Session| session.desc().map_err(script_err))
+        .register_fn("kill", |session: &mut Session| session.kill().map_err(script_err));
+
+    register_session_interactive(engine);
+
+    engine.register_type_with_name::<PayloadGenerator>("PayloadGenerator");
+}
+
+/// `Session::execute`/`run_cmd` and `SessionManager::get` only exist under
+/// the `python-bindings` feature today (see `framework.rs`) - scripts can
+/// still list and inspect sessions without it, just not drive one
+/// interactively.
+#[cfg(feature = "python-bindings")]
+fn register_session_interactive(engine: &mut Engine) {
+    engine
+        .register_fn("get", |manager: &mut SessionManager, session_id: i64| {
+            manager.get(session_id).map_err(script_err)
+        })
+        .register_fn("execute", |session: &mut Session, command: &str| session.execute(command).map_err(script_err))
+        .register_fn("run_cmd", |session: &mut Session, command: &str| session.run_cmd(command).map_err(script_err));
+}
+
+#[cfg(not(feature = "python-bindings"))]
+fn register_session_interactive(_engine: &mut Engine) {}
+
+fn script_err(e: AssassinateError) -> Box<rhai::EvalAltResult> {
+    e.to_string().into()
+}
+
+fn dynamic_to_script_result(value: &rhai::Dynamic) -> ScriptResult {
+    if value.is_unit() {
+        ScriptResult::Unit
+    } else if let Some(b) = value.clone().try_cast::<bool>() {
+        ScriptResult::Bool(b)
+    } else if let Some(i) = value.clone().try_cast::<i64>() {
+        ScriptResult::Int(i)
+    } else if let Some(f) = value.clone().try_cast::<f64>() {
+        ScriptResult::Float(f)
+    } else {
+        ScriptResult::String(value.to_string())
+    }
+}
@@ -0,0 +1,428 @@
+//! Declarative type coercion for MSF module/datastore option values
+//!
+//! MSF options are strongly typed (`OptInt`, `OptBool`, `OptPort`,
+//! `OptAddr`, ...) but the Ruby `DataStore` itself only ever stores
+//! strings - Metasploit's own option classes do the int/bool/etc. parsing
+//! on the way in. Callers over the RPC boundary send JSON, though, and
+//! JSON distinguishes numbers/booleans/strings in a way a hand-rolled
+//! `v.as_str()` per call site doesn't track consistently. `Conversion` is
+//! a small, declarative stand-in for "which MSF option type is this" so
+//! a dispatch handler can validate and normalize a value once, the same
+//! way regardless of which method it came in through, before handing the
+//! result off to `DataStore::set` as a string.
+use crate::error::AssassinateError;
+
+/// A declared option type, parsed from a short spec string
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conversion {
+    /// Passed through as-is, for options that are already free-form text
+    Bytes,
+    Integer,
+    Float,
+    Boolean,
+    /// A hostname or IP address - validated as non-empty, not resolved
+    Address,
+    Port,
+    /// A filesystem path - validated as non-empty, not checked for existence
+    Path,
+    /// A value constrained to a fixed set of allowed strings, e.g. an
+    /// `OptEnum` option's declared choices
+    Enum(Vec<String>),
+    /// `fmt` is `None` for a bare `"timestamp"` (ISO 8601 / epoch seconds),
+    /// or `Some(fmt)` for `"timestamp|<fmt>"`, where `fmt` is a strftime-style
+    /// pattern built from `%Y %m %d %H %M %S`.
+    Timestamp(Option<String>),
+}
+
+impl Conversion {
+    /// Parse a conversion spec string: `"bytes"`, `"int"`/`"integer"`,
+    /// `"float"`, `"bool"`/`"boolean"`, `"address"`/`"addr"`, `"port"`,
+    /// `"path"`, `"enum:choice1,choice2,..."`, `"timestamp"`, or
+    /// `"timestamp|<fmt>"`.
+    pub fn from_str(spec: &str) -> Result<Self, AssassinateError> {
+        match spec {
+            "bytes" => Ok(Conversion::Bytes),
+            "int" | "integer" => Ok(Conversion::Integer),
+            "float" => Ok(Conversion::Float),
+            "bool" | "boolean" => Ok(Conversion::Boolean),
+            "address" | "addr" => Ok(Conversion::Address),
+            "port" => Ok(Conversion::Port),
+            "path" => Ok(Conversion::Path),
+            "timestamp" => Ok(Conversion::Timestamp(None)),
+            _ => spec
+                .strip_prefix("enum:")
+                .map(|choices| Conversion::Enum(choices.split(',').map(|c| c.to_string()).collect()))
+                .or_else(|| {
+                    spec.strip_prefix("timestamp|")
+                        .map(|fmt| Conversion::Timestamp(Some(fmt.to_string())))
+                })
+                .ok_or_else(|| {
+                    AssassinateError::ConversionError(format!("unknown conversion spec: {:?}", spec))
+                }),
+        }
+    }
+
+    /// Coerce `value` to the declared type, returning a normalized
+    /// `serde_json::Value` (a `Number`, `Bool`, or `String`, depending on
+    /// the variant) or a `ConversionError` describing why it doesn't fit.
+    pub fn convert(&self, value: &serde_json::Value) -> Result<serde_json::Value, AssassinateError> {
+        match self {
+            Conversion::Bytes => as_str(value).map(serde_json::Value::String),
+            Conversion::Integer => as_i64(value).map(|n| serde_json::json!(n)),
+            Conversion::Float => as_f64(value).map(|n| serde_json::json!(n)),
+            Conversion::Boolean => as_bool(value).map(serde_json::Value::Bool),
+            Conversion::Address => {
+                let addr = as_str(value)?;
+                if addr.trim().is_empty() {
+                    return Err(AssassinateError::ConversionError("address must not be empty".to_string()));
+                }
+                Ok(serde_json::Value::String(addr))
+            }
+            Conversion::Port => {
+                let port = as_i64(value)?;
+                if !(1..=65535).contains(&port) {
+                    return Err(AssassinateError::ConversionError(format!(
+                        "{} is not a valid port (must be 1-65535)",
+                        port
+                    )));
+                }
+                Ok(serde_json::json!(port))
+            }
+            Conversion::Path => {
+                let path = as_str(value)?;
+                if path.trim().is_empty() {
+                    return Err(AssassinateError::ConversionError("path must not be empty".to_string()));
+                }
+                Ok(serde_json::Value::String(path))
+            }
+            Conversion::Enum(choices) => {
+                let chosen = as_str(value)?;
+                if !choices.iter().any(|c| c == &chosen) {
+                    return Err(AssassinateError::ConversionError(format!(
+                        "{:?} is not one of the allowed values: {}",
+                        chosen,
+                        choices.join(", ")
+                    )));
+                }
+                Ok(serde_json::Value::String(chosen))
+            }
+            Conversion::Timestamp(fmt) => {
+                let epoch = match fmt {
+                    Some(fmt) => parse_timestamp_with_format(&as_str(value)?, fmt)?,
+                    None => parse_timestamp_default(value)?,
+                };
+                Ok(serde_json::json!(epoch))
+            }
+        }
+    }
+}
+
+/// The converted result of `Conversion::convert`, typed by which variant
+/// produced it - what `DataStore::typed_get` returns and `typed_set`
+/// accepts, instead of both ends dealing in a bare `serde_json::Value`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataStoreValue {
+    Bytes(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Address(String),
+    Port(u16),
+    Path(String),
+    Enum(String),
+    Timestamp(i64),
+}
+
+impl DataStoreValue {
+    /// Build a `DataStoreValue` from `conversion`'s coerced JSON output
+    pub fn from_conversion(conversion: &Conversion, coerced: serde_json::Value) -> Result<Self, AssassinateError> {
+        Ok(match conversion {
+            Conversion::Bytes => DataStoreValue::Bytes(as_str(&coerced)?),
+            Conversion::Integer => DataStoreValue::Integer(as_i64(&coerced)?),
+            Conversion::Float => DataStoreValue::Float(as_f64(&coerced)?),
+            Conversion::Boolean => DataStoreValue::Boolean(as_bool(&coerced)?),
+            Conversion::Address => DataStoreValue::Address(as_str(&coerced)?),
+            Conversion::Port => DataStoreValue::Port(as_i64(&coerced)? as u16),
+            Conversion::Path => DataStoreValue::Path(as_str(&coerced)?),
+            Conversion::Enum(_) => DataStoreValue::Enum(as_str(&coerced)?),
+            Conversion::Timestamp(_) => DataStoreValue::Timestamp(as_i64(&coerced)?),
+        })
+    }
+
+    /// Serialize back to the string form Ruby's datastore expects
+    pub fn to_datastore_string(&self) -> String {
+        match self {
+            DataStoreValue::Bytes(s) => s.clone(),
+            DataStoreValue::Integer(n) => n.to_string(),
+            DataStoreValue::Float(n) => n.to_string(),
+            DataStoreValue::Boolean(b) => b.to_string(),
+            DataStoreValue::Address(s) => s.clone(),
+            DataStoreValue::Port(p) => p.to_string(),
+            DataStoreValue::Path(s) => s.clone(),
+            DataStoreValue::Enum(s) => s.clone(),
+            DataStoreValue::Timestamp(epoch) => epoch.to_string(),
+        }
+    }
+}
+
+fn as_str(value: &serde_json::Value) -> Result<String, AssassinateError> {
+    match value {
+        serde_json::Value::String(s) => Ok(s.clone()),
+        serde_json::Value::Number(n) => Ok(n.to_string()),
+        other => Err(AssassinateError::ConversionError(format!(
+            "expected a string, got {}",
+            other
+        ))),
+    }
+}
+
+fn as_i64(value: &serde_json::Value) -> Result<i64, AssassinateError> {
+    match value {
+        serde_json::Value::Number(n) => n
+            .as_i64()
+            .ok_or_else(|| AssassinateError::ConversionError(format!("{} is not an integer", n))),
+        serde_json::Value::String(s) => s
+            .trim()
+            .parse::<i64>()
+            .map_err(|e| AssassinateError::ConversionError(format!("{:?} is not an integer: {}", s, e))),
+        other => Err(AssassinateError::ConversionError(format!(
+            "expected an integer, got {}",
+            other
+        ))),
+    }
+}
+
+fn as_f64(value: &serde_json::Value) -> Result<f64, AssassinateError> {
+    match value {
+        serde_json::Value::Number(n) => n
+            .as_f64()
+            .ok_or_else(|| AssassinateError::ConversionError(format!("{} is not a float", n))),
+        serde_json::Value::String(s) => s
+            .trim()
+            .parse::<f64>()
+            .map_err(|e| AssassinateError::ConversionError(format!("{:?} is not a float: {}", s, e))),
+        other => Err(AssassinateError::ConversionError(format!(
+            "expected a float, got {}",
+            other
+        ))),
+    }
+}
+
+fn as_bool(value: &serde_json::Value) -> Result<bool, AssassinateError> {
+    match value {
+        serde_json::Value::Bool(b) => Ok(*b),
+        serde_json::Value::String(s) => match s.to_ascii_lowercase().as_str() {
+            "true" | "t" | "yes" | "y" | "1" => Ok(true),
+            "false" | "f" | "no" | "n" | "0" => Ok(false),
+            other => Err(AssassinateError::ConversionError(format!(
+                "{:?} is not a boolean",
+                other
+            ))),
+        },
+        other => Err(AssassinateError::ConversionError(format!(
+            "expected a boolean, got {}",
+            other
+        ))),
+    }
+}
+
+/// Days since the Unix epoch for a proleptic-Gregorian civil date, via
+/// Howard Hinnant's `days_from_civil` - avoids pulling in a date/time crate
+/// for what's otherwise a handful of option values per module run.
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = (y - era * 400) as i64;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_to_epoch(y: i64, mo: i64, d: i64, h: i64, mi: i64, s: i64) -> i64 {
+    days_from_civil(y, mo, d) * 86400 + h * 3600 + mi * 60 + s
+}
+
+/// Parse a bare `"timestamp"` value: an already-numeric epoch-seconds value
+/// passes straight through, otherwise the string is expected to be
+/// `YYYY-MM-DDTHH:MM:SS` (optionally `Z`-suffixed).
+fn parse_timestamp_default(value: &serde_json::Value) -> Result<i64, AssassinateError> {
+    if let serde_json::Value::Number(n) = value {
+        return n
+            .as_i64()
+            .ok_or_else(|| AssassinateError::ConversionError(format!("{} is not a valid epoch timestamp", n)));
+    }
+
+    let s = as_str(value)?;
+    let s = s.trim().trim_end_matches('Z');
+    let (date, time) = s
+        .split_once('T')
+        .ok_or_else(|| AssassinateError::ConversionError(format!("{:?} is not a valid ISO 8601 timestamp", s)))?;
+
+    let mut date_parts = date.split('-');
+    let mut time_parts = time.split(':');
+    let (y, mo, d) = (
+        next_field(&mut date_parts, s)?,
+        next_field(&mut date_parts, s)?,
+        next_field(&mut date_parts, s)?,
+    );
+    let (h, mi, sec) = (
+        next_field(&mut time_parts, s)?,
+        next_field(&mut time_parts, s)?,
+        next_field(&mut time_parts, s)?,
+    );
+
+    Ok(civil_to_epoch(y, mo, d, h, mi, sec))
+}
+
+/// Parse `value` against a strftime-style `fmt` built from `%Y`, `%m`, `%d`,
+/// `%H`, `%M`, `%S` and literal separator characters.
+fn parse_timestamp_with_format(value: &str, fmt: &str) -> Result<i64, AssassinateError> {
+    let mut fields = [0i64; 6]; // Y, m, d, H, M, S
+    let mut fmt_chars = fmt.chars().peekable();
+    let mut value_chars = value.chars().peekable();
+
+    while let Some(fc) = fmt_chars.next() {
+        if fc == '%' {
+            let token = fmt_chars.next().ok_or_else(|| {
+                AssassinateError::ConversionError(format!("dangling '%' in timestamp format {:?}", fmt))
+            })?;
+            let idx = match token {
+                'Y' => 0,
+                'm' => 1,
+                'd' => 2,
+                'H' => 3,
+                'M' => 4,
+                'S' => 5,
+                other => {
+                    return Err(AssassinateError::ConversionError(format!(
+                        "unsupported timestamp format token '%{}'",
+                        other
+                    )))
+                }
+            };
+            let width = if token == 'Y' { 4 } else { 2 };
+            let mut digits = String::with_capacity(width);
+            for _ in 0..width {
+                match value_chars.peek() {
+                    Some(c) if c.is_ascii_digit() => digits.push(value_chars.next().unwrap()),
+                    _ => break,
+                }
+            }
+            if digits.is_empty() {
+                return Err(AssassinateError::ConversionError(format!(
+                    "{:?} does not match timestamp format {:?}",
+                    value, fmt
+                )));
+            }
+            fields[idx] = digits.parse().map_err(|_| {
+                AssassinateError::ConversionError(format!("{:?} does not match timestamp format {:?}", value, fmt))
+            })?;
+        } else {
+            match value_chars.next() {
+                Some(vc) if vc == fc => {}
+                _ => {
+                    return Err(AssassinateError::ConversionError(format!(
+                        "{:?} does not match timestamp format {:?}",
+                        value, fmt
+                    )))
+                }
+            }
+        }
+    }
+
+    Ok(civil_to_epoch(fields[0], fields[1], fields[2], fields[3], fields[4], fields[5]))
+}
+
+fn next_field(parts: &mut std::str::Split<'_, char>, original: &str) -> Result<i64, AssassinateError> {
+    parts
+        .next()
+        .ok_or_else(|| AssassinateError::ConversionError(format!("{:?} is not a valid ISO 8601 timestamp", original)))?
+        .parse::<i64>()
+        .map_err(|e| AssassinateError::ConversionError(format!("{:?} is not a valid ISO 8601 timestamp: {}", original, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str_parses_known_specs() {
+        assert_eq!(Conversion::from_str("int").unwrap(), Conversion::Integer);
+        assert_eq!(Conversion::from_str("float").unwrap(), Conversion::Float);
+        assert_eq!(Conversion::from_str("bool").unwrap(), Conversion::Boolean);
+        assert_eq!(Conversion::from_str("port").unwrap(), Conversion::Port);
+        assert_eq!(Conversion::from_str("address").unwrap(), Conversion::Address);
+        assert_eq!(Conversion::from_str("path").unwrap(), Conversion::Path);
+        assert_eq!(
+            Conversion::from_str("enum:a,b,c").unwrap(),
+            Conversion::Enum(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+        );
+        assert_eq!(Conversion::from_str("timestamp").unwrap(), Conversion::Timestamp(None));
+        assert_eq!(
+            Conversion::from_str("timestamp|%Y-%m-%d").unwrap(),
+            Conversion::Timestamp(Some("%Y-%m-%d".to_string()))
+        );
+        assert!(Conversion::from_str("nonsense").is_err());
+    }
+
+    #[test]
+    fn test_int_conversion_accepts_numbers_and_numeric_strings() {
+        let conv = Conversion::Integer;
+        assert_eq!(conv.convert(&serde_json::json!(445)).unwrap(), serde_json::json!(445));
+        assert_eq!(conv.convert(&serde_json::json!("445")).unwrap(), serde_json::json!(445));
+        assert!(conv.convert(&serde_json::json!("not a number")).is_err());
+    }
+
+    #[test]
+    fn test_port_conversion_rejects_out_of_range() {
+        let conv = Conversion::Port;
+        assert_eq!(conv.convert(&serde_json::json!(445)).unwrap(), serde_json::json!(445));
+        assert!(conv.convert(&serde_json::json!(0)).is_err());
+        assert!(conv.convert(&serde_json::json!(70000)).is_err());
+    }
+
+    #[test]
+    fn test_bool_conversion_accepts_common_spellings() {
+        let conv = Conversion::Boolean;
+        assert_eq!(conv.convert(&serde_json::json!(true)).unwrap(), serde_json::json!(true));
+        assert_eq!(conv.convert(&serde_json::json!("yes")).unwrap(), serde_json::json!(true));
+        assert_eq!(conv.convert(&serde_json::json!("0")).unwrap(), serde_json::json!(false));
+        assert!(conv.convert(&serde_json::json!("maybe")).is_err());
+    }
+
+    #[test]
+    fn test_enum_conversion_rejects_values_outside_choice_set() {
+        let conv = Conversion::Enum(vec!["tcp".to_string(), "udp".to_string()]);
+        assert_eq!(conv.convert(&serde_json::json!("tcp")).unwrap(), serde_json::json!("tcp"));
+        assert!(conv.convert(&serde_json::json!("icmp")).is_err());
+    }
+
+    #[test]
+    fn test_typed_roundtrip_via_data_store_value() {
+        let conv = Conversion::Port;
+        let coerced = conv.convert(&serde_json::json!("445")).unwrap();
+        let typed = DataStoreValue::from_conversion(&conv, coerced).unwrap();
+        assert_eq!(typed, DataStoreValue::Port(445));
+        assert_eq!(typed.to_datastore_string(), "445");
+    }
+
+    #[test]
+    fn test_timestamp_default_parses_iso8601() {
+        let conv = Conversion::Timestamp(None);
+        // 2021-01-01T00:00:00Z
+        assert_eq!(
+            conv.convert(&serde_json::json!("2021-01-01T00:00:00Z")).unwrap(),
+            serde_json::json!(1609459200i64)
+        );
+    }
+
+    #[test]
+    fn test_timestamp_with_format_parses_custom_pattern() {
+        let conv = Conversion::Timestamp(Some("%Y-%m-%d".to_string()));
+        assert_eq!(
+            conv.convert(&serde_json::json!("2021-01-01")).unwrap(),
+            serde_json::json!(1609459200i64)
+        );
+    }
+}
@@ -0,0 +1,3 @@
+//! Snapshotting a module's datastore to disk, independent of the live
+//! Ruby-backed `DataStore` wrapper in `framework.rs`
+pub mod persist;
@@ -0,0 +1,210 @@
+//! Pluggable persistence backends for a module's option set
+//!
+//! `Module`/`DataStore` only exist for as long as the Ruby VM keeps their
+//! underlying object alive, so nothing a user `set`s on a module's
+//! datastore survives a process restart on its own. A `Backend` is just a
+//! named place to put the flattened `to_h` of that datastore (a
+//! `HashMap<String, String>`) so it can be written down and read back later
+//! - e.g. as a saved engagement/workspace - and `migrate` gives a safe path
+//! to move that snapshot from one backend to another (a JSON file to the
+//! embedded store, or vice versa) without silently losing entries if the
+//! destination doesn't actually round-trip them.
+use crate::error::AssassinateError;
+use crate::framework::Module;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(thiserror::Error, Debug)]
+pub enum PersistError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("embedded store error: {0}")]
+    Sled(#[from] sled::Error),
+
+    #[error("migration failed: destination did not round-trip {missing} of {total} entries from the source")]
+    VerificationFailed { missing: usize, total: usize },
+}
+
+impl From<PersistError> for AssassinateError {
+    fn from(err: PersistError) -> Self {
+        AssassinateError::DataStoreError(err.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, PersistError>;
+
+/// Somewhere a datastore snapshot (`HashMap<String, String>`) can be loaded
+/// from and saved to. Implementations own their own storage location and
+/// format; `migrate` only depends on this trait, so new backends don't need
+/// any changes elsewhere.
+pub trait Backend {
+    fn load(&self) -> Result<HashMap<String, String>>;
+    fn save(&self, map: &HashMap<String, String>) -> Result<()>;
+}
+
+/// Stores the whole map as a single JSON object at `path`
+pub struct JsonFileBackend {
+    path: PathBuf,
+}
+
+impl JsonFileBackend {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Backend for JsonFileBackend {
+    fn load(&self) -> Result<HashMap<String, String>> {
+        if !self.path.exists() {
+            return Ok(HashMap::new());
+        }
+        let contents = fs::read_to_string(&self.path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    fn save(&self, map: &HashMap<String, String>) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(map)?)?;
+        Ok(())
+    }
+}
+
+/// Stores the map in an embedded `sled` database under a single key, the
+/// same engine `assassinate_daemon`'s `ModuleStore` uses for its own
+/// persisted state
+pub struct SledBackend {
+    db: sled::Db,
+    key: &'static str,
+}
+
+impl SledBackend {
+    pub fn open(state_dir: impl Into<PathBuf>) -> Result<Self> {
+        Ok(Self {
+            db: sled::open(state_dir.into())?,
+            key: "datastore_snapshot",
+        })
+    }
+}
+
+impl Backend for SledBackend {
+    fn load(&self) -> Result<HashMap<String, String>> {
+        match self.db.get(self.key)? {
+            Some(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            None => Ok(HashMap::new()),
+        }
+    }
+
+    fn save(&self, map: &HashMap<String, String>) -> Result<()> {
+        self.db.insert(self.key, serde_json::to_vec(map)?)?;
+        self.db.flush()?;
+        Ok(())
+    }
+}
+
+/// Copy every entry from `from` to `to`, then read `to` back and confirm
+/// every entry that was written reads back with the same value before
+/// treating the migration as complete (entries `to` holds beyond those are
+/// not checked) - callers can keep using `from` if this returns an error.
+pub fn migrate(from: &dyn Backend, to: &dyn Backend) -> Result<()> {
+    let source = from.load()?;
+    to.save(&source)?;
+
+    let written = to.load()?;
+    let missing = source.iter().filter(|(k, v)| written.get(*k) != Some(v)).count();
+    if missing > 0 {
+        return Err(PersistError::VerificationFailed {
+            missing,
+            total: source.len(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Flatten a module's current datastore into `backend`
+pub fn snapshot_module(module: &Module, backend: &dyn Backend) -> crate::error::Result<()> {
+    let snapshot = module.datastore()?.to_dict()?;
+    backend.save(&snapshot)?;
+    Ok(())
+}
+
+/// Replay a previously saved snapshot into a (typically freshly created)
+/// module's datastore, one `[]=` call per entry
+pub fn rehydrate_module(module: &Module, backend: &dyn Backend) -> crate::error::Result<()> {
+    let snapshot = backend.load()?;
+    let datastore = module.datastore()?;
+    for (key, value) in snapshot {
+        datastore.set(&key, &value)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_map() -> HashMap<String, String> {
+        HashMap::from([
+            ("RHOSTS".to_string(), "10.0.0.1".to_string()),
+            ("RPORT".to_string(), "445".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_json_file_backend_round_trips() {
+        let dir = std::env::temp_dir().join(format!("assassinate-persist-test-{:?}", std::thread::current().id()));
+        let path = dir.join("snapshot.json");
+        let backend = JsonFileBackend::new(&path);
+
+        assert_eq!(backend.load().unwrap(), HashMap::new());
+
+        let map = sample_map();
+        backend.save(&map).unwrap();
+        assert_eq!(backend.load().unwrap(), map);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_migrate_json_to_json_round_trips_and_verifies() {
+        let dir = std::env::temp_dir().join(format!("assassinate-persist-migrate-{:?}", std::thread::current().id()));
+        let from = JsonFileBackend::new(dir.join("from.json"));
+        let to = JsonFileBackend::new(dir.join("to.json"));
+
+        from.save(&sample_map()).unwrap();
+        migrate(&from, &to).unwrap();
+        assert_eq!(to.load().unwrap(), sample_map());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    struct LossyBackend {
+        inner: HashMap<String, String>,
+    }
+
+    impl Backend for LossyBackend {
+        fn load(&self) -> Result<HashMap<String, String>> {
+            Ok(self.inner.clone())
+        }
+
+        fn save(&self, _map: &HashMap<String, String>) -> Result<()> {
+            // Drops everything on the floor, simulating a destination that
+            // doesn't actually persist what it was handed.
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_migrate_fails_when_destination_does_not_round_trip() {
+        let from = LossyBackend { inner: sample_map() };
+        let to = LossyBackend { inner: HashMap::new() };
+        assert!(migrate(&from, &to).is_err());
+    }
+}
@@ -32,6 +32,18 @@ pub enum AssassinateError {
     #[error("Payload generation error: {0}")]
     PayloadError(String),
 
+    #[error("Invalid payload format: {0}")]
+    InvalidFormat(String),
+
+    #[error("Encoder space budget exceeded: {0}")]
+    EncoderSpaceViolation(String),
+
+    #[error("Payload space budget exceeded: {0}")]
+    PayloadSpaceViolation(String),
+
+    #[error("Executable template not found: {0}")]
+    NoTemplateError(String),
+
     #[error("Database error: {0}")]
     DatabaseError(String),
 
@@ -41,6 +53,9 @@ pub enum AssassinateError {
     #[error("Type conversion error: {0}")]
     ConversionError(String),
 
+    #[error("Script execution error: {0}")]
+    ScriptError(String),
+
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
@@ -53,3 +68,54 @@ impl From<magnus::Error> for AssassinateError {
 
 /// Result type alias using AssassinateError
 pub type Result<T> = std::result::Result<T, AssassinateError>;
+
+/// Error type for `ruby_bridge`'s raw Ruby-VM-facing functions
+///
+/// Unlike `AssassinateError`, which the higher-level wrapper types
+/// (`Framework`, `Module`, ...) use for their own domain errors, every
+/// variant here carries exactly what `ruby_bridge` has available at the
+/// point of failure - the receiver's Ruby class, the method that was called,
+/// and Ruby's own error message - so callers debugging a failed bridge call
+/// don't have to guess which object or method was involved.
+#[derive(Error, Debug)]
+pub enum BridgeError {
+    #[error("failed to initialize the Ruby VM: {0}")]
+    RubyVmInit(String),
+
+    #[error("failed to load Metasploit from '{path}': {source}")]
+    MetasploitLoad { path: String, source: String },
+
+    #[error("failed to create the Metasploit framework instance: {0}")]
+    FrameworkCreate(String),
+
+    #[error("calling '{receiver_class}#{method}' failed: {source}")]
+    MethodCall {
+        receiver_class: String,
+        method: String,
+        source: String,
+    },
+
+    #[error("'{method}' unexpectedly returned nil")]
+    UnexpectedNil { method: String },
+
+    #[error("failed to convert a {from} to {to}")]
+    Conversion { from: String, to: String },
+}
+
+impl From<BridgeError> for AssassinateError {
+    fn from(err: BridgeError) -> Self {
+        match err {
+            BridgeError::RubyVmInit(msg) => AssassinateError::RubyInitError(msg),
+            BridgeError::MetasploitLoad { path, source } => {
+                AssassinateError::RubyInitError(format!("{} ({})", source, path))
+            }
+            BridgeError::FrameworkCreate(msg) => AssassinateError::RubyError(msg),
+            BridgeError::MethodCall { .. } => AssassinateError::RubyError(err.to_string()),
+            BridgeError::UnexpectedNil { .. } => AssassinateError::RubyError(err.to_string()),
+            BridgeError::Conversion { .. } => AssassinateError::ConversionError(err.to_string()),
+        }
+    }
+}
+
+/// Result type alias using BridgeError
+pub type BridgeResult<T> = std::result::Result<T, BridgeError>;
@@ -1,9 +1,103 @@
 //! Framework types and operations for Metasploit Framework interaction
 
+use crate::conversion::{Conversion, DataStoreValue};
 use crate::error::{AssassinateError, Result};
 use crate::ruby_bridge::{call_method, create_framework, is_nil, value_to_string};
 use magnus::{value::ReprValue, TryConvert, Value};
+use serde::Serialize;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::PathBuf;
+
+thread_local! {
+    /// The callback `Framework::on_output` registers, invoked with
+    /// `(level, line)` for each line a module prints during `exploit`/
+    /// `run`/`check`. Thread-local because `Module`/`Framework` wrap a
+    /// non-`Send` `magnus::Value` and only ever run on the thread that
+    /// owns the Ruby VM, same as the rest of the bridge.
+    static OUTPUT_CALLBACK: RefCell<Option<Box<dyn Fn(&str, &str)>>> = RefCell::new(None);
+}
+
+/// A minimal Ruby `Rex::Ui::Text::Output`-compatible object, built fresh
+/// per call and installed via the `'LocalOutput'` option key that
+/// `exploit_simple`/`run_simple`/`check_simple` forward into the module's
+/// output stream - this is how msfrpcd itself captures module output
+/// instead of letting it go to a console. Buffers every print into a
+/// Ruby array instead of writing anywhere, so it can be read back once
+/// the call returns.
+const CAPTURE_OUTPUT_SRC: &str = r#"
+unless defined?(AssassinateCaptureOutput)
+  class AssassinateCaptureOutput
+    attr_reader :lines
+    def initialize
+      @lines = []
+    end
+    def print_line(msg = '')
+      @lines << msg.to_s
+    end
+    def print_status(msg)
+      @lines << "[*] #{msg}"
+    end
+    def print_good(msg)
+      @lines << "[+] #{msg}"
+    end
+    def print_error(msg)
+      @lines << "[-] #{msg}"
+    end
+    def print_warning(msg)
+      @lines << "[!] #{msg}"
+    end
+    def print_raw(msg)
+      @lines << msg.to_s
+    end
+    def supports_color?
+      false
+    end
+  end
+end
+AssassinateCaptureOutput.new
+"#;
+
+/// Install a fresh `AssassinateCaptureOutput` under `opts_val["LocalOutput"]`,
+/// run `body`, then drain the captured lines to whatever callback
+/// `Framework::on_output` registered (classifying each by its `[*]`/`[+]`/
+/// `[-]`/`[!]` prefix) before returning `body`'s result.
+fn with_captured_output<T>(ruby: &magnus::Ruby, opts_val: Value, body: impl FnOnce() -> Result<T>) -> Result<T> {
+    let capture: Value = ruby
+        .eval(CAPTURE_OUTPUT_SRC)
+        .map_err(|e| AssassinateError::RubyError(format!("Failed to build capture output: {}", e)))?;
+
+    let local_output_key = ruby.str_new("LocalOutput").as_value();
+    call_method(opts_val, "[]=", &[local_output_key, capture])?;
+
+    let result = body();
+
+    if let Ok(lines_val) = call_method(capture, "lines", &[]) {
+        let converted: std::result::Result<Vec<String>, magnus::Error> = TryConvert::try_convert(lines_val);
+        if let Ok(lines) = converted {
+            OUTPUT_CALLBACK.with(|cell| {
+                if let Some(cb) = cell.borrow().as_ref() {
+                    for line in &lines {
+                        let level = if line.starts_with("[*]") {
+                            "status"
+                        } else if line.starts_with("[+]") {
+                            "good"
+                        } else if line.starts_with("[-]") {
+                            "error"
+                        } else if line.starts_with("[!]") {
+                            "warning"
+                        } else {
+                            "line"
+                        };
+                        cb(level, line);
+                    }
+                }
+            });
+        }
+    }
+
+    result
+}
 
 /// Core Metasploit Framework interface
 ///
@@ -135,6 +229,66 @@ impl Framework {
         Ok(results)
     }
 
+    /// Like `search`, but returns a `ModuleMetadata` per hit (fullname,
+    /// type, rank, disclosure date, platforms, references) instead of a
+    /// bare fullname string, so a caller can filter/sort results (e.g.
+    /// "every excellent-rank exploit disclosed after 2023 for linux")
+    /// without a `create_module` round-trip per hit. `query` uses the
+    /// same keyword syntax `search` does (`cve:`, `platform:`, `type:`,
+    /// `rank:`, `author:`, `disclosure_date:`, `name:`, ...) - MSF parses
+    /// it internally, so it's forwarded as-is. A hit missing a given
+    /// field (some metadata objects don't carry every attribute) leaves
+    /// that field at its default rather than failing the whole search.
+    pub fn search_ex(&self, query: &str) -> Result<Vec<ModuleMetadata>> {
+        let ruby = crate::ruby_bridge::get_ruby()?;
+        let query_val = ruby.str_new(query).as_value();
+
+        let results_val = call_method(self.ruby_framework, "search", &[query_val])?;
+        let results_array: magnus::RArray = TryConvert::try_convert(results_val).map_err(|e: magnus::Error| {
+            AssassinateError::ConversionError(format!("Failed to convert search results to array: {}", e))
+        })?;
+
+        let mut results = Vec::new();
+        for item in results_array.each() {
+            let metadata_obj = item.map_err(|e| {
+                AssassinateError::ConversionError(format!("Failed to iterate search results: {}", e))
+            })?;
+
+            let fullname = value_to_string(call_method(metadata_obj, "fullname", &[])?)?;
+            let module_type = call_method(metadata_obj, "type", &[])
+                .ok()
+                .and_then(|v| value_to_string(v).ok())
+                .unwrap_or_default();
+            let rank = call_method(metadata_obj, "rank", &[])
+                .ok()
+                .and_then(|v| value_to_string(v).ok())
+                .unwrap_or_default();
+            let disclosure_date = call_method(metadata_obj, "disclosure_date", &[])
+                .ok()
+                .filter(|v| !is_nil(*v))
+                .and_then(|v| value_to_string(v).ok());
+            let platforms: Vec<String> = call_method(metadata_obj, "platform", &[])
+                .ok()
+                .and_then(|v| TryConvert::try_convert(v).ok())
+                .unwrap_or_default();
+            let references: Vec<String> = call_method(metadata_obj, "references", &[])
+                .ok()
+                .and_then(|v| TryConvert::try_convert(v).ok())
+                .unwrap_or_default();
+
+            results.push(ModuleMetadata {
+                fullname,
+                module_type,
+                rank,
+                disclosure_date,
+                platforms,
+                references,
+            });
+        }
+
+        Ok(results)
+    }
+
     /// Get jobs manager
     pub fn jobs(&self) -> Result<JobManager> {
         let jobs_val = call_method(self.ruby_framework, "jobs", &[])?;
@@ -173,6 +327,20 @@ impl Framework {
         crate::ruby_bridge::value_to_bool(threads_val)
     }
 
+    /// Register `callback` to be invoked with `(level, line)` for every
+    /// line a module prints via `print_status`/`print_good`/`print_error`/
+    /// `print_warning`/`print_line` during `Module::exploit`/`run`/
+    /// `check`, instead of those methods' previous `Quiet => true`
+    /// discarding all of it. Modeled on Rhai's `OnPrintCallback`/
+    /// `OnDebugCallback` - a boxed `Fn` stored alongside the Ruby bridge,
+    /// since the callback only ever fires on the thread that owns the
+    /// Ruby VM. A closure can't cross the Python FFI boundary, so this
+    /// isn't exposed to `python-bindings` builds.
+    #[cfg(not(feature = "python-bindings"))]
+    pub fn on_output(&self, callback: impl Fn(&str, &str) + 'static) {
+        OUTPUT_CALLBACK.with(|cell| *cell.borrow_mut() = Some(Box::new(callback)));
+    }
+
     #[cfg(feature = "python-bindings")]
     #[cfg(feature = "python-bindings")]
     pub fn __repr__(&self) -> Result<String> {
@@ -180,6 +348,197 @@ impl Framework {
     }
 }
 
+/// A single MSF module option (from `mod.options`), normalized out of
+/// Ruby's `Msf::OptionContainer` into plain data instead of the stringified
+/// dump `Module::options` returns.
+#[derive(Debug, Clone)]
+pub struct OptionDescription {
+    pub name: String,
+    pub option_type: String,
+    pub required: bool,
+    pub default: Option<String>,
+    pub description: String,
+}
+
+/// One entry of an exploit's target list (`mod.targets`), with its index
+/// preserved - that index is what `TARGET` gets set to, so dropping it (as
+/// `Module::targets` does) loses the thing a caller actually needs to pick one.
+#[derive(Debug, Clone)]
+pub struct TargetDescription {
+    pub index: usize,
+    pub name: String,
+}
+
+/// One option's full schema, as reported by `Module::options_meta` - `type`
+/// is normalized to the same spec strings `Conversion::from_str` accepts
+/// (`"bool"`, `"port"`, `"address"`, `"path"`, `"enum:<choices>"`, `"int"`,
+/// `"float"`, `"bytes"`),
+/// so a caller can feed it straight into a `Conversion` to validate/convert
+/// a value before it ever reaches the datastore.
+#[derive(Debug, Clone)]
+pub struct OptionSpec {
+    pub name: String,
+    pub option_type: String,
+    pub required: bool,
+    pub default: Option<String>,
+    pub description: String,
+    pub enums: Option<Vec<String>>,
+}
+
+/// One `Framework::search_ex` hit - the same fields `search` throws away
+/// by only keeping `fullname`, structured so a caller can filter/sort
+/// without re-fetching each module via `create_module`.
+#[derive(Debug, Clone)]
+pub struct ModuleMetadata {
+    pub fullname: String,
+    pub module_type: String,
+    pub rank: String,
+    pub disclosure_date: Option<String>,
+    pub platforms: Vec<String>,
+    pub references: Vec<String>,
+}
+
+/// One payload/encoder/NOP module's listing entry - name, human
+/// description, reliability rank, and declared platform/arch compatibility
+/// - returned by `PayloadGenerator::list_payloads_detailed` and friends so
+/// a caller can build a picker or validate a name up front.
+#[derive(Debug, Clone)]
+pub struct ModuleSummary {
+    pub name: String,
+    pub description: String,
+    pub rank: String,
+    pub platforms: Vec<String>,
+    pub arches: Vec<String>,
+}
+
+/// A structured snapshot of a module's options, targets, and compatible
+/// payloads in one call - see `Module::describe`.
+#[derive(Debug, Clone)]
+pub struct ModuleDescription {
+    pub fullname: String,
+    pub options: Vec<OptionDescription>,
+    pub advanced_options: Vec<OptionDescription>,
+    pub evasion_options: Vec<OptionDescription>,
+    pub targets: Vec<TargetDescription>,
+    pub compatible_payloads: Vec<String>,
+}
+
+/// Every attribute `Module`'s one-at-a-time accessors (`name`, `rank`,
+/// `author`, `references`, `platform`, `arch`, `targets`,
+/// `disclosure_date`, `privileged`, `license`, `notes`) expose, gathered
+/// in one call by `Module::info` - plus `shortname` (refname's last path
+/// segment), `default_target`, and `stance` (aggressive/passive), which
+/// MSF's module base class carries but nothing here read yet.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModuleInfo {
+    pub fullname: String,
+    pub shortname: String,
+    pub name: String,
+    pub module_type: String,
+    pub description: String,
+    pub rank: String,
+    pub author: Vec<String>,
+    pub references: Vec<String>,
+    pub platforms: Vec<String>,
+    pub arches: Vec<String>,
+    pub targets: Vec<String>,
+    pub default_target: Option<i64>,
+    pub disclosure_date: Option<String>,
+    pub privileged: bool,
+    pub license: String,
+    pub stance: String,
+    pub notes: HashMap<String, String>,
+}
+
+impl ModuleInfo {
+    /// Render as a JSON string, for Python/CLI callers that want one
+    /// stable module descriptor instead of reconstructing one field at a
+    /// time from N separate accessor calls.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self)
+            .map_err(|e| AssassinateError::ConversionError(format!("Failed to serialize ModuleInfo: {}", e)))
+    }
+}
+
+/// Map one of MSF's `Msf::OptType` symbols (`"bool"`, `"port"`, `"address"`,
+/// `"enum"`, `"path"`, `"int"`, ...) to the spec string `Conversion::from_str`
+/// accepts. Anything MSF-specific with no `Conversion` equivalent (e.g.
+/// `"raw"`, `"regexp"`) falls back to `"bytes"`, the passthrough variant.
+/// `"enum"` maps to a bare `"enum"`, which `from_str` only accepts once
+/// `options_meta` has appended the option's choices (`"enum:a,b"`).
+fn msf_type_to_conversion_spec(ruby_type: &str) -> String {
+    match ruby_type {
+        "bool" => "bool",
+        "port" => "port",
+        "address" => "address",
+        "path" => "path",
+        "enum" => "enum",
+        "int" | "integer" => "int",
+        "float" => "float",
+        _ => "bytes",
+    }
+    .to_string()
+}
+
+/// Resolve a bare `template` name (e.g. `"template_x86_windows.exe"`) to a
+/// full path, searching `template_path` if given or else MSF's own data
+/// templates directory (`Msf::Config.data_directory/templates`) - mirrors
+/// `Msf::Util::EXE.set_template_default`. Errors with `NoTemplateError` if
+/// the resolved file doesn't exist, instead of letting `to_executable` fail
+/// later with a less specific Ruby exception.
+fn resolve_template_path(ruby: &magnus::Ruby, template: &str, template_path: Option<&str>) -> Result<PathBuf> {
+    let dir = match template_path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let data_dir: String = ruby
+                .eval::<Value>("Msf::Config.data_directory")
+                .map_err(|e| AssassinateError::RubyError(format!("Failed to get Msf::Config.data_directory: {}", e)))
+                .and_then(|v| value_to_string(v).map_err(AssassinateError::from))?;
+            PathBuf::from(data_dir).join("templates")
+        }
+    };
+
+    let full_path = dir.join(template);
+    if !full_path.is_file() {
+        return Err(AssassinateError::NoTemplateError(format!("{}", full_path.display())));
+    }
+
+    Ok(full_path)
+}
+
+/// Write `platform`/`arch` (when given) and then every entry of `options`
+/// into `datastore` via Ruby's `[]=`, in that order so caller-supplied
+/// options can still override `Platform`/`Arch` explicitly. `generate`,
+/// `generate_executable` and `generate_formatted` share this
+/// datastore-populating step through here; `generate_encoded` and
+/// `generate_payload` still set their datastore entries inline.
+fn set_datastore_options(
+    ruby: &magnus::Ruby,
+    datastore: Value,
+    platform: Option<&str>,
+    arch: Option<&str>,
+    options: Option<HashMap<String, String>>,
+) -> Result<()> {
+    if let Some(platform) = platform {
+        let key_val = ruby.str_new("Platform").as_value();
+        let value_val = ruby.str_new(platform).as_value();
+        call_method(datastore, "[]=", &[key_val, value_val])?;
+    }
+    if let Some(arch) = arch {
+        let key_val = ruby.str_new("Arch").as_value();
+        let value_val = ruby.str_new(arch).as_value();
+        call_method(datastore, "[]=", &[key_val, value_val])?;
+    }
+    if let Some(opts_map) = options {
+        for (key, value) in opts_map {
+            let key_val = ruby.str_new(&key).as_value();
+            let value_val = ruby.str_new(&value).as_value();
+            call_method(datastore, "[]=", &[key_val, value_val])?;
+        }
+    }
+    Ok(())
+}
+
 /// Metasploit module instance
 #[cfg_attr(feature = "python-bindings", pyclass(unsendable))]
 #[derive(Clone)]
@@ -259,6 +618,18 @@ impl Module {
     ) -> Result<Option<i64>> {
         let ruby = crate::ruby_bridge::get_ruby()?;
 
+        // Reject an incompatible payload before calling exploit_simple, so the
+        // caller gets a specific error instead of an opaque exception deep
+        // inside it.
+        let compatible = self.compatible_payloads()?;
+        if !compatible.is_empty() && !compatible.iter().any(|p| p == payload) {
+            return Err(AssassinateError::PayloadError(format!(
+                "{} is not compatible with this module. Compatible payloads: {}",
+                payload,
+                compatible.join(", ")
+            )));
+        }
+
         // Build options hash in Ruby
         let opts_val = ruby.hash_new().as_value();
 
@@ -267,9 +638,11 @@ impl Module {
         let payload_val = ruby.str_new(payload).as_value();
         call_method(opts_val, "[]=", &[payload_key, payload_val])?;
 
-        // Set Quiet mode
+        // Quiet is off so the module's own print_status/print_good/etc.
+        // output flows into the 'LocalOutput' capture `with_captured_output`
+        // installs below, instead of being discarded outright.
         let quiet_key = ruby.str_new("Quiet").as_value();
-        let quiet_val = ruby.qtrue().as_value();
+        let quiet_val = ruby.qfalse().as_value();
         call_method(opts_val, "[]=", &[quiet_key, quiet_val])?;
 
         // Set additional options
@@ -281,8 +654,14 @@ impl Module {
             }
         }
 
-        // Call exploit_simple on the module
-        let session_val = call_method(self.ruby_module, "exploit_simple", &[opts_val])?;
+        // Call exploit_simple on the module - this blocks until the exploit
+        // attempt finishes, so release the Python GIL for its duration (the
+        // Ruby GVL stays held; see `call_without_gvl`)
+        let ruby_module = self.ruby_module;
+        let session_val = with_captured_output(&ruby, opts_val, || {
+            crate::ruby_bridge::call_without_gvl(|| call_method(ruby_module, "exploit_simple", &[opts_val]))
+                .map_err(AssassinateError::from)
+        })?;
 
         if is_nil(session_val) {
             Ok(None)
@@ -309,9 +688,10 @@ impl Module {
         // Build options hash in Ruby
         let opts_val = ruby.hash_new().as_value();
 
-        // Set Quiet mode
+        // Quiet is off so the module's own print output flows into the
+        // 'LocalOutput' capture instead of being discarded (see `exploit`).
         let quiet_key = ruby.str_new("Quiet").as_value();
-        let quiet_val = ruby.qtrue().as_value();
+        let quiet_val = ruby.qfalse().as_value();
         call_method(opts_val, "[]=", &[quiet_key, quiet_val])?;
 
         // Set additional options
@@ -323,11 +703,15 @@ impl Module {
             }
         }
 
-        // Call run_simple on the module
-        match call_method(self.ruby_module, "run_simple", &[opts_val]) {
-            Ok(_) => Ok(true),
-            Err(_) => Ok(false),
-        }
+        // Call run_simple on the module - this blocks until the module
+        // finishes running, so release the Python GIL for its duration (the
+        // Ruby GVL stays held; see `call_without_gvl`)
+        let ruby_module = self.ruby_module;
+        let result = with_captured_output(&ruby, opts_val, || {
+            crate::ruby_bridge::call_without_gvl(|| call_method(ruby_module, "run_simple", &[opts_val]))
+                .map_err(AssassinateError::from)
+        });
+        Ok(result.is_ok())
     }
 
     /// Check if target is vulnerable
@@ -338,13 +722,21 @@ impl Module {
         // Build options hash in Ruby
         let opts_val = ruby.hash_new().as_value();
 
-        // Set Quiet mode
+        // Quiet is off so the module's own print output flows into the
+        // 'LocalOutput' capture instead of being discarded (see `exploit`).
         let quiet_key = ruby.str_new("Quiet").as_value();
-        let quiet_val = ruby.qtrue().as_value();
+        let quiet_val = ruby.qfalse().as_value();
         call_method(opts_val, "[]=", &[quiet_key, quiet_val])?;
 
-        // Call check_simple on the module
-        match call_method(self.ruby_module, "check_simple", &[opts_val]) {
+        // Call check_simple on the module - this blocks on network I/O, so
+        // release the Python GIL for its duration (the Ruby GVL stays held;
+        // see `call_without_gvl`)
+        let ruby_module = self.ruby_module;
+        let result = with_captured_output(&ruby, opts_val, || {
+            crate::ruby_bridge::call_without_gvl(|| call_method(ruby_module, "check_simple", &[opts_val]))
+                .map_err(AssassinateError::from)
+        });
+        match result {
             Ok(result) => Ok(value_to_string(result)?),
             Err(e) => {
                 let err_msg = e.to_string();
@@ -371,18 +763,26 @@ impl Module {
 
         match call_method(self.ruby_module, "respond_to?", &[method_name]) {
             Ok(responds) if crate::ruby_bridge::value_to_bool(responds)? => {
-                // Get compatible payloads
-                match call_method(self.ruby_module, "compatible_payloads", &[]) {
-                    Ok(payloads_val) => {
-                        // Set payloads_array variable
-                        ruby.eval::<Value>(&format!("$temp_payloads = {:?}", payloads_val))
-                            .ok();
-
-                        // For now, return empty if we can't easily extract
-                        Ok(vec![])
-                    }
-                    Err(_) => Ok(vec![]),
+                let payloads_val = call_method(self.ruby_module, "compatible_payloads", &[])?;
+
+                if is_nil(payloads_val) {
+                    return Ok(vec![]);
                 }
+
+                // compatible_payloads is an array of [refname, payload_class]
+                // pairs - we only want the refname out of each
+                let payloads_array: magnus::RArray = TryConvert::try_convert(payloads_val)
+                    .map_err(|e: magnus::Error| {
+                        AssassinateError::ConversionError(format!("Failed to convert compatible payloads to array: {}", e))
+                    })?;
+
+                let mut refnames = Vec::new();
+                for pair in payloads_array.into_iter() {
+                    let refname_val = call_method(pair, "first", &[])?;
+                    refnames.push(value_to_string(refname_val)?);
+                }
+
+                Ok(refnames)
             }
             _ => Ok(vec![]),
         }
@@ -487,6 +887,229 @@ impl Module {
         }
     }
 
+    /// Build a structured snapshot of this module's options, targets, and
+    /// compatible payloads in one call, instead of a caller stitching
+    /// together `options()`, `targets()`, and `compatible_payloads()`
+    /// separately and losing the advanced/evasion bucketing and target
+    /// index those flatten away.
+    pub fn describe(&self) -> Result<ModuleDescription> {
+        let fullname = self.fullname()?;
+
+        let options_container = call_method(self.ruby_module, "options", &[])?;
+        let pairs_val = call_method(options_container, "to_a", &[])?;
+        let pairs: magnus::RArray = TryConvert::try_convert(pairs_val).map_err(|e: magnus::Error| {
+            AssassinateError::ConversionError(format!("Failed to convert options to array: {}", e))
+        })?;
+
+        let mut options = Vec::new();
+        let mut advanced_options = Vec::new();
+        let mut evasion_options = Vec::new();
+
+        for pair in pairs.into_iter() {
+            let name_val = call_method(pair, "first", &[])?;
+            let opt_val = call_method(pair, "last", &[])?;
+
+            let name = value_to_string(name_val)?;
+            let option_type = value_to_string(call_method(opt_val, "type", &[])?)?;
+            let required = crate::ruby_bridge::value_to_bool(call_method(opt_val, "required", &[])?)?;
+
+            let default_val = call_method(opt_val, "default", &[])?;
+            let default = if is_nil(default_val) {
+                None
+            } else {
+                Some(value_to_string(default_val)?)
+            };
+
+            let description = value_to_string(call_method(opt_val, "desc", &[])?)?;
+            let is_advanced = crate::ruby_bridge::value_to_bool(call_method(opt_val, "advanced?", &[])?)?;
+            let is_evasion = crate::ruby_bridge::value_to_bool(call_method(opt_val, "evasion?", &[])?)?;
+
+            let entry = OptionDescription { name, option_type, required, default, description };
+            if is_evasion {
+                evasion_options.push(entry);
+            } else if is_advanced {
+                advanced_options.push(entry);
+            } else {
+                options.push(entry);
+            }
+        }
+
+        // Targets, but keeping the index this time (see `TargetDescription`)
+        let ruby = crate::ruby_bridge::get_ruby()?;
+        let method_name = ruby.str_new("targets").as_value();
+        let mut targets = Vec::new();
+        if let Ok(responds) = call_method(self.ruby_module, "respond_to?", &[method_name]) {
+            if crate::ruby_bridge::value_to_bool(responds)? {
+                let targets_val = call_method(self.ruby_module, "targets", &[])?;
+                if !is_nil(targets_val) {
+                    let targets_array: magnus::RArray =
+                        TryConvert::try_convert(targets_val).map_err(|e: magnus::Error| {
+                            AssassinateError::ConversionError(format!("Failed to convert targets to array: {}", e))
+                        })?;
+
+                    for (index, target_obj) in targets_array.into_iter().enumerate() {
+                        let name = value_to_string(call_method(target_obj, "name", &[])?)?;
+                        targets.push(TargetDescription { index, name });
+                    }
+                }
+            }
+        }
+
+        let compatible_payloads = self.compatible_payloads()?;
+
+        Ok(ModuleDescription {
+            fullname,
+            options,
+            advanced_options,
+            evasion_options,
+            targets,
+            compatible_payloads,
+        })
+    }
+
+    /// Gather every attribute the individual accessors above expose, plus
+    /// `shortname`/`default_target`/`stance`, into one `ModuleInfo` - see
+    /// its doc comment for why.
+    pub fn info(&self) -> Result<ModuleInfo> {
+        let fullname = self.fullname()?;
+        let shortname = fullname.rsplit('/').next().unwrap_or(&fullname).to_string();
+
+        let default_target = call_method(self.ruby_module, "default_target", &[])
+            .ok()
+            .filter(|v| !is_nil(*v))
+            .and_then(|v| TryConvert::try_convert(v).ok());
+
+        let stance = call_method(self.ruby_module, "stance", &[])
+            .ok()
+            .and_then(|v| value_to_string(v).ok())
+            .unwrap_or_default();
+
+        Ok(ModuleInfo {
+            fullname,
+            shortname,
+            name: self.name()?,
+            module_type: self.module_type()?,
+            description: self.description()?,
+            rank: self.rank()?,
+            author: self.author()?,
+            references: self.references()?,
+            platforms: self.platform()?,
+            arches: self.arch()?,
+            targets: self.targets()?,
+            default_target,
+            disclosure_date: self.disclosure_date()?,
+            privileged: self.privileged()?,
+            license: self.license()?,
+            stance,
+            notes: self.notes()?,
+        })
+    }
+
+    /// Build the full option schema for this module - `name`, `type`
+    /// (normalized to a `Conversion` spec string), `required`, `default`,
+    /// `description`, and, for `OptEnum` options, the allowed choices.
+    /// Unlike `describe`'s `OptionDescription` (which keeps Ruby's own type
+    /// label and drops enum choices), this is meant to drive typed
+    /// validation and conversion directly via `Conversion::from_str`.
+    pub fn options_meta(&self) -> Result<Vec<OptionSpec>> {
+        let options_container = call_method(self.ruby_module, "options", &[])?;
+        let pairs_val = call_method(options_container, "to_a", &[])?;
+        let pairs: magnus::RArray = TryConvert::try_convert(pairs_val).map_err(|e: magnus::Error| {
+            AssassinateError::ConversionError(format!("Failed to convert options to array: {}", e))
+        })?;
+
+        let mut specs = Vec::new();
+        for pair in pairs.into_iter() {
+            let name_val = call_method(pair, "first", &[])?;
+            let opt_val = call_method(pair, "last", &[])?;
+
+            let name = value_to_string(name_val)?;
+            let ruby_type = value_to_string(call_method(opt_val, "type", &[])?)?;
+            let required = crate::ruby_bridge::value_to_bool(call_method(opt_val, "required", &[])?)?;
+
+            let default_val = call_method(opt_val, "default", &[])?;
+            let default = if is_nil(default_val) {
+                None
+            } else {
+                Some(value_to_string(default_val)?)
+            };
+
+            let description = value_to_string(call_method(opt_val, "desc", &[])?)?;
+
+            let enums = if ruby_type == "enum" {
+                let enums_val = call_method(opt_val, "enums", &[])?;
+                if is_nil(enums_val) {
+                    None
+                } else {
+                    let enums_array: magnus::RArray = TryConvert::try_convert(enums_val)
+                        .map_err(|e: magnus::Error| {
+                            AssassinateError::ConversionError(format!("Failed to convert enum choices: {}", e))
+                        })?;
+                    let mut choices = Vec::new();
+                    for choice_val in enums_array.into_iter() {
+                        choices.push(value_to_string(choice_val)?);
+                    }
+                    Some(choices)
+                }
+            } else {
+                None
+            };
+
+            let option_type = match &enums {
+                Some(choices) => format!("enum:{}", choices.join(",")),
+                None => msf_type_to_conversion_spec(&ruby_type),
+            };
+
+            specs.push(OptionSpec {
+                name,
+                option_type,
+                required,
+                default,
+                description,
+                enums,
+            });
+        }
+
+        Ok(specs)
+    }
+
+    /// Validate `options` against this module's declared required options
+    /// (via `describe`), then push them all into the live Ruby datastore in
+    /// one `RHash` update. Returns `ModuleValidationError` listing whatever
+    /// required options are missing instead of letting Ruby fail later with
+    /// a less specific error once the module actually runs.
+    pub fn apply_options(&self, options: &ModuleOptions) -> Result<()> {
+        let description = self.describe()?;
+        let missing: Vec<String> = description
+            .options
+            .iter()
+            .chain(description.advanced_options.iter())
+            .chain(description.evasion_options.iter())
+            .filter(|opt| opt.required && opt.default.is_none() && !options.values.contains_key(&opt.name))
+            .map(|opt| opt.name.clone())
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(AssassinateError::ModuleValidationError(format!(
+                "missing required option(s): {}",
+                missing.join(", ")
+            )));
+        }
+
+        let ruby = crate::ruby_bridge::get_ruby()?;
+        let hash_val = ruby.hash_new().as_value();
+        for (key, value) in &options.values {
+            let key_val = ruby.str_new(key).as_value();
+            let value_val = ruby.str_new(&value.to_datastore_string()).as_value();
+            call_method(hash_val, "[]=", &[key_val, value_val])?;
+        }
+
+        let datastore_val = call_method(self.ruby_module, "datastore", &[])?;
+        call_method(datastore_val, "update", &[hash_val])?;
+
+        Ok(())
+    }
+
     /// Get vulnerability disclosure date
     pub fn disclosure_date(&self) -> Result<Option<String>> {
         let date_val = call_method(self.ruby_module, "disclosure_date", &[])?;
@@ -576,6 +1199,51 @@ impl Module {
     }
 }
 
+/// A single typed option value for `ModuleOptions` - validated/normalized
+/// on the Rust side instead of a bare string a caller has to get right
+/// themselves (MSF's own datastore only ever stores strings, so this still
+/// bottoms out as a string once applied - see `OptionValue::to_datastore_string`).
+#[derive(Debug, Clone)]
+pub enum OptionValue {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+    Port(u16),
+    Host(String),
+}
+
+impl OptionValue {
+    fn to_datastore_string(&self) -> String {
+        match self {
+            OptionValue::Str(s) | OptionValue::Host(s) => s.clone(),
+            OptionValue::Int(n) => n.to_string(),
+            OptionValue::Bool(b) => b.to_string(),
+            OptionValue::Port(p) => p.to_string(),
+        }
+    }
+}
+
+/// A typed batch of module options, applied to a module's live Ruby
+/// datastore in one `RHash` update (`Module::apply_options`) instead of one
+/// `eval`-interpolated Ruby script per run - a quote in an option value
+/// can't break out of anything here, since no option value is ever woven
+/// into a string of Ruby source.
+#[derive(Debug, Clone, Default)]
+pub struct ModuleOptions {
+    values: HashMap<String, OptionValue>,
+}
+
+impl ModuleOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, value: OptionValue) -> &mut Self {
+        self.values.insert(name.into(), value);
+        self
+    }
+}
+
 #[cfg_attr(feature = "python-bindings", pyclass(unsendable))]
 #[derive(Clone)]
 pub struct DataStore {
@@ -664,6 +1332,32 @@ impl DataStore {
         Ok(())
     }
 
+    /// Read `key` and coerce it through `conversion`, returning a typed
+    /// `DataStoreValue` instead of the raw string `get` hands back. A
+    /// missing key converts `""` the same as a set-but-empty one would
+    /// (MSF's datastore doesn't distinguish the two), so callers that care
+    /// about presence should check `get`/`keys` first.
+    #[cfg(not(feature = "python-bindings"))]
+    pub fn typed_get(&self, key: &str, conversion: &Conversion) -> Result<DataStoreValue> {
+        let raw = self.get(key)?.unwrap_or_default();
+        let coerced = conversion
+            .convert(&serde_json::Value::String(raw))
+            .map_err(|e| AssassinateError::DataStoreError(format!("{}: {}", key, e)))?;
+        DataStoreValue::from_conversion(conversion, coerced)
+            .map_err(|e| AssassinateError::DataStoreError(format!("{}: {}", key, e)))
+    }
+
+    /// Validate `value` against `conversion` and write its string form into
+    /// the datastore, rejecting it up front instead of letting Ruby accept
+    /// whatever string MSF's option parser later fails on.
+    #[cfg(not(feature = "python-bindings"))]
+    pub fn typed_set(&self, key: &str, conversion: &Conversion, value: &DataStoreValue) -> Result<()> {
+        conversion
+            .convert(&serde_json::Value::String(value.to_datastore_string()))
+            .map_err(|e| AssassinateError::DataStoreError(format!("{}: {}", key, e)))?;
+        self.set(key, &value.to_datastore_string())
+    }
+
     #[cfg(feature = "python-bindings")]
     pub fn __repr__(&self) -> Result<String> {
         Ok(format!("<DataStore {}>", self.to_dict()?.len()))
@@ -790,13 +1484,16 @@ impl Session {
     pub fn read(&self, length: Option<usize>) -> Result<String> {
         let ruby = crate::ruby_bridge::get_ruby()?;
 
+        let ruby_session = self.ruby_session;
+        // Reading session I/O blocks on the network, so release the Python
+        // GIL for its duration (the Ruby GVL stays held; see `call_without_gvl`)
         let result = if let Some(len) = length {
             let len_val = ruby
                 .eval::<Value>(&format!("{}", len))
                 .map_err(|e| AssassinateError::ConversionError(e.to_string()))?;
-            call_method(self.ruby_session, "read", &[len_val])?
+            crate::ruby_bridge::call_without_gvl(|| call_method(ruby_session, "read", &[len_val]))?
         } else {
-            call_method(self.ruby_session, "read", &[])?
+            crate::ruby_bridge::call_without_gvl(|| call_method(ruby_session, "read", &[]))?
         };
 
         if is_nil(result) {
@@ -810,14 +1507,14 @@ impl Session {
     #[cfg(feature = "python-bindings")]
     #[pyo3(signature = (command))]
     pub fn execute(&self, command: &str) -> Result<String> {
-        let ruby = crate::ruby_bridge::get_ruby()?;
-
         // Write command
         self.write(&format!("{}\n", command))?;
 
-        // Give it time to execute (you may want to make this configurable)
-        ruby.eval::<Value>("sleep 0.5")
-            .map_err(|e| AssassinateError::RubyError(e.to_string()))?;
+        // Give it time to execute (you may want to make this configurable).
+        // A plain native sleep instead of `ruby.eval("sleep 0.5")`, so the
+        // wait doesn't run Ruby code; the Python GIL is only released around
+        // the blocking `read` below, not during this sleep.
+        std::thread::sleep(std::time::Duration::from_millis(500));
 
         // Read response
         self.read(None)
@@ -1171,6 +1868,213 @@ impl JobManager {
     }
 }
 
+/// Output mode for `PayloadGenerator::generate_payload`, modeled on the
+/// format list `msfpayload`/`msfvenom` have historically supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadFormat {
+    Raw,
+    Ruby,
+    Perl,
+    Python,
+    CSharp,
+    Js,
+    C,
+    Hex,
+    Base64,
+    Exe,
+    Dll,
+    Elf,
+    Macho,
+    Vba,
+    /// `vbapplication` - a VBA macro wrapped for direct execution rather
+    /// than embedding in a document.
+    VbApplication,
+    War,
+    /// PowerShell source (`psh`)
+    PowerShell,
+    /// Not a rendering at all - returns the payload module's description
+    /// instead of generating anything.
+    Summary,
+}
+
+impl PayloadFormat {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "raw" => Ok(PayloadFormat::Raw),
+            "ruby" | "rb" => Ok(PayloadFormat::Ruby),
+            "perl" | "pl" => Ok(PayloadFormat::Perl),
+            "python" | "py" => Ok(PayloadFormat::Python),
+            "csharp" | "cs" => Ok(PayloadFormat::CSharp),
+            "js" | "javascript" => Ok(PayloadFormat::Js),
+            "c" => Ok(PayloadFormat::C),
+            "hex" => Ok(PayloadFormat::Hex),
+            "base64" => Ok(PayloadFormat::Base64),
+            "exe" => Ok(PayloadFormat::Exe),
+            "dll" => Ok(PayloadFormat::Dll),
+            "elf" => Ok(PayloadFormat::Elf),
+            "macho" => Ok(PayloadFormat::Macho),
+            "vba" => Ok(PayloadFormat::Vba),
+            "vbapplication" => Ok(PayloadFormat::VbApplication),
+            "war" => Ok(PayloadFormat::War),
+            "psh" | "powershell" => Ok(PayloadFormat::PowerShell),
+            "summary" => Ok(PayloadFormat::Summary),
+            other => Err(AssassinateError::InvalidFormat(format!(
+                "unknown payload format: {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// Every format name `PayloadFormat::from_str` accepts, for
+    /// `PayloadGenerator::list_formats`.
+    pub fn all() -> &'static [&'static str] {
+        &[
+            "raw",
+            "ruby",
+            "perl",
+            "python",
+            "csharp",
+            "js",
+            "c",
+            "hex",
+            "base64",
+            "exe",
+            "dll",
+            "elf",
+            "macho",
+            "vba",
+            "vbapplication",
+            "war",
+            "psh",
+            "summary",
+        ]
+    }
+
+    /// Whether this format renders through `Msf::Util::EXE` (a native
+    /// executable/library) rather than `Msf::Simple::Buffer.transform` (a
+    /// source/encoded text representation).
+    fn is_executable_format(&self) -> bool {
+        matches!(self, PayloadFormat::Exe | PayloadFormat::Dll | PayloadFormat::Elf | PayloadFormat::Macho)
+    }
+
+    /// The format name `Msf::Simple::Buffer.transform` expects; only
+    /// meaningful for the source/encoded-text formats - the executable
+    /// formats are rendered through `Msf::Util::EXE` instead, and `Summary`
+    /// never reaches this at all.
+    fn as_msf_format(&self) -> &'static str {
+        match self {
+            PayloadFormat::Ruby => "ruby",
+            PayloadFormat::Perl => "perl",
+            PayloadFormat::Python => "python",
+            PayloadFormat::CSharp => "csharp",
+            PayloadFormat::Js => "js_be",
+            PayloadFormat::C => "c",
+            PayloadFormat::Hex => "hex",
+            PayloadFormat::Base64 => "base64",
+            PayloadFormat::Vba => "vba",
+            PayloadFormat::VbApplication => "vbapplication",
+            PayloadFormat::War => "war",
+            PayloadFormat::PowerShell => "psh",
+            PayloadFormat::Raw
+            | PayloadFormat::Exe
+            | PayloadFormat::Dll
+            | PayloadFormat::Elf
+            | PayloadFormat::Macho
+            | PayloadFormat::Summary => "raw",
+        }
+    }
+
+    /// The `Msf::Util::EXE` method that renders this format, given the
+    /// target `platform` (e.g. `"linux"`, `"osx"`) - only meaningful for
+    /// `is_executable_format` variants.
+    fn exe_method(&self, platform: &str) -> &'static str {
+        match self {
+            PayloadFormat::Dll => "to_win32pe_dll",
+            PayloadFormat::Elf => "to_linux_x86_elf",
+            PayloadFormat::Macho => "to_osx_x86_macho",
+            _ => {
+                if platform.eq_ignore_ascii_case("linux") {
+                    "to_linux_x86_elf"
+                } else if platform.eq_ignore_ascii_case("osx") || platform.eq_ignore_ascii_case("macos") {
+                    "to_osx_x86_macho"
+                } else {
+                    "to_executable"
+                }
+            }
+        }
+    }
+
+    /// The one platform this format can render for, if it's pinned to a
+    /// single one - `Dll`/`Elf`/`Macho` each wrap a single `Msf::Util::EXE`
+    /// method for one OS, so asking for e.g. `Macho` against a Windows
+    /// payload would otherwise fail deep inside that Ruby call instead of
+    /// up front with a clear message.
+    fn expected_platform(&self) -> Option<&'static str> {
+        match self {
+            PayloadFormat::Dll => Some("windows"),
+            PayloadFormat::Elf => Some("linux"),
+            PayloadFormat::Macho => Some("osx"),
+            _ => None,
+        }
+    }
+}
+
+/// Build the options hash `Msf::Util::EXE`'s `to_*` methods take: resolves
+/// `template`/`template_path` into `:template` (plus `:keep_template_working`
+/// if `keep`), and sets `:secname` when given, so the custom PE/ELF section
+/// the payload is injected under can be named instead of MSF picking its
+/// default - shared by `generate_formatted` and `format_raw`, which both
+/// build this same hash around their own differently-sourced raw payload.
+fn build_exe_opts(
+    ruby: &magnus::Ruby,
+    template: Option<&str>,
+    template_path: Option<&str>,
+    keep: bool,
+    secname: Option<&str>,
+) -> Result<Value> {
+    let opts = ruby.hash_new();
+
+    if let Some(template_name) = template {
+        let resolved = resolve_template_path(ruby, template_name, template_path)?;
+        let template_key: Value = ruby.eval(":template").map_err(|e| AssassinateError::RubyError(e.to_string()))?;
+        let template_val = ruby.str_new(&resolved.display().to_string()).as_value();
+        call_method(opts.as_value(), "[]=", &[template_key, template_val])?;
+
+        if keep {
+            let keep_key: Value = ruby
+                .eval(":keep_template_working")
+                .map_err(|e| AssassinateError::RubyError(e.to_string()))?;
+            call_method(opts.as_value(), "[]=", &[keep_key, ruby.qtrue().as_value()])?;
+        }
+    }
+
+    if let Some(section_name) = secname {
+        let secname_key: Value = ruby.eval(":secname").map_err(|e| AssassinateError::RubyError(e.to_string()))?;
+        let secname_val = ruby.str_new(section_name).as_value();
+        call_method(opts.as_value(), "[]=", &[secname_key, secname_val])?;
+    }
+
+    Ok(opts.as_value())
+}
+
+/// Reject a format/platform combination that `Msf::Util::EXE` has no
+/// method for (e.g. `Macho` against a Windows payload) before calling into
+/// `Msf::Util::EXE`, so the caller gets a `PayloadError` naming the mismatch
+/// instead of a generic Ruby exception from deep inside `to_*_macho`/etc.
+fn validate_format_platform(format: PayloadFormat, platform: &str) -> Result<()> {
+    if let Some(expected) = format.expected_platform() {
+        let compatible = platform.eq_ignore_ascii_case(expected)
+            || (expected == "osx" && platform.eq_ignore_ascii_case("macos"));
+        if !compatible {
+            return Err(AssassinateError::PayloadError(format!(
+                "{:?} format requires platform \"{}\", but \"{}\" was requested",
+                format, expected, platform
+            )));
+        }
+    }
+    Ok(())
+}
+
 #[cfg_attr(feature = "python-bindings", pyclass(unsendable))]
 #[derive(Clone)]
 pub struct PayloadGenerator {
@@ -1208,13 +2112,9 @@ impl PayloadGenerator {
         }
 
         // Set options
-        if let Some(opts_map) = options {
+        if options.is_some() {
             let datastore = call_method(payload, "datastore", &[])?;
-            for (key, value) in opts_map {
-                let key_val = ruby.str_new(&key).as_value();
-                let value_val = ruby.str_new(&value).as_value();
-                call_method(datastore, "[]=", &[key_val, value_val])?;
-            }
+            set_datastore_options(&ruby, datastore, None, None, options)?;
         }
 
         // Generate the payload
@@ -1302,6 +2202,333 @@ impl PayloadGenerator {
         Ok(bytes)
     }
 
+    /// Generate `payload_name`'s raw output, then run it through `encoders`
+    /// - a comma-separated chain, e.g. `"x86/shikata_ga_nai,x86/countdown"`,
+    /// or `""` to auto-select the highest-ranked encoder compatible with
+    /// the payload's arch - applying each `iterations` times in order, the
+    /// equivalent of `msfvenom -e ... -i ... -b ...`. Unlike `generate_encoded` (which
+    /// lets the payload module encode itself via its `ENCODER`/`Iterations`
+    /// datastore options), this drives each encoder module directly so a
+    /// multi-encoder chain and a hard `encoder_space`/`payload_space`
+    /// budget are both possible.
+    #[cfg_attr(
+        feature = "python-bindings",
+        pyo3(signature = (payload_name, encoders, iterations=1, badchars=None, encoder_space=None, payload_space=None, **options))
+    )]
+    pub fn generate_chained(
+        &self,
+        payload_name: &str,
+        encoders: &str,
+        iterations: Option<u32>,
+        badchars: Option<&str>,
+        encoder_space: Option<usize>,
+        payload_space: Option<usize>,
+        options: Option<HashMap<String, String>>,
+    ) -> Result<Vec<u8>> {
+        let buffer = self.generate(payload_name, options)?;
+        let encoders = self.resolve_encoders(encoders, Some(payload_name))?;
+        self.apply_encoder_chain(buffer, &encoders, iterations.unwrap_or(1), badchars, encoder_space, payload_space)
+    }
+
+    /// Raw-bytes counterpart to `generate_chained`, for msfvenom's `-p -` /
+    /// stdin mode: `raw` is shellcode the caller already has - produced by
+    /// another tool, or by a prior `generate`/`generate_chained` call -
+    /// instead of a freshly created payload module. Skips
+    /// `modules.create`/`payload.generate` entirely and runs the same
+    /// encoder chain over the supplied buffer, turning the crate into a
+    /// general shellcode post-processor rather than only a payload
+    /// generator. `encoders` may be `""` here too, in which case the
+    /// highest-ranked encoder overall is auto-selected (there's no payload
+    /// module to read an arch off of to narrow the choice).
+    #[cfg_attr(
+        feature = "python-bindings",
+        pyo3(signature = (raw, encoders, iterations=1, badchars=None, encoder_space=None, payload_space=None))
+    )]
+    pub fn encode_raw(
+        &self,
+        raw: Vec<u8>,
+        encoders: &str,
+        iterations: Option<u32>,
+        badchars: Option<&str>,
+        encoder_space: Option<usize>,
+        payload_space: Option<usize>,
+    ) -> Result<Vec<u8>> {
+        let encoders = self.resolve_encoders(encoders, None)?;
+        self.apply_encoder_chain(raw, &encoders, iterations.unwrap_or(1), badchars, encoder_space, payload_space)
+    }
+
+    /// If `encoders` already names at least one encoder, use it unchanged;
+    /// otherwise auto-select the highest-ranked encoder MSF has that's
+    /// compatible with `payload_name`'s arch (or, with no payload to read
+    /// an arch off of, the highest-ranked encoder overall) - mirrors
+    /// msfvenom falling back to its own encoder choice when `-e` is
+    /// omitted instead of requiring the caller to name one.
+    fn resolve_encoders(&self, encoders: &str, payload_name: Option<&str>) -> Result<String> {
+        if !encoders.trim().is_empty() {
+            return Ok(encoders.to_string());
+        }
+
+        let payload_archs: Vec<String> = match payload_name {
+            Some(name) => {
+                let ruby = crate::ruby_bridge::get_ruby()?;
+                let name_val = ruby.str_new(name).as_value();
+                let modules_mgr = call_method(self.ruby_framework, "modules", &[])?;
+                let payload = call_method(modules_mgr, "create", &[name_val])?;
+                if is_nil(payload) {
+                    Vec::new()
+                } else {
+                    call_method(payload, "arch", &[])
+                        .ok()
+                        .and_then(|v| TryConvert::try_convert(v).ok())
+                        .unwrap_or_default()
+                }
+            }
+            None => Vec::new(),
+        };
+
+        self.auto_select_encoder(&payload_archs)
+    }
+
+    /// Pick the encoder MSF itself ranks highest among those compatible
+    /// with `payload_archs` (no filter if empty) - `Module::rank` returns
+    /// MSF's own numeric ranking constant as a string (e.g. `"600"` for
+    /// `ExcellentRanking`), so higher parses to higher.
+    fn auto_select_encoder(&self, payload_archs: &[String]) -> Result<String> {
+        let mut best: Option<(i64, String)> = None;
+        for summary in self.list_encoders_detailed()? {
+            if !payload_archs.is_empty()
+                && !summary.arches.is_empty()
+                && !summary.arches.iter().any(|a| payload_archs.contains(a))
+            {
+                continue;
+            }
+            let rank: i64 = summary.rank.parse().unwrap_or(0);
+            if best.as_ref().map(|(best_rank, _)| rank > *best_rank).unwrap_or(true) {
+                best = Some((rank, summary.name));
+            }
+        }
+
+        best.map(|(_, name)| name)
+            .ok_or_else(|| AssassinateError::PayloadError("No compatible encoder available for auto-selection".to_string()))
+    }
+
+    fn apply_encoder_chain(
+        &self,
+        mut buffer: Vec<u8>,
+        encoders: &str,
+        iterations: u32,
+        badchars: Option<&str>,
+        encoder_space: Option<usize>,
+        payload_space: Option<usize>,
+    ) -> Result<Vec<u8>> {
+        let ruby = crate::ruby_bridge::get_ruby()?;
+
+        let modules_mgr = call_method(self.ruby_framework, "modules", &[])?;
+        let encoders_mgr = call_method(modules_mgr, "encoders", &[])?;
+        let badchars_val = ruby.str_new(badchars.unwrap_or("")).as_value();
+
+        for encoder_name in encoders.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            let name_val = ruby.str_new(encoder_name).as_value();
+            let encoder = call_method(encoders_mgr, "create", &[name_val])?;
+            if is_nil(encoder) {
+                return Err(AssassinateError::PayloadError(format!("Encoder not found: {}", encoder_name)));
+            }
+
+            for _ in 0..iterations {
+                let buf_val = unsafe { magnus::RString::from_slice(&buffer) }.as_value();
+                let encoded = call_method(encoder, "encode", &[buf_val, badchars_val])?;
+
+                let rstring: magnus::RString = TryConvert::try_convert(encoded).map_err(|e: magnus::Error| {
+                    AssassinateError::ConversionError(format!("Failed to convert encoded payload to RString: {}", e))
+                })?;
+                buffer = unsafe { rstring.as_slice() }.to_vec();
+
+                if let Some(budget) = encoder_space {
+                    if buffer.len() > budget {
+                        return Err(AssassinateError::EncoderSpaceViolation(format!(
+                            "{} produced {} bytes, exceeding the {}-byte encoder space budget",
+                            encoder_name,
+                            buffer.len(),
+                            budget
+                        )));
+                    }
+                }
+            }
+        }
+
+        if let Some(budget) = payload_space {
+            if buffer.len() > budget {
+                return Err(AssassinateError::PayloadSpaceViolation(format!(
+                    "final payload is {} bytes, exceeding the {}-byte payload space budget",
+                    buffer.len(),
+                    budget
+                )));
+            }
+        }
+
+        Ok(buffer)
+    }
+
+    /// Port of msfvenom's `--smallest`: try every encoder compatible with
+    /// `payload_name`'s arch, encoding the raw payload once with each, and
+    /// return whichever produced the shortest buffer - falling back to the
+    /// unencoded payload if no encoder beats it. An encoder that's
+    /// incompatible with the payload's arch, or that fails outright (e.g.
+    /// on bad chars), is skipped rather than failing the whole search.
+    #[cfg_attr(feature = "python-bindings", pyo3(signature = (payload_name, badchars=None, **options)))]
+    pub fn generate_smallest(
+        &self,
+        payload_name: &str,
+        badchars: Option<&str>,
+        options: Option<HashMap<String, String>>,
+    ) -> Result<Vec<u8>> {
+        let ruby = crate::ruby_bridge::get_ruby()?;
+        let raw = self.generate(payload_name, options)?;
+
+        let name_val = ruby.str_new(payload_name).as_value();
+        let modules_mgr = call_method(self.ruby_framework, "modules", &[])?;
+        let payload = call_method(modules_mgr, "create", &[name_val])?;
+        if is_nil(payload) {
+            return Err(AssassinateError::PayloadError(format!(
+                "Payload not found: {}",
+                payload_name
+            )));
+        }
+
+        let payload_archs: Vec<String> = call_method(payload, "arch", &[])
+            .ok()
+            .and_then(|v| TryConvert::try_convert(v).ok())
+            .unwrap_or_default();
+
+        let encoders_mgr = call_method(modules_mgr, "encoders", &[])?;
+        let badchars_val = ruby.str_new(badchars.unwrap_or("")).as_value();
+
+        let mut best = raw.clone();
+        for encoder_name in self.list_encoders()? {
+            let encoder_name_val = ruby.str_new(&encoder_name).as_value();
+            let encoder = match call_method(encoders_mgr, "create", &[encoder_name_val]) {
+                Ok(e) if !is_nil(e) => e,
+                _ => continue,
+            };
+
+            if !payload_archs.is_empty() {
+                let encoder_archs: Vec<String> = call_method(encoder, "arch", &[])
+                    .ok()
+                    .and_then(|v| TryConvert::try_convert(v).ok())
+                    .unwrap_or_default();
+                if !encoder_archs.is_empty() && !encoder_archs.iter().any(|a| payload_archs.contains(a)) {
+                    continue;
+                }
+            }
+
+            let buf_val = unsafe { magnus::RString::from_slice(&raw) }.as_value();
+            let encoded = match call_method(encoder, "encode", &[buf_val, badchars_val]) {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+            let rstring: magnus::RString = match TryConvert::try_convert(encoded) {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+            let bytes = unsafe { rstring.as_slice() }.to_vec();
+
+            if bytes.len() < best.len() {
+                best = bytes;
+            }
+        }
+
+        Ok(best)
+    }
+
+    /// Port of msfvenom's `--space`/`--nops`/`--pad-nops`: pad `payload`
+    /// with a NOP sled so the total reaches a fixed size, for exploits
+    /// with fixed-size buffers. `nops`, when given, is an exact sled
+    /// length; otherwise (`padnops` alone) the sled fills whatever room
+    /// `space` leaves after `payload`. The sled is built from the first
+    /// NOP generator (from `list_nops`) compatible with `arch` and
+    /// prepended, matching msfvenom's NOP-then-shellcode layout. Returns
+    /// `payload` unchanged if neither `nops` nor `padnops` is set, and
+    /// `PayloadSpaceViolation` if the payload plus sled would exceed
+    /// `space`.
+    #[cfg_attr(
+        feature = "python-bindings",
+        pyo3(signature = (payload, arch, space=None, nops=None, padnops=false))
+    )]
+    pub fn pad_with_nops(
+        &self,
+        payload: Vec<u8>,
+        arch: &str,
+        space: Option<usize>,
+        nops: Option<usize>,
+        padnops: bool,
+    ) -> Result<Vec<u8>> {
+        if nops.is_none() && !padnops {
+            return Ok(payload);
+        }
+
+        let sled_len = match nops {
+            Some(n) => n,
+            None => space.map(|total| total.saturating_sub(payload.len())).unwrap_or(0),
+        };
+
+        if let Some(total) = space {
+            if payload.len() + sled_len > total {
+                return Err(AssassinateError::PayloadSpaceViolation(format!(
+                    "payload ({} bytes) plus a {}-byte NOP sled exceeds the {}-byte space budget",
+                    payload.len(),
+                    sled_len,
+                    total
+                )));
+            }
+        }
+
+        if sled_len == 0 {
+            return Ok(payload);
+        }
+
+        let ruby = crate::ruby_bridge::get_ruby()?;
+        let modules_mgr = call_method(self.ruby_framework, "modules", &[])?;
+        let nops_mgr = call_method(modules_mgr, "nops", &[])?;
+        let len_val: Value = ruby
+            .eval(&format!("{}", sled_len))
+            .map_err(|e| AssassinateError::RubyError(e.to_string()))?;
+
+        let mut sled: Option<Vec<u8>> = None;
+        for nop_name in self.list_nops()? {
+            let name_val = ruby.str_new(&nop_name).as_value();
+            let nop = match call_method(nops_mgr, "create", &[name_val]) {
+                Ok(n) if !is_nil(n) => n,
+                _ => continue,
+            };
+
+            let nop_archs: Vec<String> = call_method(nop, "arch", &[])
+                .ok()
+                .and_then(|v| TryConvert::try_convert(v).ok())
+                .unwrap_or_default();
+            if !nop_archs.is_empty() && !nop_archs.iter().any(|a| a == arch) {
+                continue;
+            }
+
+            let opts = ruby.hash_new();
+            let generated = match call_method(nop, "generate_sled", &[len_val, opts.as_value()]) {
+                Ok(v) if !is_nil(v) => v,
+                _ => continue,
+            };
+            let rstring: magnus::RString = match TryConvert::try_convert(generated) {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+            sled = Some(unsafe { rstring.as_slice() }.to_vec());
+            break;
+        }
+
+        let mut sled = sled.ok_or_else(|| {
+            AssassinateError::PayloadError(format!("No NOP generator available for arch: {}", arch))
+        })?;
+        sled.extend_from_slice(&payload);
+        Ok(sled)
+    }
+
     /// List all available payloads
     pub fn list_payloads(&self) -> Result<Vec<String>> {
         let modules_mgr = call_method(self.ruby_framework, "modules", &[])?;
@@ -1316,6 +2543,93 @@ impl PayloadGenerator {
         Ok(payload_list)
     }
 
+    /// List every output format `generate_formatted`/`generate_payload`
+    /// accept, for a caller driving a format picker interactively.
+    pub fn list_formats(&self) -> Vec<String> {
+        PayloadFormat::all().iter().map(|s| s.to_string()).collect()
+    }
+
+    /// List all available encoders (e.g. `x86/shikata_ga_nai`)
+    pub fn list_encoders(&self) -> Result<Vec<String>> {
+        let modules_mgr = call_method(self.ruby_framework, "modules", &[])?;
+        let encoders = call_method(modules_mgr, "encoders", &[])?;
+        let refnames = call_method(encoders, "module_refnames", &[])?;
+
+        let encoder_list: Vec<String> = TryConvert::try_convert(refnames).map_err(|e: magnus::Error| {
+            AssassinateError::ConversionError(format!("Failed to convert encoder list: {}", e))
+        })?;
+
+        Ok(encoder_list)
+    }
+
+    /// List all available NOP generators (e.g. `x86/single_byte`), used to
+    /// pick an arch-appropriate sled module when padding a payload.
+    pub fn list_nops(&self) -> Result<Vec<String>> {
+        let modules_mgr = call_method(self.ruby_framework, "modules", &[])?;
+        let nops = call_method(modules_mgr, "nops", &[])?;
+        let refnames = call_method(nops, "module_refnames", &[])?;
+
+        let nop_list: Vec<String> = TryConvert::try_convert(refnames).map_err(|e: magnus::Error| {
+            AssassinateError::ConversionError(format!("Failed to convert NOP list: {}", e))
+        })?;
+
+        Ok(nop_list)
+    }
+
+    /// `list_payloads` with each entry's description, rank, and declared
+    /// platform/arch compatibility - mirrors `msfvenom --list payloads`, so
+    /// a caller can validate a name and show a picker before ever calling
+    /// `generate`, instead of only discovering "Payload not found" at
+    /// creation time.
+    pub fn list_payloads_detailed(&self) -> Result<Vec<ModuleSummary>> {
+        let modules_mgr = call_method(self.ruby_framework, "modules", &[])?;
+        let payloads_mgr = call_method(modules_mgr, "payloads", &[])?;
+        self.list_payloads()?
+            .iter()
+            .map(|name| self.summarize_module(payloads_mgr, name))
+            .collect()
+    }
+
+    /// `list_encoders` with each entry's description, rank, and declared
+    /// platform/arch compatibility - mirrors `msfvenom --list encoders`.
+    pub fn list_encoders_detailed(&self) -> Result<Vec<ModuleSummary>> {
+        let modules_mgr = call_method(self.ruby_framework, "modules", &[])?;
+        let encoders_mgr = call_method(modules_mgr, "encoders", &[])?;
+        self.list_encoders()?
+            .iter()
+            .map(|name| self.summarize_module(encoders_mgr, name))
+            .collect()
+    }
+
+    /// `list_nops` with each entry's description, rank, and declared
+    /// platform/arch compatibility - mirrors `msfvenom --list nops`.
+    pub fn list_nops_detailed(&self) -> Result<Vec<ModuleSummary>> {
+        let modules_mgr = call_method(self.ruby_framework, "modules", &[])?;
+        let nops_mgr = call_method(modules_mgr, "nops", &[])?;
+        self.list_nops()?
+            .iter()
+            .map(|name| self.summarize_module(nops_mgr, name))
+            .collect()
+    }
+
+    fn summarize_module(&self, module_set: Value, refname: &str) -> Result<ModuleSummary> {
+        let ruby = crate::ruby_bridge::get_ruby()?;
+        let name_val = ruby.str_new(refname).as_value();
+        let instance = call_method(module_set, "create", &[name_val])?;
+        if is_nil(instance) {
+            return Err(AssassinateError::ModuleNotFound(refname.to_string()));
+        }
+        let module = Module { ruby_module: instance };
+
+        Ok(ModuleSummary {
+            name: refname.to_string(),
+            description: module.description()?,
+            rank: module.rank()?,
+            platforms: module.platform()?,
+            arches: module.arch()?,
+        })
+    }
+
     /// Generate a standalone executable payload
     #[cfg_attr(feature = "python-bindings", pyo3(signature = (payload_name, platform, arch, **options)))]
     pub fn generate_executable(
@@ -1339,26 +2653,9 @@ impl PayloadGenerator {
             )));
         }
 
-        // Get datastore
+        // Get datastore, then set platform/arch and any additional options
         let datastore = call_method(payload, "datastore", &[])?;
-
-        // Set platform and arch
-        let platform_key = ruby.str_new("Platform").as_value();
-        let platform_val = ruby.str_new(platform).as_value();
-        call_method(datastore, "[]=", &[platform_key, platform_val])?;
-
-        let arch_key = ruby.str_new("Arch").as_value();
-        let arch_val = ruby.str_new(arch).as_value();
-        call_method(datastore, "[]=", &[arch_key, arch_val])?;
-
-        // Set additional options
-        if let Some(opts_map) = options {
-            for (key, value) in opts_map {
-                let key_val = ruby.str_new(&key).as_value();
-                let value_val = ruby.str_new(&value).as_value();
-                call_method(datastore, "[]=", &[key_val, value_val])?;
-            }
-        }
+        set_datastore_options(&ruby, datastore, Some(platform), Some(arch), options)?;
 
         // Generate the raw payload
         let raw_payload = call_method(payload, "generate", &[])?;
@@ -1405,6 +2702,289 @@ impl PayloadGenerator {
         Ok(bytes)
     }
 
+    /// Generate a payload and render it in `format`, mirroring the output
+    /// modes `msfpayload`/`msfvenom` historically offered. `Raw`, `Exe`, and
+    /// `Dll` produce binary output; the source-language formats produce
+    /// UTF-8 text (as bytes, so the return type stays uniform); `Summary`
+    /// skips generation entirely and returns the payload module's
+    /// description instead.
+    pub fn generate_payload(
+        &self,
+        payload_name: &str,
+        options: Option<HashMap<String, String>>,
+        format: PayloadFormat,
+    ) -> Result<Vec<u8>> {
+        let ruby = crate::ruby_bridge::get_ruby()?;
+
+        let name_val = ruby.str_new(payload_name).as_value();
+        let modules_mgr = call_method(self.ruby_framework, "modules", &[])?;
+        let payload = call_method(modules_mgr, "create", &[name_val])?;
+
+        if is_nil(payload) {
+            return Err(AssassinateError::PayloadError(format!(
+                "Payload not found: {}",
+                payload_name
+            )));
+        }
+
+        if let PayloadFormat::Summary = format {
+            let description_val = call_method(payload, "description", &[])?;
+            return Ok(value_to_string(description_val)?.into_bytes());
+        }
+
+        if let Some(opts_map) = options {
+            let datastore = call_method(payload, "datastore", &[])?;
+            for (key, value) in opts_map {
+                let key_val = ruby.str_new(&key).as_value();
+                let value_val = ruby.str_new(&value).as_value();
+                call_method(datastore, "[]=", &[key_val, value_val])?;
+            }
+        }
+
+        let raw = call_method(payload, "generate", &[])?;
+        if is_nil(raw) {
+            return Err(AssassinateError::PayloadError(
+                "Failed to generate payload".to_string(),
+            ));
+        }
+
+        match format {
+            PayloadFormat::Summary => unreachable!("handled above"),
+            PayloadFormat::Raw => {
+                let rstring: magnus::RString = TryConvert::try_convert(raw).map_err(|e: magnus::Error| {
+                    AssassinateError::ConversionError(format!("Failed to convert payload to RString: {}", e))
+                })?;
+                Ok(unsafe { rstring.as_slice() }.to_vec())
+            }
+            PayloadFormat::Exe | PayloadFormat::Dll | PayloadFormat::Elf | PayloadFormat::Macho => {
+                let payload_arch = call_method(payload, "arch", &[])?;
+                let payload_platform = call_method(payload, "platform", &[])?;
+                let exe_module: Value = ruby
+                    .eval("Msf::Util::EXE")
+                    .map_err(|e| AssassinateError::RubyError(format!("Failed to get Msf::Util::EXE: {}", e)))?;
+
+                let platform_str = value_to_string(call_method(payload, "platform", &[])?).unwrap_or_default();
+                let method = format.exe_method(&platform_str);
+                let opts = ruby.hash_new();
+                let rendered: Value = exe_module
+                    .funcall(method, (self.ruby_framework, payload_arch, payload_platform, raw, opts))
+                    .map_err(|e| AssassinateError::RubyError(format!("{} failed: {}", method, e)))?;
+
+                if is_nil(rendered) {
+                    return Err(AssassinateError::PayloadError(format!(
+                        "{} returned nil for payload {}",
+                        method, payload_name
+                    )));
+                }
+                let rstring: magnus::RString = TryConvert::try_convert(rendered).map_err(|e: magnus::Error| {
+                    AssassinateError::ConversionError(format!("Failed to convert {} output to RString: {}", method, e))
+                })?;
+                Ok(unsafe { rstring.as_slice() }.to_vec())
+            }
+            _ => {
+                let buffer_module: Value = ruby
+                    .eval("Msf::Simple::Buffer")
+                    .map_err(|e| AssassinateError::RubyError(format!("Failed to get Msf::Simple::Buffer: {}", e)))?;
+                let format_val = ruby.str_new(format.as_msf_format()).as_value();
+                let transformed = call_method(buffer_module, "transform", &[raw, format_val])?;
+
+                let rstring: magnus::RString = TryConvert::try_convert(transformed).map_err(|e: magnus::Error| {
+                    AssassinateError::ConversionError(format!("Failed to convert transformed payload to RString: {}", e))
+                })?;
+                Ok(unsafe { rstring.as_slice() }.to_vec())
+            }
+        }
+    }
+
+    /// Like `generate_payload`, but with `platform`/`arch` set explicitly
+    /// instead of inferred from the payload module's own declared
+    /// platform/arch - needed for the executable formats (`Exe`, `Dll`,
+    /// `Elf`, `Macho`), where the same payload can be rendered for more
+    /// than one target and the caller, not the payload, decides which.
+    /// `secname`, when given, names the PE/ELF section the payload is
+    /// injected under instead of leaving `Msf::Util::EXE`'s default name.
+    #[cfg_attr(
+        feature = "python-bindings",
+        pyo3(signature = (payload_name, format, platform, arch, template=None, template_path=None, keep=false, secname=None, **options))
+    )]
+    pub fn generate_formatted(
+        &self,
+        payload_name: &str,
+        format: PayloadFormat,
+        platform: &str,
+        arch: &str,
+        template: Option<&str>,
+        template_path: Option<&str>,
+        keep: bool,
+        secname: Option<&str>,
+        options: Option<HashMap<String, String>>,
+    ) -> Result<Vec<u8>> {
+        let ruby = crate::ruby_bridge::get_ruby()?;
+
+        let name_val = ruby.str_new(payload_name).as_value();
+        let modules_mgr = call_method(self.ruby_framework, "modules", &[])?;
+        let payload = call_method(modules_mgr, "create", &[name_val])?;
+
+        if is_nil(payload) {
+            return Err(AssassinateError::PayloadError(format!(
+                "Payload not found: {}",
+                payload_name
+            )));
+        }
+
+        if let PayloadFormat::Summary = format {
+            let description_val = call_method(payload, "description", &[])?;
+            return Ok(value_to_string(description_val)?.into_bytes());
+        }
+
+        let datastore = call_method(payload, "datastore", &[])?;
+        set_datastore_options(&ruby, datastore, Some(platform), Some(arch), options)?;
+
+        let raw = call_method(payload, "generate", &[])?;
+        if is_nil(raw) {
+            return Err(AssassinateError::PayloadError(
+                "Failed to generate payload".to_string(),
+            ));
+        }
+
+        if format.is_executable_format() {
+            validate_format_platform(format, platform)?;
+
+            let payload_arch = call_method(payload, "arch", &[])?;
+            let payload_platform = call_method(payload, "platform", &[])?;
+            let exe_module: Value = ruby
+                .eval("Msf::Util::EXE")
+                .map_err(|e| AssassinateError::RubyError(format!("Failed to get Msf::Util::EXE: {}", e)))?;
+
+            let method = format.exe_method(platform);
+            let opts = build_exe_opts(&ruby, template, template_path, keep, secname)?;
+
+            let rendered: Value = exe_module
+                .funcall(method, (self.ruby_framework, payload_arch, payload_platform, raw, opts))
+                .map_err(|e| {
+                    AssassinateError::InvalidFormat(format!(
+                        "{:?} is not available for platform={}, arch={}: {}",
+                        format, platform, arch, e
+                    ))
+                })?;
+
+            if is_nil(rendered) {
+                return Err(AssassinateError::InvalidFormat(format!(
+                    "{:?} is not available for payload {} (platform={}, arch={})",
+                    format, payload_name, platform, arch
+                )));
+            }
+            let rstring: magnus::RString = TryConvert::try_convert(rendered).map_err(|e: magnus::Error| {
+                AssassinateError::ConversionError(format!("Failed to convert {} output to RString: {}", method, e))
+            })?;
+            Ok(unsafe { rstring.as_slice() }.to_vec())
+        } else if let PayloadFormat::Raw = format {
+            let rstring: magnus::RString = TryConvert::try_convert(raw).map_err(|e: magnus::Error| {
+                AssassinateError::ConversionError(format!("Failed to convert payload to RString: {}", e))
+            })?;
+            Ok(unsafe { rstring.as_slice() }.to_vec())
+        } else {
+            let buffer_module: Value = ruby
+                .eval("Msf::Simple::Buffer")
+                .map_err(|e| AssassinateError::RubyError(format!("Failed to get Msf::Simple::Buffer: {}", e)))?;
+            let format_val = ruby.str_new(format.as_msf_format()).as_value();
+            let transformed = call_method(buffer_module, "transform", &[raw, format_val])?;
+
+            let rstring: magnus::RString = TryConvert::try_convert(transformed).map_err(|e: magnus::Error| {
+                AssassinateError::ConversionError(format!("Failed to convert transformed payload to RString: {}", e))
+            })?;
+            Ok(unsafe { rstring.as_slice() }.to_vec())
+        }
+    }
+
+    /// Raw-bytes counterpart to `generate_formatted`, for msfvenom's `-p -`
+    /// / stdin mode: `raw` is shellcode the caller already has - from
+    /// another tool, or from `generate`/`encode_raw` - instead of a
+    /// freshly created payload module, so there's no `payload.arch`/
+    /// `payload.platform` to read; the caller's `platform`/`arch` are used
+    /// to build the arch list and `Msf::Module::PlatformList` directly.
+    /// `Summary` has no module description to report for a caller-supplied
+    /// buffer and returns a placeholder instead of erroring. `secname`
+    /// behaves the same as in `generate_formatted`.
+    #[cfg_attr(
+        feature = "python-bindings",
+        pyo3(signature = (raw, format, platform, arch, template=None, template_path=None, keep=false, secname=None))
+    )]
+    pub fn format_raw(
+        &self,
+        raw: Vec<u8>,
+        format: PayloadFormat,
+        platform: &str,
+        arch: &str,
+        template: Option<&str>,
+        template_path: Option<&str>,
+        keep: bool,
+        secname: Option<&str>,
+    ) -> Result<Vec<u8>> {
+        let ruby = crate::ruby_bridge::get_ruby()?;
+
+        if let PayloadFormat::Summary = format {
+            return Ok(b"<raw payload supplied directly - no module description available>".to_vec());
+        }
+        if let PayloadFormat::Raw = format {
+            return Ok(raw);
+        }
+
+        let raw_val = unsafe { magnus::RString::from_slice(&raw) }.as_value();
+
+        if format.is_executable_format() {
+            validate_format_platform(format, platform)?;
+
+            let arch_val: Value = ruby
+                .eval(&format!("[{:?}]", arch))
+                .map_err(|e| AssassinateError::RubyError(format!("Failed to build arch array: {}", e)))?;
+
+            let platform_list_class: Value = ruby
+                .eval("Msf::Module::PlatformList")
+                .map_err(|e| AssassinateError::RubyError(format!("Failed to get Msf::Module::PlatformList: {}", e)))?;
+            let platform_val = ruby.str_new(platform).as_value();
+            let platform_list = call_method(platform_list_class, "transform", &[platform_val])?;
+
+            let exe_module: Value = ruby
+                .eval("Msf::Util::EXE")
+                .map_err(|e| AssassinateError::RubyError(format!("Failed to get Msf::Util::EXE: {}", e)))?;
+
+            let method = format.exe_method(platform);
+            let opts = build_exe_opts(&ruby, template, template_path, keep, secname)?;
+
+            let rendered: Value = exe_module
+                .funcall(method, (self.ruby_framework, arch_val, platform_list, raw_val, opts))
+                .map_err(|e| {
+                    AssassinateError::InvalidFormat(format!(
+                        "{:?} is not available for platform={}, arch={}: {}",
+                        format, platform, arch, e
+                    ))
+                })?;
+
+            if is_nil(rendered) {
+                return Err(AssassinateError::InvalidFormat(format!(
+                    "{:?} is not available for the supplied raw payload (platform={}, arch={})",
+                    format, platform, arch
+                )));
+            }
+            let rstring: magnus::RString = TryConvert::try_convert(rendered).map_err(|e: magnus::Error| {
+                AssassinateError::ConversionError(format!("Failed to convert {} output to RString: {}", method, e))
+            })?;
+            Ok(unsafe { rstring.as_slice() }.to_vec())
+        } else {
+            let buffer_module: Value = ruby
+                .eval("Msf::Simple::Buffer")
+                .map_err(|e| AssassinateError::RubyError(format!("Failed to get Msf::Simple::Buffer: {}", e)))?;
+            let format_val = ruby.str_new(format.as_msf_format()).as_value();
+            let transformed = call_method(buffer_module, "transform", &[raw_val, format_val])?;
+
+            let rstring: magnus::RString = TryConvert::try_convert(transformed).map_err(|e: magnus::Error| {
+                AssassinateError::ConversionError(format!("Failed to convert transformed payload to RString: {}", e))
+            })?;
+            Ok(unsafe { rstring.as_slice() }.to_vec())
+        }
+    }
+
     #[cfg(feature = "python-bindings")]
     pub fn __repr__(&self) -> Result<String> {
         Ok("<PayloadGenerator>".to_string())
@@ -1,7 +1,17 @@
-use crate::error::{AssassinateError, Result};
+use crate::error::BridgeError;
 use magnus::{embed, value::ReprValue, Ruby, TryConvert, Value};
+use rand::Rng;
 use std::mem;
 use std::sync::Once;
+use std::time::Duration;
+
+/// `ruby_bridge`'s own `Result` alias, distinct from `crate::error::Result`:
+/// these functions talk directly to the Ruby VM and report failures as
+/// `BridgeError` (Ruby class + method + source), not the higher-level
+/// `AssassinateError` the `Framework`/`Module`/... wrappers use. Callers up
+/// in `framework.rs` still propagate these with a plain `?`, since
+/// `AssassinateError: From<BridgeError>`.
+pub type Result<T> = std::result::Result<T, BridgeError>;
 
 static INIT: Once = Once::new();
 
@@ -48,14 +58,85 @@ pub fn init_ruby() -> Result<()> {
 /// Get the Ruby VM handle
 pub fn get_ruby() -> Result<Ruby> {
     init_ruby()?;
-    Ruby::get().map_err(|e| {
-        AssassinateError::RubyInitError(format!("Failed to get Ruby VM reference: {}", e))
-    })
+    Ruby::get().map_err(|e| BridgeError::RubyVmInit(format!("Failed to get Ruby VM reference: {}", e)))
+}
+
+/// Configuration for resolving and loading a Metasploit Framework
+/// installation, instead of `init_metasploit` only ever accepting one
+/// hardcoded-shaped path.
+///
+/// `framework_path` is resolved in priority order: this field (if set) →
+/// the `MSF_LOCAL_LIB` env var → a default discovered relative to the
+/// running binary (`<exe_dir>/metasploit-framework`), so the bridge works
+/// across Kali/omnibus/source installs without a recompile.
+/// `extra_load_paths` are unshifted onto `$LOAD_PATH` ahead of the
+/// framework's own `lib`, so a locally-installed conflicting gem (e.g. a
+/// newer `metasm`) can be forced over the one the framework bundles.
+#[derive(Debug, Clone, Default)]
+pub struct RubyInitConfig {
+    pub framework_path: Option<String>,
+    pub extra_load_paths: Vec<String>,
+    /// Gates a one-time dump of the resolved paths and loaded MSF version -
+    /// noisy to print unconditionally, useful when a load is failing.
+    pub verbose: bool,
+}
+
+impl RubyInitConfig {
+    /// Priority order: `framework_path` (explicit) -> `MSF_LOCAL_LIB` env ->
+    /// a default discovered relative to the running binary.
+    fn resolve_framework_path(&self) -> Result<String> {
+        if let Some(path) = &self.framework_path {
+            return Ok(path.clone());
+        }
+
+        if let Ok(path) = std::env::var("MSF_LOCAL_LIB") {
+            if !path.is_empty() {
+                return Ok(path);
+            }
+        }
+
+        let exe = std::env::current_exe()
+            .map_err(|e| BridgeError::RubyVmInit(format!("failed to resolve current executable: {}", e)))?;
+        let default_dir = exe
+            .parent()
+            .ok_or_else(|| BridgeError::RubyVmInit("executable has no parent directory".to_string()))?
+            .join("metasploit-framework");
+
+        default_dir
+            .to_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| BridgeError::RubyVmInit("binary-relative framework path is not valid UTF-8".to_string()))
+    }
 }
 
-/// Initialize Metasploit Framework
+/// Initialize Metasploit Framework at a fixed path, with no extra load
+/// paths - a thin wrapper around `init_metasploit_with_config` for the
+/// common case.
 pub fn init_metasploit(msf_path: &str) -> Result<Value> {
+    init_metasploit_with_config(&RubyInitConfig {
+        framework_path: Some(msf_path.to_string()),
+        ..Default::default()
+    })
+}
+
+/// Initialize Metasploit Framework per `config` - see `RubyInitConfig`.
+pub fn init_metasploit_with_config(config: &RubyInitConfig) -> Result<Value> {
     let ruby = get_ruby()?;
+    let msf_path = config.resolve_framework_path()?;
+
+    // Each unshift goes to the front, so the framework lib goes first and
+    // the extra paths after it (in reverse, keeping their listed order).
+    let load_path_unshifts: String = std::iter::once(format!("{}/lib", msf_path))
+        .chain(config.extra_load_paths.iter().rev().cloned())
+        .map(|path| format!("$LOAD_PATH.unshift('{}')\n", path))
+        .collect();
+
+    if config.verbose {
+        eprintln!("[assassinate] resolved framework path: {}", msf_path);
+        for path in &config.extra_load_paths {
+            eprintln!("[assassinate] extra $LOAD_PATH entry: {}", path);
+        }
+    }
 
     // Initialize Metasploit the same way msfconsole does:
     // 1. Load config/boot (sets up bundler)
@@ -64,26 +145,35 @@ pub fn init_metasploit(msf_path: &str) -> Result<Value> {
     let code = format!(
         r###"
         # Change to MSF installation directory
-        Dir.chdir('{}')
+        Dir.chdir('{msf_path}')
 
-        # Add lib directory to load path
-        $LOAD_PATH.unshift('{}/lib')
+        # Extra load paths (if any) go first, so a locally-installed gem
+        # wins over the one the framework bundles
+        {load_path_unshifts}
 
         # Set environment to production (same as msfconsole default)
         ENV['RAILS_ENV'] ||= 'production'
 
         # Load boot configuration (sets up bundler)
-        require '{}/config/boot'
+        require '{msf_path}/config/boot'
 
         # Load msfenv (sets up Rails app and MSF autoloader)
         require 'msfenv'
         "###,
-        msf_path, msf_path, msf_path
+        msf_path = msf_path,
+        load_path_unshifts = load_path_unshifts,
     );
 
-    let _result: Value = ruby
-        .eval::<Value>(&code)
-        .map_err(|e| AssassinateError::RubyInitError(e.to_string()))?;
+    let _result: Value = ruby.eval::<Value>(&code).map_err(|e| BridgeError::MetasploitLoad {
+        path: msf_path.clone(),
+        source: e.to_string(),
+    })?;
+
+    if config.verbose {
+        if let Ok(version) = ruby.eval::<Value>("Msf::Framework::Version") {
+            eprintln!("[assassinate] loaded Metasploit Framework {:?}", version);
+        }
+    }
 
     // Return the Ruby nil value
     Ok(ruby.qnil().as_value())
@@ -93,57 +183,201 @@ pub fn init_metasploit(msf_path: &str) -> Result<Value> {
 pub fn create_framework(options: Option<serde_json::Value>) -> Result<Value> {
     let ruby = get_ruby()?;
 
-    // MSF is already loaded by init_metasploit (via msfenv)
-    // Just create the framework instance
-    let code = if let Some(opts) = options {
-        format!(
-            r#"
-            opts = {}
-            Msf::Simple::Framework.create(opts)
-            "#,
-            serde_json::to_string(&opts).unwrap_or_else(|_| "{}".to_string())
-        )
-    } else {
-        r#"Msf::Simple::Framework.create"#.to_string()
+    // MSF is already loaded by init_metasploit (via msfenv) - just create
+    // the framework instance. `options` is built into a real Ruby Hash via
+    // `json_to_ruby` rather than interpolated into an `eval`'d string,
+    // which broke on embedded quotes and was an injection vector.
+    let framework_class = ruby
+        .eval::<Value>("Msf::Simple::Framework")
+        .map_err(|e| BridgeError::FrameworkCreate(e.to_string()))?;
+
+    let result = match options {
+        Some(opts) => {
+            let opts_val = json_to_ruby(&ruby, &opts)?;
+            call_method(framework_class, "create", &[opts_val])
+        }
+        None => call_method(framework_class, "create", &[]),
     };
 
-    ruby.eval(&code)
-        .map_err(|e| AssassinateError::RubyError(e.to_string()))
+    result.map_err(|e| BridgeError::FrameworkCreate(e.to_string()))
 }
 
 /// Evaluate Ruby code and return the result
 #[allow(dead_code)]
 pub fn eval_ruby(code: &str) -> Result<Value> {
     let ruby = get_ruby()?;
-    ruby.eval::<Value>(code)
-        .map_err(|e| AssassinateError::RubyError(e.to_string()))
+    ruby.eval::<Value>(code).map_err(|e| BridgeError::MethodCall {
+        receiver_class: "Kernel".to_string(),
+        method: "eval".to_string(),
+        source: e.to_string(),
+    })
+}
+
+/// Best-effort Ruby class name for an object, used to fill in
+/// `BridgeError::MethodCall`'s `receiver_class` - goes through the same
+/// `funcall`-then-`to_s` path as `value_to_string` rather than magnus'
+/// `RClass` API, so a lookup failure can't itself obscure the original error.
+fn class_name(val: Value) -> String {
+    val.funcall::<_, _, Value>("class", ())
+        .and_then(|class| class.funcall::<_, _, Value>("to_s", ()))
+        .and_then(TryConvert::try_convert)
+        .unwrap_or_else(|_| "<unknown>".to_string())
 }
 
 /// Call a Ruby method on an object
 pub fn call_method(obj: Value, method_name: &str, args: &[Value]) -> Result<Value> {
     let _ruby = get_ruby()?;
 
-    obj.funcall(method_name, args).map_err(|e| {
-        AssassinateError::RubyError(format!("Failed to call method '{}': {}", method_name, e))
+    obj.funcall(method_name, args).map_err(|e| BridgeError::MethodCall {
+        receiver_class: class_name(obj),
+        method: method_name.to_string(),
+        source: e.to_string(),
     })
 }
 
+/// Fail with `BridgeError::UnexpectedNil` if `val` is nil, otherwise pass it through
+pub fn expect_non_nil(val: Value, method: &str) -> Result<Value> {
+    if is_nil(val) {
+        Err(BridgeError::UnexpectedNil {
+            method: method.to_string(),
+        })
+    } else {
+        Ok(val)
+    }
+}
+
+/// Backoff schedule for `call_method_retry`. Attempt `n` (0-indexed) sleeps a
+/// random duration in `[0, min(max_delay, base_delay * 2^n)]` - "full jitter"
+/// - before retrying, so a herd of simultaneously-failing calls doesn't
+/// retry in lockstep.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32, base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            max_retries,
+            base_delay,
+            max_delay,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Whether a `call_method` outcome is worth retrying. A Ruby-side failure
+/// from a transient condition (framework not done booting, a DB connection
+/// hiccup) looks different on the next attempt; a `nil` result or a
+/// conversion failure reflects the call's actual, stable outcome and won't,
+/// so both fail fast instead of spending the retry budget on a foregone
+/// conclusion.
+fn is_retryable(result: &Result<Value>) -> bool {
+    match result {
+        Ok(_) => false,
+        Err(BridgeError::Conversion { .. }) => false,
+        Err(_) => true,
+    }
+}
+
+/// Call a Ruby method on an object, retrying on transient failures with
+/// exponential backoff and full jitter. The sleep between attempts blocks
+/// the calling thread - callers running on the single Ruby-VM-owning thread
+/// (see `Daemon::run`'s poll loop) should size `policy` accordingly, since a
+/// retry here stalls everything else sharing that thread.
+pub fn call_method_retry(
+    obj: Value,
+    method_name: &str,
+    args: &[Value],
+    policy: &RetryPolicy,
+) -> Result<Value> {
+    let mut attempt = 0u32;
+    loop {
+        let result = call_method(obj, method_name, args);
+        if attempt >= policy.max_retries || !is_retryable(&result) {
+            return result;
+        }
+
+        let max_sleep = policy
+            .base_delay
+            .checked_mul(1u32 << attempt)
+            .unwrap_or(Duration::MAX)
+            .min(policy.max_delay);
+        let sleep_for = Duration::from_nanos(rand::thread_rng().gen_range(0..=max_sleep.as_nanos() as u64));
+        std::thread::sleep(sleep_for);
+
+        attempt += 1;
+    }
+}
+
+/// Run a known-blocking Ruby call (`exploit_simple`, `check_simple`,
+/// `run_simple`, a session `read`, ...) with the Python GIL
+/// released for its duration, so it doesn't freeze every other Python
+/// thread sharing it for however long the blocking call takes, via the same
+/// `PyEval_SaveThread`/`PyEval_RestoreThread` pair `Python::allow_threads`
+/// uses internally (there's no `Python<'_>` token available this deep in
+/// `call_method`'s callers to call `allow_threads` on directly).
+///
+/// There is deliberately no equivalent Ruby-GVL release here: `f` is itself
+/// a `funcall` into Ruby, so running it GVL-free is undefined behavior, and
+/// reacquiring the GVL before invoking `f` (an earlier version of this
+/// function did exactly that, via `rb_thread_call_with_gvl`) just serializes
+/// the blocking call behind two thread transitions of pure overhead - the
+/// call still runs, start to finish, with the GVL held. Any concurrency
+/// between Ruby threads during `exploit_simple`/`run_simple`/`check_simple`
+/// comes from Ruby's own internal GVL release around its blocking I/O, which
+/// happens with or without this function's involvement.
+///
+/// Safety: under `python-bindings`, `f` must only touch `Value`s that were
+/// created on, and stay on, this thread - releasing the Python GIL lets
+/// *other* Python threads run concurrently, it doesn't make a `Value`
+/// itself safe to share across threads.
+pub fn call_without_gvl<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    #[cfg(feature = "python-bindings")]
+    unsafe {
+        let tstate = pyo3::ffi::PyEval_SaveThread();
+        let result = f();
+        pyo3::ffi::PyEval_RestoreThread(tstate);
+        result
+    }
+
+    #[cfg(not(feature = "python-bindings"))]
+    {
+        f()
+    }
+}
+
 /// Convert Ruby value to String
 pub fn value_to_string(val: Value) -> Result<String> {
-    let str_val = val.funcall::<_, _, Value>("to_s", ()).map_err(|e| {
-        AssassinateError::ConversionError(format!("Failed to call to_s on Ruby value: {}", e))
+    let str_val = val.funcall::<_, _, Value>("to_s", ()).map_err(|e| BridgeError::MethodCall {
+        receiver_class: class_name(val),
+        method: "to_s".to_string(),
+        source: e.to_string(),
     })?;
 
-    TryConvert::try_convert(str_val).map_err(|e: magnus::Error| {
-        AssassinateError::ConversionError(format!("Failed to convert Ruby value to string: {}", e))
+    TryConvert::try_convert(str_val).map_err(|_: magnus::Error| BridgeError::Conversion {
+        from: "Ruby value".to_string(),
+        to: "String".to_string(),
     })
 }
 
 /// Convert Ruby value to Integer
 #[allow(dead_code)]
 pub fn value_to_i64(val: Value) -> Result<i64> {
-    TryConvert::try_convert(val).map_err(|e: magnus::Error| {
-        AssassinateError::ConversionError(format!("Failed to convert Ruby value to i64: {}", e))
+    TryConvert::try_convert(val).map_err(|_: magnus::Error| BridgeError::Conversion {
+        from: "Ruby value".to_string(),
+        to: "i64".to_string(),
     })
 }
 
@@ -153,22 +387,141 @@ pub fn value_to_bool(val: Value) -> Result<bool> {
     Ok(!is_nil(val))
 }
 
-/// Convert Ruby Hash to JSON
-pub fn hash_to_json(hash: Value) -> Result<serde_json::Value> {
-    let _ruby = get_ruby()?;
+/// Recursively convert a Ruby value to `serde_json::Value`, dispatching on
+/// its class rather than going through a string round-trip (the previous
+/// `hash_to_json` fed a Rust-debug-formatted `Value` - `#<Value ...>`, not
+/// the hash contents - into `JSON.generate`, which just produced garbage).
+/// Anything that isn't one of the classes below (a custom object, a
+/// Range, ...) is reported as a `Conversion` error rather than silently
+/// stringified.
+pub fn ruby_to_json(val: Value) -> Result<serde_json::Value> {
+    if is_nil(val) {
+        return Ok(serde_json::Value::Null);
+    }
 
-    let json_val: Value = _ruby
-        .eval::<Value>(&format!("require 'json'; JSON.generate({:?})", hash))
-        .map_err(|e| {
-            AssassinateError::ConversionError(format!("Failed to convert Hash to JSON: {}", e))
-        })?;
+    let class = class_name(val);
+    let json = match class.as_str() {
+        "TrueClass" => serde_json::Value::Bool(true),
+        "FalseClass" => serde_json::Value::Bool(false),
+        "Integer" => {
+            let i: i64 = TryConvert::try_convert(val).map_err(|_: magnus::Error| BridgeError::Conversion {
+                from: "Ruby Integer".to_string(),
+                to: "i64".to_string(),
+            })?;
+            serde_json::Value::Number(i.into())
+        }
+        "Float" => {
+            let f: f64 = TryConvert::try_convert(val).map_err(|_: magnus::Error| BridgeError::Conversion {
+                from: "Ruby Float".to_string(),
+                to: "f64".to_string(),
+            })?;
+            serde_json::Number::from_f64(f)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null)
+        }
+        "String" | "Symbol" => serde_json::Value::String(value_to_string(val)?),
+        "Array" => {
+            let array: magnus::RArray =
+                TryConvert::try_convert(val).map_err(|_: magnus::Error| BridgeError::Conversion {
+                    from: "Ruby value".to_string(),
+                    to: "Array".to_string(),
+                })?;
+
+            let mut items = Vec::new();
+            for item in array.each() {
+                let item = item.map_err(|e| BridgeError::MethodCall {
+                    receiver_class: "Array".to_string(),
+                    method: "each".to_string(),
+                    source: e.to_string(),
+                })?;
+                items.push(ruby_to_json(item)?);
+            }
+            serde_json::Value::Array(items)
+        }
+        "Hash" => {
+            let hash: magnus::RHash =
+                TryConvert::try_convert(val).map_err(|_: magnus::Error| BridgeError::Conversion {
+                    from: "Ruby value".to_string(),
+                    to: "Hash".to_string(),
+                })?;
+
+            let mut map = serde_json::Map::new();
+            let mut first_err = None;
+            let _: std::result::Result<(), magnus::Error> = hash.foreach(|k: Value, v: Value| {
+                match value_to_string(k).and_then(|key| ruby_to_json(v).map(|value| (key, value))) {
+                    Ok((key, value)) => {
+                        map.insert(key, value);
+                        Ok(magnus::r_hash::ForEach::Continue)
+                    }
+                    Err(e) => {
+                        first_err = Some(e);
+                        Ok(magnus::r_hash::ForEach::Stop)
+                    }
+                }
+            });
+            if let Some(e) = first_err {
+                return Err(e);
+            }
+            serde_json::Value::Object(map)
+        }
+        other => {
+            return Err(BridgeError::Conversion {
+                from: format!("Ruby {}", other),
+                to: "serde_json::Value".to_string(),
+            });
+        }
+    };
 
-    let json_str: String = TryConvert::try_convert(json_val).map_err(|e: magnus::Error| {
-        AssassinateError::ConversionError(format!("Failed to parse JSON string: {}", e))
-    })?;
+    Ok(json)
+}
 
-    serde_json::from_str(&json_str)
-        .map_err(|e| AssassinateError::ConversionError(format!("Failed to parse JSON: {}", e)))
+/// Inverse of `ruby_to_json`: build the Ruby object a `serde_json::Value`
+/// describes via `ruby`, recursing for `Array`/`Object`. Object keys are
+/// always built as Ruby `String`s, matching `ruby_to_json`'s stringified
+/// keys on the way back.
+pub fn json_to_ruby(ruby: &Ruby, value: &serde_json::Value) -> Result<Value> {
+    let val = match value {
+        serde_json::Value::Null => ruby.qnil().as_value(),
+        serde_json::Value::Bool(true) => ruby.qtrue().as_value(),
+        serde_json::Value::Bool(false) => ruby.qfalse().as_value(),
+        serde_json::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                ruby.into_value(i)
+            } else if let Some(f) = n.as_f64() {
+                ruby.into_value(f)
+            } else {
+                return Err(BridgeError::Conversion {
+                    from: format!("number {}", n),
+                    to: "Ruby Numeric".to_string(),
+                });
+            }
+        }
+        serde_json::Value::String(s) => ruby.str_new(s).as_value(),
+        serde_json::Value::Array(items) => {
+            let array = ruby.ary_new().as_value();
+            for item in items {
+                let item_val = json_to_ruby(ruby, item)?;
+                call_method(array, "push", &[item_val])?;
+            }
+            array
+        }
+        serde_json::Value::Object(map) => {
+            let hash = ruby.hash_new().as_value();
+            for (key, value) in map {
+                let key_val = ruby.str_new(key).as_value();
+                let value_val = json_to_ruby(ruby, value)?;
+                call_method(hash, "[]=", &[key_val, value_val])?;
+            }
+            hash
+        }
+    };
+
+    Ok(val)
+}
+
+/// Convert Ruby Hash to JSON
+pub fn hash_to_json(hash: Value) -> Result<serde_json::Value> {
+    ruby_to_json(hash)
 }
 
 #[cfg(test)]
@@ -200,4 +553,30 @@ mod tests {
             assert_eq!(value_to_string(str_val).unwrap(), "hello");
         }
     }
+
+    #[test]
+    fn test_call_method_retry_returns_nil_immediately() {
+        let _ = init_ruby();
+        if let Ok(ruby) = Ruby::get() {
+            let nil_val = ruby.qnil().as_value();
+            let policy = RetryPolicy::new(5, Duration::from_secs(1), Duration::from_secs(5));
+            let start = std::time::Instant::now();
+            let result = call_method_retry(nil_val, "itself", &[], &policy);
+            assert!(result.is_ok());
+            assert!(is_nil(result.unwrap()));
+            // A nil result is a terminal outcome, not a transient failure -
+            // it must not burn any of the policy's retry budget.
+            assert!(start.elapsed() < Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn test_call_method_retry_exhausts_retries_on_persistent_failure() {
+        let _ = init_ruby();
+        if let Ok(int_val) = eval_ruby("42") {
+            let policy = RetryPolicy::new(2, Duration::from_millis(1), Duration::from_millis(5));
+            let result = call_method_retry(int_val, "no_such_method", &[], &policy);
+            assert!(result.is_err());
+        }
+    }
 }
@@ -0,0 +1,301 @@
+//! Declarative scenario scripts that drive the bridge end to end
+//!
+//! `tests/integration_tests.rs` already exercises the bridge this way by
+//! hand, one `call_method` at a time; this module gives the same kind of
+//! walkthrough a small line-based DSL so a scenario can be written down once
+//! (as a fixture, or embedded in an ops runbook) and replayed without a
+//! recompile. Each line is one step:
+//!
+//! ```text
+//! use exploit/windows/smb/ms17_010_eternalblue
+//! set RHOSTS 10.0.0.5
+//! set RPORT 445
+//! search vsftpd
+//! get-option RHOSTS
+//! assert type == exploit
+//! expect fullname == exploit/windows/smb/ms17_010_eternalblue
+//! ```
+//!
+//! `assert` stops the scenario on the first failed check; `expect` records a
+//! failure in the report but keeps running, so a single scenario can surface
+//! more than one problem per run.
+
+use crate::error::{AssassinateError, Result};
+use crate::framework::Framework;
+use std::collections::HashMap;
+
+/// One parsed line of a scenario script
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// `use <module_path>` - create the module and make it current
+    Use(String),
+    /// `set <KEY> <value>` - set an option on the current module's datastore
+    Set { key: String, value: String },
+    /// `search <query>` - search the module database
+    Search(String),
+    /// `get-option <KEY>` - read back an option from the current module's datastore
+    GetOption(String),
+    /// `assert <field> == <value>` - stop the scenario if this check fails
+    Assert(Check),
+    /// `expect <field> == <value>` - record a failed check but keep going
+    Expect(Check),
+}
+
+/// A single `<field> == <value>` comparison used by `assert`/`expect`.
+/// `field` names a property of the current module (`type`, `name`,
+/// `fullname`, `rank`) or, for `get-option`'s result, the datastore key just read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub field: String,
+    pub expected: String,
+}
+
+/// Parse a full scenario script into its steps, in order. Blank lines and
+/// lines starting with `#` are ignored.
+pub fn parse_scenario(script: &str) -> Result<Vec<Step>> {
+    script
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(parse_step)
+        .collect()
+}
+
+fn parse_step(line: &str) -> Result<Step> {
+    let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+
+    match command {
+        "use" => Ok(Step::Use(rest.to_string())),
+        "set" => {
+            let (key, value) = rest
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| AssassinateError::ConfigError(format!("malformed 'set' step: {}", line)))?;
+            Ok(Step::Set {
+                key: key.to_string(),
+                value: value.trim().to_string(),
+            })
+        }
+        "search" => Ok(Step::Search(rest.to_string())),
+        "get-option" => Ok(Step::GetOption(rest.to_string())),
+        "assert" => Ok(Step::Assert(parse_check(rest, line)?)),
+        "expect" => Ok(Step::Expect(parse_check(rest, line)?)),
+        other => Err(AssassinateError::ConfigError(format!(
+            "unknown scenario step '{}' in: {}",
+            other, line
+        ))),
+    }
+}
+
+fn parse_check(rest: &str, line: &str) -> Result<Check> {
+    let (field, expected) = rest
+        .split_once("==")
+        .ok_or_else(|| AssassinateError::ConfigError(format!("malformed check (expected '<field> == <value>'): {}", line)))?;
+    Ok(Check {
+        field: field.trim().to_string(),
+        expected: expected.trim().to_string(),
+    })
+}
+
+/// Outcome of running a single step
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Passed,
+    Failed(String),
+}
+
+/// One step's textual description alongside what happened when it ran
+#[derive(Debug, Clone)]
+pub struct StepResult {
+    pub step: Step,
+    pub outcome: Outcome,
+}
+
+/// The full result of running a scenario: every step's outcome, plus whether
+/// the scenario as a whole passed (no failed `assert`, no failed `expect`)
+#[derive(Debug, Clone)]
+pub struct ScenarioReport {
+    pub results: Vec<StepResult>,
+}
+
+impl ScenarioReport {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|r| r.outcome == Outcome::Passed)
+    }
+}
+
+/// Run a parsed scenario against `framework`, returning a report that
+/// covers every step that ran (execution stops early only when an `assert`
+/// step fails, or a step errors outright - e.g. `use` naming a module that
+/// doesn't exist).
+pub fn run_scenario(framework: &Framework, steps: &[Step]) -> Result<ScenarioReport> {
+    let mut results = Vec::with_capacity(steps.len());
+    let mut current_module = None;
+    let mut last_option_read: HashMap<String, Option<String>> = HashMap::new();
+
+    for step in steps {
+        let outcome = match step {
+            Step::Use(module_path) => {
+                current_module = Some(framework.create_module(module_path)?);
+                Outcome::Passed
+            }
+            Step::Set { key, value } => {
+                let module = current_module
+                    .as_ref()
+                    .ok_or_else(|| AssassinateError::ConfigError("'set' with no module selected by 'use'".to_string()))?;
+                module.datastore()?.set(key, value)?;
+                Outcome::Passed
+            }
+            Step::Search(query) => {
+                framework.search(query)?;
+                Outcome::Passed
+            }
+            Step::GetOption(key) => {
+                let module = current_module
+                    .as_ref()
+                    .ok_or_else(|| AssassinateError::ConfigError("'get-option' with no module selected by 'use'".to_string()))?;
+                let value = module.datastore()?.get(key)?;
+                last_option_read.insert(key.clone(), value);
+                Outcome::Passed
+            }
+            Step::Assert(check) => {
+                let outcome = evaluate_check(current_module.as_ref(), &last_option_read, check)?;
+                if let Outcome::Failed(_) = &outcome {
+                    results.push(StepResult {
+                        step: step.clone(),
+                        outcome,
+                    });
+                    return Ok(ScenarioReport { results });
+                }
+                outcome
+            }
+            Step::Expect(check) => evaluate_check(current_module.as_ref(), &last_option_read, check)?,
+        };
+
+        results.push(StepResult {
+            step: step.clone(),
+            outcome,
+        });
+    }
+
+    Ok(ScenarioReport { results })
+}
+
+/// Resolve a `Check`'s `field` against either the current module's
+/// properties or a previously `get-option`'d datastore value, and compare it
+/// to `expected`.
+fn evaluate_check(
+    module: Option<&crate::framework::Module>,
+    last_option_read: &HashMap<String, Option<String>>,
+    check: &Check,
+) -> Result<Outcome> {
+    let actual: Option<String> = match check.field.as_str() {
+        "type" => Some(
+            module
+                .ok_or_else(|| AssassinateError::ConfigError("check on 'type' with no module selected".to_string()))?
+                .module_type()?,
+        ),
+        "name" => Some(
+            module
+                .ok_or_else(|| AssassinateError::ConfigError("check on 'name' with no module selected".to_string()))?
+                .name()?,
+        ),
+        "fullname" => Some(
+            module
+                .ok_or_else(|| AssassinateError::ConfigError("check on 'fullname' with no module selected".to_string()))?
+                .fullname()?,
+        ),
+        "rank" => Some(
+            module
+                .ok_or_else(|| AssassinateError::ConfigError("check on 'rank' with no module selected".to_string()))?
+                .rank()?,
+        ),
+        key => last_option_read.get(key).cloned().ok_or_else(|| {
+            AssassinateError::ConfigError(format!(
+                "check on unknown field '{}' (not a module property, and no prior 'get-option {}')",
+                key, key
+            ))
+        })?,
+    };
+
+    Ok(match actual {
+        Some(actual) if actual == check.expected => Outcome::Passed,
+        Some(actual) => Outcome::Failed(format!("{}: expected '{}', got '{}'", check.field, check.expected, actual)),
+        None => Outcome::Failed(format!("{}: expected '{}', got <unset>", check.field, check.expected)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scenario_skips_blank_lines_and_comments() {
+        let script = "\n# a comment\nuse exploit/foo\n\nsearch bar\n";
+        let steps = parse_scenario(script).unwrap();
+        assert_eq!(steps, vec![Step::Use("exploit/foo".to_string()), Step::Search("bar".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_all_step_kinds() {
+        let script = "use exploit/foo\nset RHOSTS 10.0.0.1\nsearch vsftpd\nget-option RHOSTS\nassert type == exploit\nexpect fullname == exploit/foo";
+        let steps = parse_scenario(script).unwrap();
+        assert_eq!(
+            steps,
+            vec![
+                Step::Use("exploit/foo".to_string()),
+                Step::Set {
+                    key: "RHOSTS".to_string(),
+                    value: "10.0.0.1".to_string()
+                },
+                Step::Search("vsftpd".to_string()),
+                Step::GetOption("RHOSTS".to_string()),
+                Step::Assert(Check {
+                    field: "type".to_string(),
+                    expected: "exploit".to_string()
+                }),
+                Step::Expect(Check {
+                    field: "fullname".to_string(),
+                    expected: "exploit/foo".to_string()
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_step_rejects_unknown_command() {
+        assert!(parse_scenario("frobnicate everything").is_err());
+    }
+
+    #[test]
+    fn test_parse_step_rejects_malformed_set() {
+        assert!(parse_scenario("set RHOSTS").is_err());
+    }
+
+    #[test]
+    fn test_parse_step_rejects_malformed_check() {
+        assert!(parse_scenario("assert type exploit").is_err());
+    }
+
+    #[test]
+    fn test_scenario_report_passed_reflects_all_outcomes() {
+        let report = ScenarioReport {
+            results: vec![StepResult {
+                step: Step::Search("x".to_string()),
+                outcome: Outcome::Passed,
+            }],
+        };
+        assert!(report.passed());
+
+        let failing_report = ScenarioReport {
+            results: vec![StepResult {
+                step: Step::Assert(Check {
+                    field: "type".to_string(),
+                    expected: "exploit".to_string(),
+                }),
+                outcome: Outcome::Failed("mismatch".to_string()),
+            }],
+        };
+        assert!(!failing_report.passed());
+    }
+}
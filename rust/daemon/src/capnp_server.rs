@@ -0,0 +1,699 @@
+/// Cap'n Proto RPC transport exposing the MSF bridge to remote clients
+///
+/// `http_transport` proved out the pattern: a transport that lives on
+/// whatever thread its own executor picks can never touch `Framework`/
+/// `Module`/`DataStore` directly, because all of them hold a Ruby `Value`
+/// (via magnus), which is not `Send`. The capnp-rpc servers below hold only a
+/// `CapnpBridge` (an `mpsc::UnboundedSender`, which is `Send`) plus an opaque
+/// `ObjectHandle`; every method body packages the call as a `BridgeCall` and
+/// awaits its reply over a oneshot channel. `Daemon::run`'s poll loop drains
+/// that queue on the single thread that owns the Ruby VM, resolves the
+/// handle back to a live bridge object, runs the call, and replies - so
+/// arbitrarily many concurrent capnp clients can be in flight without ever
+/// touching Ruby off that one thread.
+use anyhow::{anyhow, Result};
+use capnp::capability::Promise;
+use capnp_rpc::{pry, rpc_twoparty_capnp, twoparty, RpcSystem};
+use futures::AsyncReadExt;
+use ipc::msf_capnp::{data_store, db, framework, jobs, module, module_manager, session_manager};
+use tokio::sync::{mpsc, oneshot};
+
+/// Handle to a live bridge object (`Framework`, `Module`, `DataStore`, ...)
+/// held in `Daemon`'s object table. Handle `0` is reserved for the single
+/// root `Framework`.
+pub type ObjectHandle = u64;
+
+pub const ROOT_FRAMEWORK_HANDLE: ObjectHandle = 0;
+
+/// What a `BridgeCall` can hand back: a plain JSON-convertible value, or a
+/// handle to a newly-created object (a `ModuleManager`, a created `Module`,
+/// the framework's `DataStore`, ...) for the caller to wrap in a capability.
+pub enum BridgeReply {
+    Value(serde_json::Value),
+    Handle(ObjectHandle),
+}
+
+/// One capnp method call waiting to run on the VM thread
+pub struct BridgeCall {
+    pub receiver: ObjectHandle,
+    pub method: &'static str,
+    pub args: Vec<serde_json::Value>,
+    pub responder: oneshot::Sender<Result<BridgeReply>>,
+}
+
+/// Sending half handed to every capnp server object; cheap to clone
+#[derive(Clone)]
+pub struct CapnpBridge {
+    sender: mpsc::UnboundedSender<BridgeCall>,
+}
+
+impl CapnpBridge {
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<BridgeCall>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self { sender }, receiver)
+    }
+
+    async fn call(
+        &self,
+        receiver: ObjectHandle,
+        method: &'static str,
+        args: Vec<serde_json::Value>,
+    ) -> Result<BridgeReply> {
+        let (responder, recv) = oneshot::channel();
+        self.sender
+            .send(BridgeCall {
+                receiver,
+                method,
+                args,
+                responder,
+            })
+            .map_err(|_| anyhow!("daemon dispatch loop is not running"))?;
+        recv.await
+            .map_err(|_| anyhow!("dispatch loop dropped the call before replying"))?
+    }
+
+    async fn call_handle(
+        &self,
+        receiver: ObjectHandle,
+        method: &'static str,
+        args: Vec<serde_json::Value>,
+    ) -> Result<ObjectHandle> {
+        match self.call(receiver, method, args).await? {
+            BridgeReply::Handle(handle) => Ok(handle),
+            BridgeReply::Value(_) => Err(anyhow!("expected a handle reply from '{}'", method)),
+        }
+    }
+
+    async fn call_value(
+        &self,
+        receiver: ObjectHandle,
+        method: &'static str,
+        args: Vec<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        match self.call(receiver, method, args).await? {
+            BridgeReply::Value(value) => Ok(value),
+            BridgeReply::Handle(_) => Err(anyhow!("expected a value reply from '{}'", method)),
+        }
+    }
+}
+
+fn capnp_err(e: anyhow::Error) -> capnp::Error {
+    capnp::Error::failed(e.to_string())
+}
+
+fn as_str_list(value: serde_json::Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Root `Framework` server, backed by handle `ROOT_FRAMEWORK_HANDLE`
+pub struct FrameworkServer {
+    bridge: CapnpBridge,
+}
+
+impl FrameworkServer {
+    pub fn new(bridge: CapnpBridge) -> Self {
+        Self { bridge }
+    }
+}
+
+impl framework::Server for FrameworkServer {
+    fn version(
+        &mut self,
+        _: framework::VersionParams,
+        mut results: framework::VersionResults,
+    ) -> Promise<(), capnp::Error> {
+        let bridge = self.bridge.clone();
+        Promise::from_future(async move {
+            let version = bridge
+                .call_value(ROOT_FRAMEWORK_HANDLE, "version", vec![])
+                .await
+                .map_err(capnp_err)?;
+            results
+                .get()
+                .set_version(version.as_str().unwrap_or_default().into());
+            Ok(())
+        })
+    }
+
+    fn modules(
+        &mut self,
+        _: framework::ModulesParams,
+        mut results: framework::ModulesResults,
+    ) -> Promise<(), capnp::Error> {
+        let bridge = self.bridge.clone();
+        Promise::from_future(async move {
+            let handle = bridge
+                .call_handle(ROOT_FRAMEWORK_HANDLE, "modules", vec![])
+                .await
+                .map_err(capnp_err)?;
+            results.get().set_modules(capnp_rpc::new_client(ModuleManagerServer {
+                bridge,
+                handle,
+            }));
+            Ok(())
+        })
+    }
+
+    fn sessions(
+        &mut self,
+        _: framework::SessionsParams,
+        mut results: framework::SessionsResults,
+    ) -> Promise<(), capnp::Error> {
+        let bridge = self.bridge.clone();
+        Promise::from_future(async move {
+            let handle = bridge
+                .call_handle(ROOT_FRAMEWORK_HANDLE, "sessions", vec![])
+                .await
+                .map_err(capnp_err)?;
+            results
+                .get()
+                .set_sessions(capnp_rpc::new_client(SessionManagerServer { bridge, handle }));
+            Ok(())
+        })
+    }
+
+    fn jobs(
+        &mut self,
+        _: framework::JobsParams,
+        mut results: framework::JobsResults,
+    ) -> Promise<(), capnp::Error> {
+        let bridge = self.bridge.clone();
+        Promise::from_future(async move {
+            let handle = bridge
+                .call_handle(ROOT_FRAMEWORK_HANDLE, "jobs", vec![])
+                .await
+                .map_err(capnp_err)?;
+            results
+                .get()
+                .set_jobs(capnp_rpc::new_client(JobsServer { bridge, handle }));
+            Ok(())
+        })
+    }
+
+    fn db(
+        &mut self,
+        _: framework::DbParams,
+        mut results: framework::DbResults,
+    ) -> Promise<(), capnp::Error> {
+        let bridge = self.bridge.clone();
+        Promise::from_future(async move {
+            let handle = bridge
+                .call_handle(ROOT_FRAMEWORK_HANDLE, "db", vec![])
+                .await
+                .map_err(capnp_err)?;
+            results.get().set_db(capnp_rpc::new_client(DbServer { bridge, handle }));
+            Ok(())
+        })
+    }
+
+    fn datastore(
+        &mut self,
+        _: framework::DatastoreParams,
+        mut results: framework::DatastoreResults,
+    ) -> Promise<(), capnp::Error> {
+        let bridge = self.bridge.clone();
+        Promise::from_future(async move {
+            let handle = bridge
+                .call_handle(ROOT_FRAMEWORK_HANDLE, "datastore", vec![])
+                .await
+                .map_err(capnp_err)?;
+            results
+                .get()
+                .set_datastore(capnp_rpc::new_client(DataStoreServer { bridge, handle }));
+            Ok(())
+        })
+    }
+
+    fn search(
+        &mut self,
+        params: framework::SearchParams,
+        mut results: framework::SearchResults,
+    ) -> Promise<(), capnp::Error> {
+        let bridge = self.bridge.clone();
+        let query = pry!(pry!(params.get()).get_query()).to_string();
+        Promise::from_future(async move {
+            let value = bridge
+                .call_value(
+                    ROOT_FRAMEWORK_HANDLE,
+                    "search",
+                    vec![serde_json::Value::String(query)],
+                )
+                .await
+                .map_err(capnp_err)?;
+            let refnames = as_str_list(value);
+            let mut list = results.get().init_refnames(refnames.len() as u32);
+            for (i, name) in refnames.into_iter().enumerate() {
+                list.set(i as u32, name.into());
+            }
+            Ok(())
+        })
+    }
+}
+
+struct ModuleManagerServer {
+    bridge: CapnpBridge,
+    handle: ObjectHandle,
+}
+
+impl module_manager::Server for ModuleManagerServer {
+    fn exploits(
+        &mut self,
+        _: module_manager::ExploitsParams,
+        results: module_manager::ExploitsResults,
+    ) -> Promise<(), capnp::Error> {
+        refnames_method(self.bridge.clone(), self.handle, "exploits", results)
+    }
+
+    fn auxiliary(
+        &mut self,
+        _: module_manager::AuxiliaryParams,
+        results: module_manager::AuxiliaryResults,
+    ) -> Promise<(), capnp::Error> {
+        refnames_method(self.bridge.clone(), self.handle, "auxiliary", results)
+    }
+
+    fn payloads(
+        &mut self,
+        _: module_manager::PayloadsParams,
+        results: module_manager::PayloadsResults,
+    ) -> Promise<(), capnp::Error> {
+        refnames_method(self.bridge.clone(), self.handle, "payloads", results)
+    }
+
+    fn create(
+        &mut self,
+        params: module_manager::CreateParams,
+        mut results: module_manager::CreateResults,
+    ) -> Promise<(), capnp::Error> {
+        let bridge = self.bridge.clone();
+        let handle = self.handle;
+        let fullname = pry!(pry!(params.get()).get_fullname()).to_string();
+        Promise::from_future(async move {
+            let module_handle = bridge
+                .call_handle(handle, "create", vec![serde_json::Value::String(fullname)])
+                .await
+                .map_err(capnp_err)?;
+            results.get().set_module(capnp_rpc::new_client(ModuleServer {
+                bridge,
+                handle: module_handle,
+            }));
+            Ok(())
+        })
+    }
+}
+
+/// Shared body for the three `ModuleManager` enumeration methods - identical
+/// shape, differing only in which method name is forwarded to the VM thread.
+fn refnames_method<R>(
+    bridge: CapnpBridge,
+    handle: ObjectHandle,
+    method: &'static str,
+    mut results: R,
+) -> Promise<(), capnp::Error>
+where
+    R: SetRefnames + 'static,
+{
+    Promise::from_future(async move {
+        let value = bridge.call_value(handle, method, vec![]).await.map_err(capnp_err)?;
+        let refnames = as_str_list(value);
+        results.set_refnames(&refnames);
+        Ok(())
+    })
+}
+
+/// The three `ModuleManager` results builders (`ExploitsResults`,
+/// `AuxiliaryResults`, `PayloadsResults`) all expose the same
+/// `refnames: List(Text)` field under different generated names - this trait
+/// lets `refnames_method` write to any of them uniformly.
+trait SetRefnames {
+    fn set_refnames(&mut self, refnames: &[String]);
+}
+
+impl SetRefnames for module_manager::ExploitsResults {
+    fn set_refnames(&mut self, refnames: &[String]) {
+        let mut list = self.get().init_refnames(refnames.len() as u32);
+        for (i, name) in refnames.iter().enumerate() {
+            list.set(i as u32, name.as_str().into());
+        }
+    }
+}
+
+impl SetRefnames for module_manager::AuxiliaryResults {
+    fn set_refnames(&mut self, refnames: &[String]) {
+        let mut list = self.get().init_refnames(refnames.len() as u32);
+        for (i, name) in refnames.iter().enumerate() {
+            list.set(i as u32, name.as_str().into());
+        }
+    }
+}
+
+impl SetRefnames for module_manager::PayloadsResults {
+    fn set_refnames(&mut self, refnames: &[String]) {
+        let mut list = self.get().init_refnames(refnames.len() as u32);
+        for (i, name) in refnames.iter().enumerate() {
+            list.set(i as u32, name.as_str().into());
+        }
+    }
+}
+
+struct ModuleServer {
+    bridge: CapnpBridge,
+    handle: ObjectHandle,
+}
+
+impl module::Server for ModuleServer {
+    fn name(&mut self, _: module::NameParams, mut results: module::NameResults) -> Promise<(), capnp::Error> {
+        string_method(self.bridge.clone(), self.handle, "name", move |value| {
+            results.get().set_name(value.into());
+        })
+    }
+
+    fn fullname(
+        &mut self,
+        _: module::FullnameParams,
+        mut results: module::FullnameResults,
+    ) -> Promise<(), capnp::Error> {
+        string_method(self.bridge.clone(), self.handle, "fullname", move |value| {
+            results.get().set_fullname(value.into());
+        })
+    }
+
+    fn description(
+        &mut self,
+        _: module::DescriptionParams,
+        mut results: module::DescriptionResults,
+    ) -> Promise<(), capnp::Error> {
+        string_method(self.bridge.clone(), self.handle, "description", move |value| {
+            results.get().set_description(value.into());
+        })
+    }
+
+    fn module_type(
+        &mut self,
+        _: module::ModuleTypeParams,
+        mut results: module::ModuleTypeResults,
+    ) -> Promise<(), capnp::Error> {
+        string_method(self.bridge.clone(), self.handle, "module_type", move |value| {
+            results.get().set_module_type(value.into());
+        })
+    }
+
+    fn rank(&mut self, _: module::RankParams, mut results: module::RankResults) -> Promise<(), capnp::Error> {
+        string_method(self.bridge.clone(), self.handle, "rank", move |value| {
+            results.get().set_rank(value.into());
+        })
+    }
+
+    fn privileged(
+        &mut self,
+        _: module::PrivilegedParams,
+        mut results: module::PrivilegedResults,
+    ) -> Promise<(), capnp::Error> {
+        let bridge = self.bridge.clone();
+        let handle = self.handle;
+        Promise::from_future(async move {
+            let value = bridge
+                .call_value(handle, "privileged", vec![])
+                .await
+                .map_err(capnp_err)?;
+            results.get().set_privileged(value.as_bool().unwrap_or(false));
+            Ok(())
+        })
+    }
+
+    fn datastore(
+        &mut self,
+        _: module::DatastoreParams,
+        mut results: module::DatastoreResults,
+    ) -> Promise<(), capnp::Error> {
+        let bridge = self.bridge.clone();
+        let handle = self.handle;
+        Promise::from_future(async move {
+            let datastore_handle = bridge
+                .call_handle(handle, "datastore", vec![])
+                .await
+                .map_err(capnp_err)?;
+            results.get().set_datastore(capnp_rpc::new_client(DataStoreServer {
+                bridge,
+                handle: datastore_handle,
+            }));
+            Ok(())
+        })
+    }
+}
+
+/// Shared body for the handful of `Module` methods that just return a string
+fn string_method(
+    bridge: CapnpBridge,
+    handle: ObjectHandle,
+    method: &'static str,
+    set: impl FnOnce(&str) + 'static,
+) -> Promise<(), capnp::Error> {
+    Promise::from_future(async move {
+        let value = bridge.call_value(handle, method, vec![]).await.map_err(capnp_err)?;
+        set(value.as_str().unwrap_or_default());
+        Ok(())
+    })
+}
+
+struct DataStoreServer {
+    bridge: CapnpBridge,
+    handle: ObjectHandle,
+}
+
+impl data_store::Server for DataStoreServer {
+    fn get(
+        &mut self,
+        params: data_store::GetParams,
+        mut results: data_store::GetResults,
+    ) -> Promise<(), capnp::Error> {
+        let bridge = self.bridge.clone();
+        let handle = self.handle;
+        let key = pry!(pry!(params.get()).get_key()).to_string();
+        Promise::from_future(async move {
+            let value = bridge
+                .call_value(handle, "get", vec![serde_json::Value::String(key)])
+                .await
+                .map_err(capnp_err)?;
+            let mut builder = results.get();
+            match value.as_str() {
+                Some(s) => {
+                    builder.set_value(s.into());
+                    builder.set_present(true);
+                }
+                None => {
+                    builder.set_value("".into());
+                    builder.set_present(false);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn set(
+        &mut self,
+        params: data_store::SetParams,
+        _results: data_store::SetResults,
+    ) -> Promise<(), capnp::Error> {
+        let bridge = self.bridge.clone();
+        let handle = self.handle;
+        let params = pry!(params.get());
+        let key = pry!(pry!(params.get_key())).to_string();
+        let value = pry!(pry!(params.get_value())).to_string();
+        Promise::from_future(async move {
+            bridge
+                .call_value(
+                    handle,
+                    "set",
+                    vec![serde_json::Value::String(key), serde_json::Value::String(value)],
+                )
+                .await
+                .map_err(capnp_err)?;
+            Ok(())
+        })
+    }
+
+    fn delete(
+        &mut self,
+        params: data_store::DeleteParams,
+        _results: data_store::DeleteResults,
+    ) -> Promise<(), capnp::Error> {
+        let bridge = self.bridge.clone();
+        let handle = self.handle;
+        let key = pry!(pry!(params.get()).get_key()).to_string();
+        Promise::from_future(async move {
+            bridge
+                .call_value(handle, "delete", vec![serde_json::Value::String(key)])
+                .await
+                .map_err(capnp_err)?;
+            Ok(())
+        })
+    }
+
+    fn keys(
+        &mut self,
+        _: data_store::KeysParams,
+        mut results: data_store::KeysResults,
+    ) -> Promise<(), capnp::Error> {
+        let bridge = self.bridge.clone();
+        let handle = self.handle;
+        Promise::from_future(async move {
+            let value = bridge.call_value(handle, "keys", vec![]).await.map_err(capnp_err)?;
+            let keys = as_str_list(value);
+            let mut list = results.get().init_keys(keys.len() as u32);
+            for (i, key) in keys.into_iter().enumerate() {
+                list.set(i as u32, key.into());
+            }
+            Ok(())
+        })
+    }
+
+    fn clear(
+        &mut self,
+        _: data_store::ClearParams,
+        _results: data_store::ClearResults,
+    ) -> Promise<(), capnp::Error> {
+        let bridge = self.bridge.clone();
+        let handle = self.handle;
+        Promise::from_future(async move {
+            bridge.call_value(handle, "clear", vec![]).await.map_err(capnp_err)?;
+            Ok(())
+        })
+    }
+}
+
+struct SessionManagerServer {
+    bridge: CapnpBridge,
+    handle: ObjectHandle,
+}
+
+impl session_manager::Server for SessionManagerServer {
+    fn keys(
+        &mut self,
+        _: session_manager::KeysParams,
+        mut results: session_manager::KeysResults,
+    ) -> Promise<(), capnp::Error> {
+        let bridge = self.bridge.clone();
+        let handle = self.handle;
+        Promise::from_future(async move {
+            let value = bridge.call_value(handle, "keys", vec![]).await.map_err(capnp_err)?;
+            let ids: Vec<i64> = value
+                .as_array()
+                .map(|items| items.iter().filter_map(|v| v.as_i64()).collect())
+                .unwrap_or_default();
+            let mut list = results.get().init_ids(ids.len() as u32);
+            for (i, id) in ids.into_iter().enumerate() {
+                list.set(i as u32, id);
+            }
+            Ok(())
+        })
+    }
+}
+
+struct JobsServer {
+    bridge: CapnpBridge,
+    handle: ObjectHandle,
+}
+
+impl jobs::Server for JobsServer {
+    fn keys(&mut self, _: jobs::KeysParams, results: jobs::KeysResults) -> Promise<(), capnp::Error> {
+        refnames_method(self.bridge.clone(), self.handle, "keys", JobsKeysResults(results))
+    }
+}
+
+/// Thin wrapper so `Jobs#keys`'s `ids: List(Text)` field can reuse
+/// `refnames_method`/`SetRefnames` even though the generated field name
+/// (`ids`, not `refnames`) differs from `ModuleManager`'s.
+struct JobsKeysResults(jobs::KeysResults);
+
+impl SetRefnames for JobsKeysResults {
+    fn set_refnames(&mut self, refnames: &[String]) {
+        let mut list = self.0.get().init_ids(refnames.len() as u32);
+        for (i, id) in refnames.iter().enumerate() {
+            list.set(i as u32, id.as_str().into());
+        }
+    }
+}
+
+struct DbServer {
+    bridge: CapnpBridge,
+    handle: ObjectHandle,
+}
+
+impl db::Server for DbServer {
+    fn hosts(&mut self, _: db::HostsParams, results: db::HostsResults) -> Promise<(), capnp::Error> {
+        refnames_method(self.bridge.clone(), self.handle, "hosts", DbHostsResults(results))
+    }
+}
+
+struct DbHostsResults(db::HostsResults);
+
+impl SetRefnames for DbHostsResults {
+    fn set_refnames(&mut self, refnames: &[String]) {
+        let mut list = self.0.get().init_hosts(refnames.len() as u32);
+        for (i, host) in refnames.iter().enumerate() {
+            list.set(i as u32, host.as_str().into());
+        }
+    }
+}
+
+/// Accept connections on `addr` and serve the root `Framework` capability
+/// (backed by `ROOT_FRAMEWORK_HANDLE`) over capnp-rpc on each one.
+///
+/// Must be polled from within a `tokio::task::LocalSet`: `RpcSystem` and the
+/// generated `Client`/`Server` types are `!Send`, so unlike `http_transport`'s
+/// `serve` this can't be handed to a plain multi-threaded `tokio::spawn` - see
+/// `spawn_on_own_thread` below, which gives it a dedicated thread instead.
+async fn serve(addr: std::net::SocketAddr, bridge: CapnpBridge) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!("Cap'n Proto RPC server listening on {}", addr);
+
+    loop {
+        let (stream, peer_addr) = listener.accept().await?;
+        stream.set_nodelay(true).ok();
+        let bridge = bridge.clone();
+
+        tokio::task::spawn_local(async move {
+            let client: framework::Client = capnp_rpc::new_client(FrameworkServer::new(bridge));
+
+            let (reader, writer) = tokio_util::compat::TokioAsyncReadCompatExt::compat(stream).split();
+            let network = Box::new(twoparty::VatNetwork::new(
+                reader,
+                writer,
+                rpc_twoparty_capnp::Side::Server,
+                Default::default(),
+            ));
+            let rpc_system = RpcSystem::new(network, Some(client.client));
+
+            if let Err(e) = rpc_system.await {
+                tracing::warn!("Cap'n Proto connection from {} ended: {}", peer_addr, e);
+            }
+        });
+    }
+}
+
+/// Run the capnp-rpc server on a dedicated OS thread with its own
+/// current-thread runtime and `LocalSet`, since `serve`'s futures are
+/// `!Send` and can't share the daemon's main multi-threaded runtime
+pub fn spawn_on_own_thread(addr: std::net::SocketAddr, bridge: CapnpBridge) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build Cap'n Proto RPC runtime");
+        let local = tokio::task::LocalSet::new();
+        local.block_on(&runtime, async move {
+            if let Err(e) = serve(addr, bridge).await {
+                tracing::error!("Cap'n Proto RPC server error: {:#}", e);
+            }
+        });
+    })
+}
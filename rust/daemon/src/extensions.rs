@@ -0,0 +1,134 @@
+/// Host-side extension registry for runtime-pluggable RPC methods
+///
+/// `dispatch_call`'s registry (see `dispatch.rs`) only knows the fixed,
+/// compiled-in set of methods backed by the MSF bridge. `plugins_load`/
+/// `plugins_unload` manage *Metasploit* plugins, not daemon-side RPC
+/// extensions, so there was no way for an operator to bolt on post-
+/// exploitation workflows (credential parsing, loot transforms, report
+/// generation) without patching the core match. `CommandHandlerRegistry`
+/// lets a handler register a set of method names at runtime; `dispatch_call`
+/// consults it only after the compiled-in registry has no match, so built-in
+/// methods always take priority and can't be shadowed.
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A bundle of runtime-registered RPC methods
+pub trait CommandHandler: Send + Sync {
+    /// The method names this handler answers for
+    fn methods(&self) -> &[&str];
+
+    /// Handle one of `methods()`, given its positional arguments
+    fn handle(&self, method: &str, args: &[serde_json::Value]) -> Result<serde_json::Value>;
+}
+
+/// Maps method name -> the handler that owns it
+#[derive(Default)]
+pub struct CommandHandlerRegistry {
+    handlers: Mutex<HashMap<String, Arc<dyn CommandHandler>>>,
+}
+
+impl CommandHandlerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register every method `handler` reports, rejecting the whole batch if
+    /// any of its names collide with an already-registered extension method.
+    /// Names that collide with compiled-in methods are not rejected here;
+    /// `dispatch_call` only consults this registry once the built-in lookup
+    /// misses, so such a method is never reached.
+    pub fn register_handler(&self, handler: Arc<dyn CommandHandler>) -> Result<()> {
+        let mut handlers = self.handlers.lock();
+        for method in handler.methods() {
+            if handlers.contains_key(*method) {
+                return Err(anyhow!("Method '{}' is already registered by another handler", method));
+            }
+        }
+        for method in handler.methods() {
+            handlers.insert(method.to_string(), Arc::clone(&handler));
+        }
+        Ok(())
+    }
+
+    /// Unregister a single extension method by name. Returns `false` if no
+    /// handler owned it.
+    pub fn unregister_handler(&self, method: &str) -> bool {
+        self.handlers.lock().remove(method).is_some()
+    }
+
+    /// Look up and run an extension method, if one is registered for it
+    pub fn dispatch(&self, method: &str, args: &[serde_json::Value]) -> Option<Result<serde_json::Value>> {
+        let handler = self.handlers.lock().get(method).cloned()?;
+        Some(handler.handle(method, args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EchoHandler;
+
+    impl CommandHandler for EchoHandler {
+        fn methods(&self) -> &[&str] {
+            &["ext_echo"]
+        }
+
+        fn handle(&self, _method: &str, args: &[serde_json::Value]) -> Result<serde_json::Value> {
+            Ok(serde_json::json!({ "echoed": args }))
+        }
+    }
+
+    struct OtherEchoHandler;
+
+    impl CommandHandler for OtherEchoHandler {
+        fn methods(&self) -> &[&str] {
+            &["ext_echo"]
+        }
+
+        fn handle(&self, _method: &str, _args: &[serde_json::Value]) -> Result<serde_json::Value> {
+            Ok(serde_json::json!({}))
+        }
+    }
+
+    #[test]
+    fn test_register_and_dispatch() {
+        let registry = CommandHandlerRegistry::new();
+        registry.register_handler(Arc::new(EchoHandler)).unwrap();
+
+        let result = registry
+            .dispatch("ext_echo", &[serde_json::json!("hi")])
+            .expect("handler should be found")
+            .unwrap();
+        assert_eq!(result, serde_json::json!({ "echoed": ["hi"] }));
+    }
+
+    #[test]
+    fn test_dispatch_fall_through_for_unknown_method() {
+        let registry = CommandHandlerRegistry::new();
+        registry.register_handler(Arc::new(EchoHandler)).unwrap();
+
+        assert!(registry.dispatch("not_registered", &[]).is_none());
+    }
+
+    #[test]
+    fn test_name_collision_rejected() {
+        let registry = CommandHandlerRegistry::new();
+        registry.register_handler(Arc::new(EchoHandler)).unwrap();
+
+        let result = registry.register_handler(Arc::new(OtherEchoHandler));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_unregister() {
+        let registry = CommandHandlerRegistry::new();
+        registry.register_handler(Arc::new(EchoHandler)).unwrap();
+
+        assert!(registry.unregister_handler("ext_echo"));
+        assert!(!registry.unregister_handler("ext_echo"));
+        assert!(registry.dispatch("ext_echo", &[]).is_none());
+    }
+}
@@ -0,0 +1,221 @@
+/// Runner for external (non-Ruby) modules that speak the MSF external-module
+/// protocol - JSON-RPC over stdio instead of being `require`d into the Ruby
+/// VM. Each line written to the subprocess's stdin/read from its stdout is
+/// one JSON object: a `describe` request reads the module's metadata and
+/// options before anything runs, a `run` request with the datastore then
+/// streams back `message`/`report`/`job` events until the process exits.
+use bridge::{AssassinateError, OptionDescription};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+
+/// How to invoke one external module - inferred from its file extension by
+/// `ExternalModule::for_path` rather than hardcoded, since modules ship as
+/// `.py` scripts, `.go` sources, or prebuilt executables.
+#[derive(Debug, Clone)]
+pub struct ExternalModule {
+    pub module_path: PathBuf,
+    command: String,
+    args: Vec<String>,
+}
+
+/// Metadata and declared options pulled out of a module's `describe` response
+#[derive(Debug, Clone)]
+pub struct ExternalModuleDescription {
+    pub fullname: String,
+    pub description: String,
+    pub module_type: String,
+    pub options: Vec<OptionDescription>,
+}
+
+/// One event streamed back while a module is running
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExternalEvent {
+    Message { text: String },
+    Report { data: Value },
+    Job { id: String, status: String },
+}
+
+impl ExternalModule {
+    pub fn new(module_path: impl Into<PathBuf>, command: impl Into<String>, args: Vec<String>) -> Self {
+        Self { module_path: module_path.into(), command: command.into(), args }
+    }
+
+    /// Infer how to invoke `path` from its extension: `.py` under
+    /// `python3`, `.go` under `go run`, anything else assumed to already be
+    /// an executable and run directly.
+    pub fn for_path(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("py") => Self::new(path.clone(), "python3", vec![path.display().to_string()]),
+            Some("go") => Self::new(path.clone(), "go", vec!["run".to_string(), path.display().to_string()]),
+            _ => {
+                let command = path.display().to_string();
+                Self::new(path, command, vec![])
+            }
+        }
+    }
+
+    /// Send a `describe` request and parse the module's metadata/options
+    /// out of its response.
+    pub fn describe(&self) -> bridge::Result<ExternalModuleDescription> {
+        let mut child = self.spawn()?;
+        let response = self.request(&mut child, serde_json::json!({ "type": "describe" }));
+        let _ = child.kill();
+        let response = response?;
+
+        let fullname = response
+            .get("fullname")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                AssassinateError::ModuleExecutionError(format!(
+                    "{}: describe response missing 'fullname'",
+                    self.module_path.display()
+                ))
+            })?
+            .to_string();
+        let description = response.get("description").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let module_type = response.get("module_type").and_then(|v| v.as_str()).unwrap_or("external").to_string();
+        let options = response
+            .get("options")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(parse_option).collect())
+            .unwrap_or_default();
+
+        Ok(ExternalModuleDescription { fullname, description, module_type, options })
+    }
+
+    /// Send a `run` request with `datastore`, calling `on_event` for every
+    /// `message`/`report`/`job` event as it arrives, until the subprocess
+    /// exits.
+    pub fn run(&self, datastore: &HashMap<String, String>, mut on_event: impl FnMut(ExternalEvent)) -> bridge::Result<()> {
+        let mut child = self.spawn()?;
+        let result = (|| {
+            self.write_request(&mut child, &serde_json::json!({ "type": "run", "datastore": datastore }))?;
+
+            let stdout = child.stdout.take().ok_or_else(|| self.io_error("failed to open stdout"))?;
+            for line in BufReader::new(stdout).lines() {
+                let line = line.map_err(|e| self.io_error(&format!("failed to read event: {}", e)))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let event: ExternalEvent = serde_json::from_str(&line)
+                    .map_err(|e| self.io_error(&format!("malformed event {:?}: {}", line, e)))?;
+                on_event(event);
+            }
+
+            Ok(())
+        })();
+
+        let status = child.wait().map_err(|e| self.io_error(&format!("failed to wait on module process: {}", e)))?;
+        result?;
+
+        if !status.success() {
+            return Err(self.exit_error(&mut child, status));
+        }
+
+        Ok(())
+    }
+
+    fn spawn(&self) -> bridge::Result<Child> {
+        Command::new(&self.command)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| external_spawn_error(&self.command, e))
+    }
+
+    fn write_request(&self, child: &mut Child, request: &Value) -> bridge::Result<()> {
+        let stdin = child.stdin.as_mut().ok_or_else(|| self.io_error("failed to open stdin"))?;
+        writeln!(stdin, "{}", request).map_err(|e| self.io_error(&format!("failed to write request: {}", e)))
+    }
+
+    fn request(&self, child: &mut Child, request: Value) -> bridge::Result<Value> {
+        self.write_request(child, &request)?;
+
+        let mut line = String::new();
+        {
+            let stdout = child.stdout.as_mut().ok_or_else(|| self.io_error("failed to open stdout"))?;
+            let mut reader = BufReader::new(stdout);
+            reader.read_line(&mut line).map_err(|e| self.io_error(&format!("failed to read response: {}", e)))?;
+        }
+
+        if line.trim().is_empty() {
+            let status = child.wait().map_err(|e| self.io_error(&format!("failed to wait on module process: {}", e)))?;
+            return Err(self.exit_error(child, status));
+        }
+
+        serde_json::from_str(&line).map_err(|e| self.io_error(&format!("malformed response {:?}: {}", line, e)))
+    }
+
+    fn io_error(&self, reason: &str) -> AssassinateError {
+        AssassinateError::ModuleExecutionError(format!("{}: {}", self.module_path.display(), reason))
+    }
+
+    /// Build a `ModuleExecutionError` for a module process that exited
+    /// uncleanly, reading its stderr for a recognizable missing-dependency
+    /// message (see `missing_dependency_hint`) rather than just reporting
+    /// the exit code.
+    fn exit_error(&self, child: &mut Child, status: std::process::ExitStatus) -> AssassinateError {
+        let mut stderr_text = String::new();
+        if let Some(stderr) = child.stderr.as_mut() {
+            let _ = stderr.read_to_string(&mut stderr_text);
+        }
+
+        let detail = missing_dependency_hint(&stderr_text).unwrap_or_else(|| {
+            if stderr_text.trim().is_empty() {
+                format!("exited with {}", status)
+            } else {
+                format!("exited with {}: {}", status, stderr_text.trim())
+            }
+        });
+
+        AssassinateError::ModuleExecutionError(format!("{}: {}", self.module_path.display(), detail))
+    }
+}
+
+fn parse_option(value: &Value) -> Option<OptionDescription> {
+    Some(OptionDescription {
+        name: value.get("name")?.as_str()?.to_string(),
+        option_type: value.get("type").and_then(|v| v.as_str()).unwrap_or("string").to_string(),
+        required: value.get("required").and_then(|v| v.as_bool()).unwrap_or(false),
+        default: value.get("default").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        description: value.get("description").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+    })
+}
+
+/// Map a subprocess spawn failure (most commonly the interpreter itself not
+/// being on `PATH`) to `ModuleExecutionError` with a hint, instead of a bare
+/// OS error.
+fn external_spawn_error(command: &str, err: std::io::Error) -> AssassinateError {
+    let hint = if err.kind() == std::io::ErrorKind::NotFound {
+        format!("'{}' was not found on PATH - install the interpreter for this module", command)
+    } else {
+        format!("failed to launch '{}': {}", command, err)
+    };
+    AssassinateError::ModuleExecutionError(hint)
+}
+
+/// Recognize a handful of common "missing dependency" shapes in a module's
+/// stderr (Python's `ModuleNotFoundError`, Go's unresolved-package errors)
+/// and turn them into a concrete, actionable hint (e.g. "python-requests
+/// required") instead of a raw stack trace.
+fn missing_dependency_hint(stderr: &str) -> Option<String> {
+    if let Some(idx) = stderr.find("No module named") {
+        let rest = &stderr[idx..];
+        let name = rest.split('\'').nth(1)?;
+        return Some(format!("python-{} required (pip install {})", name, name));
+    }
+
+    if stderr.contains("cannot find package") || stderr.contains("no required module provides package") {
+        return Some("a required Go package is missing - run 'go mod tidy' for this module".to_string());
+    }
+
+    None
+}
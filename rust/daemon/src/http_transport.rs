@@ -0,0 +1,133 @@
+/// JSON-RPC 2.0 transport over HTTP, alongside the shared-memory ring buffers
+///
+/// `Daemon` wires `dispatch_call` exclusively to the ring buffers today,
+/// which limits clients to processes on the host that understand the custom
+/// MessagePack framing. This module exposes the same dispatch over an
+/// axum HTTP server instead, so remote orchestration and language bindings
+/// that can't map POSIX shared memory can drive the daemon too.
+///
+/// `Module`/`Framework` wrap a Ruby `Value`, which is not `Send`, so axum's
+/// handler (which runs on whatever worker thread the tokio runtime picks)
+/// can never call `dispatch_call` directly. Instead a handler packages the
+/// request as a `PendingCall` with a oneshot reply channel and pushes it
+/// onto an unbounded queue; `Daemon::run`'s poll loop drains that queue on
+/// its own thread right alongside ring-buffer reads and async job
+/// execution, then sends the result back over the oneshot.
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+
+/// A dispatch call requested over HTTP, waiting to be run on the daemon's thread
+pub struct PendingCall {
+    pub method: String,
+    pub args: Vec<serde_json::Value>,
+    pub responder: oneshot::Sender<anyhow::Result<serde_json::Value>>,
+}
+
+/// Sending half handed to the HTTP server; cheap to clone, one per connection
+#[derive(Clone)]
+pub struct HttpBridge {
+    sender: mpsc::UnboundedSender<PendingCall>,
+}
+
+impl HttpBridge {
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<PendingCall>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self { sender }, receiver)
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonRpcRequest {
+    #[allow(dead_code)]
+    jsonrpc: Option<String>,
+    method: String,
+    #[serde(default)]
+    params: Vec<serde_json::Value>,
+    id: serde_json::Value,
+}
+
+#[derive(Serialize)]
+struct JsonRpcResponse {
+    jsonrpc: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<JsonRpcError>,
+    id: serde_json::Value,
+}
+
+#[derive(Serialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+/// Serve JSON-RPC 2.0 over HTTP at `POST /`, forwarding every request to the
+/// daemon's dispatch loop through `bridge` and waiting for its reply
+pub async fn serve(addr: std::net::SocketAddr, bridge: HttpBridge) -> std::io::Result<()> {
+    let app = Router::new()
+        .route("/", post(handle_rpc))
+        .with_state(bridge);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!("JSON-RPC HTTP server listening on {}", addr);
+    axum::serve(listener, app).await
+}
+
+async fn handle_rpc(
+    State(bridge): State<HttpBridge>,
+    Json(request): Json<JsonRpcRequest>,
+) -> Json<JsonRpcResponse> {
+    let (responder, receiver) = oneshot::channel();
+    let id = request.id.clone();
+
+    if bridge
+        .sender
+        .send(PendingCall {
+            method: request.method,
+            args: request.params,
+            responder,
+        })
+        .is_err()
+    {
+        return Json(JsonRpcResponse {
+            jsonrpc: "2.0",
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32603,
+                message: "Daemon dispatch loop is not running".to_string(),
+            }),
+            id,
+        });
+    }
+
+    match receiver.await {
+        Ok(Ok(result)) => Json(JsonRpcResponse {
+            jsonrpc: "2.0",
+            result: Some(result),
+            error: None,
+            id,
+        }),
+        Ok(Err(e)) => Json(JsonRpcResponse {
+            jsonrpc: "2.0",
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32000,
+                message: format!("{:#}", e),
+            }),
+            id,
+        }),
+        Err(_) => Json(JsonRpcResponse {
+            jsonrpc: "2.0",
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32603,
+                message: "Dispatch loop dropped the call before replying".to_string(),
+            }),
+            id,
+        }),
+    }
+}
@@ -0,0 +1,176 @@
+/// Asynchronous job subsystem for long-running module execution
+///
+/// `Module`/`Framework` wrap a Ruby `Value` behind the `magnus` FFI, which is
+/// not `Send` - every Ruby call has to happen on the thread that owns the
+/// embedded interpreter, so a real `tokio::spawn` of an exploit run won't
+/// compile (and wouldn't be sound if it did). Instead, `run_module` enqueues
+/// the module execution and returns a `job_id` immediately; `Daemon::run`'s
+/// existing single-threaded poll loop drains one queued job per iteration
+/// alongside its ring-buffer reads, so the run happens on the same thread
+/// Ruby requires without ever blocking the IPC request that kicked it off.
+///
+/// Cancellation is cooperative and queue-only: a job can be cancelled while
+/// it's still `Queued`, removing it before it ever reaches `exploit`/`run`.
+/// Once a job is `Running` the underlying Ruby call is a single blocking
+/// FFI round trip with no checkpoint to interrupt, so a running job always
+/// runs to completion.
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// A module execution waiting to be drained by `Daemon::run`
+pub struct QueuedJob {
+    pub job_id: String,
+    pub module_id: String,
+    pub payload: Option<String>,
+    pub options: Option<HashMap<String, String>>,
+}
+
+struct JobEntry {
+    module_id: String,
+    status: Mutex<JobStatus>,
+    output: Mutex<Option<serde_json::Value>>,
+    error: Mutex<Option<String>>,
+    cancelled: AtomicBool,
+}
+
+impl JobEntry {
+    fn to_json(&self, job_id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "job_id": job_id,
+            "module_id": self.module_id,
+            "status": self.status.lock().as_str(),
+            "output": *self.output.lock(),
+            "error": *self.error.lock(),
+        })
+    }
+}
+
+/// Tracks every submitted job by id and the queue of work still waiting to run
+pub struct AsyncJobManager {
+    jobs: Mutex<HashMap<String, Arc<JobEntry>>>,
+    queue: Mutex<VecDeque<QueuedJob>>,
+    next_id: AtomicU64,
+}
+
+impl AsyncJobManager {
+    pub fn new() -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+            queue: Mutex::new(VecDeque::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Enqueue a module run/exploit, returning its job id without blocking
+    pub fn submit(
+        &self,
+        module_id: &str,
+        payload: Option<String>,
+        options: Option<HashMap<String, String>>,
+    ) -> String {
+        let job_id = self.next_id.fetch_add(1, Ordering::SeqCst).to_string();
+        let entry = Arc::new(JobEntry {
+            module_id: module_id.to_string(),
+            status: Mutex::new(JobStatus::Queued),
+            output: Mutex::new(None),
+            error: Mutex::new(None),
+            cancelled: AtomicBool::new(false),
+        });
+        self.jobs.lock().insert(job_id.clone(), entry);
+        self.queue.lock().push_back(QueuedJob {
+            job_id: job_id.clone(),
+            module_id: module_id.to_string(),
+            payload,
+            options,
+        });
+        job_id
+    }
+
+    /// Pop the next job that hasn't been cancelled while it waited in queue
+    pub fn next_runnable(&self) -> Option<QueuedJob> {
+        loop {
+            let job = self.queue.lock().pop_front()?;
+            let jobs = self.jobs.lock();
+            let Some(entry) = jobs.get(&job.job_id) else {
+                continue;
+            };
+            if entry.cancelled.load(Ordering::Relaxed) {
+                *entry.status.lock() = JobStatus::Cancelled;
+                continue;
+            }
+            return Some(job);
+        }
+    }
+
+    pub fn mark_running(&self, job_id: &str) {
+        if let Some(entry) = self.jobs.lock().get(job_id) {
+            *entry.status.lock() = JobStatus::Running;
+        }
+    }
+
+    pub fn mark_completed(&self, job_id: &str, output: serde_json::Value) {
+        if let Some(entry) = self.jobs.lock().get(job_id) {
+            *entry.output.lock() = Some(output);
+            *entry.status.lock() = JobStatus::Completed;
+        }
+    }
+
+    pub fn mark_failed(&self, job_id: &str, error: String) {
+        if let Some(entry) = self.jobs.lock().get(job_id) {
+            *entry.error.lock() = Some(error);
+            *entry.status.lock() = JobStatus::Failed;
+        }
+    }
+
+    /// Cancel a still-queued job. Returns false if the job doesn't exist or
+    /// has already started running (or finished).
+    pub fn cancel(&self, job_id: &str) -> bool {
+        let jobs = self.jobs.lock();
+        let Some(entry) = jobs.get(job_id) else {
+            return false;
+        };
+        let mut status = entry.status.lock();
+        if *status == JobStatus::Queued {
+            entry.cancelled.store(true, Ordering::Relaxed);
+            *status = JobStatus::Cancelled;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn status(&self, job_id: &str) -> Option<serde_json::Value> {
+        self.jobs.lock().get(job_id).map(|entry| entry.to_json(job_id))
+    }
+
+    pub fn list(&self) -> Vec<serde_json::Value> {
+        self.jobs
+            .lock()
+            .iter()
+            .map(|(job_id, entry)| entry.to_json(job_id))
+            .collect()
+    }
+}
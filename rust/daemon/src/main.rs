@@ -1,28 +1,79 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
-use bridge::{Framework, Module};
+use bridge::conversion::Conversion;
+use bridge::{DataStore, DbManager, Framework, JobManager, Module, SessionManager};
 use clap::Parser;
-use futures::stream::StreamExt;
-use ipc::{protocol, IpcError, RingBuffer, DEFAULT_BUFFER_SIZE, DEFAULT_SHM_NAME};
+use futures::stream::{FuturesUnordered, StreamExt};
+use futures::FutureExt;
+use ipc::{protocol, IpcChannel, IpcError, DEFAULT_BUFFER_SIZE, DEFAULT_SHM_NAME};
 use parking_lot::Mutex;
 use signal_hook::consts::{SIGINT, SIGTERM};
 use signal_hook_tokio::Signals;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
 
+mod capnp_server;
+mod dispatch;
+mod external_module;
+mod extensions;
+mod http_transport;
+mod jobs;
+mod metrics;
+mod module_cache;
+mod store;
+
+use capnp_server::{BridgeReply, CapnpBridge, ObjectHandle, ROOT_FRAMEWORK_HANDLE};
+use dispatch::{rpc_methods, MethodSpec};
+use extensions::CommandHandlerRegistry;
+use http_transport::{HttpBridge, PendingCall};
+use jobs::AsyncJobManager;
+use metrics::Metrics;
+use module_cache::{ModuleCache, SearchFilters};
+use store::ModuleStore;
+
+/// A single in-flight RPC call's eventual response frame, dispatched without
+/// waiting for an earlier call's response to be written first - see
+/// `Daemon::run`'s doc comment on `inflight` for how this stays on the single
+/// Ruby-VM-owning thread despite looking concurrent.
+type PipelinedResponse<'a> = Pin<Box<dyn Future<Output = Vec<u8>> + 'a>>;
+
+/// A live bridge object reachable by capnp clients through an `ObjectHandle`,
+/// other than the root `Framework` (handle `ROOT_FRAMEWORK_HANDLE`, which
+/// lives on `Daemon` directly rather than in this table)
+enum CapnpObject {
+    ModuleManager,
+    Module(Module),
+    DataStore(DataStore),
+    SessionManager(SessionManager),
+    Jobs(JobManager),
+    Db(DbManager),
+}
+
 /// Assassinate Daemon - High-performance IPC bridge to Metasploit Framework
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// Path to Metasploit Framework installation
+    /// Path to Metasploit Framework installation. Falls back to the
+    /// `MSF_LOCAL_LIB` env var, then a `metasploit-framework` directory next
+    /// to this binary, if not passed (see `bridge::RubyInitConfig`).
     #[arg(short, long)]
     msf_root: Option<PathBuf>,
 
+    /// Extra `$LOAD_PATH` entries to unshift ahead of the framework's own
+    /// `lib`, e.g. to force a locally-installed gem over the one the
+    /// framework bundles. May be passed more than once.
+    #[arg(long = "msf-extra-load-path")]
+    msf_extra_load_paths: Vec<PathBuf>,
+
     /// Shared memory name for IPC
     #[arg(short, long, default_value = DEFAULT_SHM_NAME)]
     shm_name: String,
@@ -34,19 +85,92 @@ struct Args {
     /// Log level (trace, debug, info, warn, error)
     #[arg(short, long, default_value = "info")]
     log_level: String,
+
+    /// Address to serve Prometheus metrics on (e.g. 127.0.0.1:9898); the
+    /// exporter only starts when this is set
+    #[arg(long)]
+    metrics_addr: Option<SocketAddr>,
+
+    /// Directory for persisted module/datastore state (enables crash-resilient restarts)
+    #[arg(long)]
+    state_dir: Option<PathBuf>,
+
+    /// Directory to cache scanned module metadata under (see `ModuleCache`);
+    /// the cache is rebuilt by walking the framework's module set if this
+    /// directory has no cache file yet. Disabled unless set.
+    #[arg(long)]
+    module_cache_dir: Option<PathBuf>,
+
+    /// Directory of external (non-Ruby) modules - `.py`/`.go` sources or
+    /// prebuilt executables speaking the external-module protocol (see
+    /// `external_module`). Scanned once at startup and merged into the
+    /// module cache. Disabled unless set.
+    #[arg(long)]
+    external_module_dir: Option<PathBuf>,
+
+    /// Seconds between background session-reaper sweeps (pruning dead MSF
+    /// sessions); the reaper is disabled when unset
+    #[arg(long)]
+    session_reap_interval_secs: Option<u64>,
+
+    /// Address to serve JSON-RPC 2.0 over HTTP on (e.g. 127.0.0.1:9899), for
+    /// clients that can't map the shared-memory ring buffers; disabled unless set
+    #[arg(long)]
+    http_addr: Option<SocketAddr>,
+
+    /// Address to serve the Cap'n Proto RPC interface on (e.g. 127.0.0.1:9900),
+    /// for clients that want typed, capability-based access to the MSF bridge
+    /// instead of the JSON-RPC methods; disabled unless set
+    #[arg(long)]
+    capnp_addr: Option<SocketAddr>,
 }
 
 /// Main daemon structure
 struct Daemon {
     framework: Framework,
-    request_buffer: RingBuffer,  // Python writes, Daemon reads
-    response_buffer: RingBuffer, // Daemon writes, Python reads
+    // Client->daemon and daemon->client traffic each get their own
+    // single-producer/single-consumer ring buffer segment; see `IpcChannel`.
+    channel: IpcChannel,
     shutdown: Arc<AtomicBool>,
     request_count: AtomicU64,
     error_count: AtomicU64,
     // Module instance storage
     modules: Arc<Mutex<HashMap<String, Module>>>,
+    // Creation path for each live module, needed to persist/rebuild state
+    module_paths: Arc<Mutex<HashMap<String, String>>>,
     next_module_id: AtomicU64,
+    metrics: Arc<Metrics>,
+    store: Option<Arc<ModuleStore>>,
+    // Scanned module metadata, built once at startup instead of per-lookup;
+    // `None` when `--module-cache-dir` wasn't passed.
+    module_cache: Option<ModuleCache>,
+    // Declarative method table backing `dispatch_call` and `describe_methods`
+    registry: Vec<MethodSpec<Daemon>>,
+    // Queued/running module executions addressable by job_id
+    async_jobs: Arc<AsyncJobManager>,
+    // How often `run`'s poll loop sweeps for dead sessions; `None` disables the reaper
+    session_reap_interval: Option<Duration>,
+    // Calls submitted by the JSON-RPC HTTP server, drained alongside the ring buffer
+    http_calls: Mutex<mpsc::UnboundedReceiver<PendingCall>>,
+    // Runtime-registered RPC methods, consulted when the compiled-in registry misses
+    extensions: CommandHandlerRegistry,
+    // Non-root objects (ModuleManager, created Modules, DataStores, ...) handed
+    // out to Cap'n Proto clients, addressable by the `ObjectHandle` they were
+    // returned under; the root `Framework` is always handle `ROOT_FRAMEWORK_HANDLE`
+    capnp_objects: Mutex<HashMap<ObjectHandle, CapnpObject>>,
+    next_capnp_handle: AtomicU64,
+    // Calls submitted by the Cap'n Proto RPC server, drained alongside the ring buffer
+    capnp_calls: Mutex<mpsc::UnboundedReceiver<capnp_server::BridgeCall>>,
+}
+
+/// Pull the `index`th Cap'n Proto call argument out as a string, or fail with
+/// a message naming the position - `dispatch_capnp_*` never takes malformed
+/// args (the capnp schema only lets `Text` params in), so this only trips on
+/// a bug in `capnp_server`'s call construction.
+fn capnp_string_arg(args: &[serde_json::Value], index: usize) -> Result<&str> {
+    args.get(index)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("expected a string argument at position {}", index))
 }
 
 /// Helper function to parse options from JSON Value to HashMap
@@ -58,24 +182,205 @@ fn parse_options(value: Option<&serde_json::Value>) -> Option<HashMap<String, St
     })
 }
 
+/// Like `parse_options`, but a value whose key has an entry in `conversions`
+/// (a map of option name -> `Conversion` spec string, e.g. `"RPORT": "port"`)
+/// is validated and normalized by that `Conversion` first, rather than
+/// requiring the caller to already be passing a JSON string. Options with no
+/// declared conversion are taken as they are: a string unchanged, a number or
+/// boolean as its text, and any other value is an error (unlike
+/// `parse_options`, which drops non-string values).
+fn parse_typed_options(
+    value: Option<&serde_json::Value>,
+    conversions: Option<&serde_json::Value>,
+) -> Result<Option<HashMap<String, String>>> {
+    let Some(obj) = value.and_then(|v| v.as_object()) else {
+        return Ok(None);
+    };
+    let specs = conversions.and_then(|v| v.as_object());
+
+    let mut out = HashMap::with_capacity(obj.len());
+    for (key, val) in obj {
+        let spec = specs.and_then(|s| s.get(key)).and_then(|v| v.as_str());
+        let coerced = match spec {
+            Some(spec) => Conversion::from_str(spec)?.convert(val)?,
+            None => val.clone(),
+        };
+        let as_string = match coerced {
+            serde_json::Value::String(s) => s,
+            serde_json::Value::Bool(b) => b.to_string(),
+            serde_json::Value::Number(n) => n.to_string(),
+            other => {
+                return Err(anyhow!("option {:?} did not resolve to a scalar value: {}", key, other))
+            }
+        };
+        out.insert(key.clone(), as_string);
+    }
+    Ok(Some(out))
+}
+
+/// Like `parse_typed_options`, but builds a `bridge::ModuleOptions` instead
+/// of immediately flattening every value to a string - used by
+/// `module_apply_options`, which pushes the whole batch into the module's
+/// datastore in one `RHash` update via `Module::apply_options`.
+fn build_module_options(
+    options: &serde_json::Value,
+    conversions: Option<&serde_json::Value>,
+) -> Result<bridge::ModuleOptions> {
+    let obj = options.as_object().context("options must be a JSON object")?;
+    let specs = conversions.and_then(|v| v.as_object());
+
+    let mut out = bridge::ModuleOptions::new();
+    for (key, val) in obj {
+        let spec = specs.and_then(|s| s.get(key)).and_then(|v| v.as_str());
+        let coerced = match spec {
+            Some(spec) => Conversion::from_str(spec)?.convert(val)?,
+            None => val.clone(),
+        };
+
+        let value = match spec {
+            Some("port") => bridge::OptionValue::Port(
+                coerced.as_u64().context("port did not resolve to a number")? as u16,
+            ),
+            Some("bool") => {
+                bridge::OptionValue::Bool(coerced.as_bool().context("bool did not resolve to a boolean")?)
+            }
+            Some("int") => bridge::OptionValue::Int(coerced.as_i64().context("int did not resolve to a number")?),
+            _ => bridge::OptionValue::Str(match coerced {
+                serde_json::Value::String(s) => s,
+                serde_json::Value::Bool(b) => b.to_string(),
+                serde_json::Value::Number(n) => n.to_string(),
+                other => {
+                    return Err(anyhow!("option {:?} did not resolve to a scalar value: {}", key, other))
+                }
+            }),
+        };
+
+        out.insert(key.clone(), value);
+    }
+
+    Ok(out)
+}
+
+/// Wrap every regular file directly under `dir` as an `ExternalModule`,
+/// inferring how to invoke it from its extension (see
+/// `ExternalModule::for_path`); no file is skipped for its extension. Not recursive - external
+/// modules are expected one file per module, same as `module_cache_dir`'s
+/// flat JSON file rather than a directory tree.
+fn discover_external_modules(dir: &std::path::Path) -> std::io::Result<Vec<external_module::ExternalModule>> {
+    let mut modules = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() {
+            modules.push(external_module::ExternalModule::for_path(path));
+        }
+    }
+    Ok(modules)
+}
+
 impl Daemon {
     /// Create a new daemon instance
     fn new(
         framework: Framework,
-        request_buffer: RingBuffer,
-        response_buffer: RingBuffer,
+        channel: IpcChannel,
         shutdown: Arc<AtomicBool>,
+        metrics: Arc<Metrics>,
+        store: Option<Arc<ModuleStore>>,
+        module_cache: Option<ModuleCache>,
+        session_reap_interval: Option<Duration>,
+        http_calls: mpsc::UnboundedReceiver<PendingCall>,
+        capnp_calls: mpsc::UnboundedReceiver<capnp_server::BridgeCall>,
     ) -> Self {
         Self {
             framework,
-            request_buffer,
-            response_buffer,
+            channel,
             shutdown,
             request_count: AtomicU64::new(0),
             error_count: AtomicU64::new(0),
             modules: Arc::new(Mutex::new(HashMap::new())),
+            module_paths: Arc::new(Mutex::new(HashMap::new())),
             next_module_id: AtomicU64::new(1),
+            metrics,
+            store,
+            module_cache,
+            registry: build_registry(),
+            async_jobs: Arc::new(AsyncJobManager::new()),
+            session_reap_interval,
+            http_calls: Mutex::new(http_calls),
+            extensions: CommandHandlerRegistry::new(),
+            capnp_objects: Mutex::new(HashMap::new()),
+            next_capnp_handle: AtomicU64::new(1),
+            capnp_calls: Mutex::new(capnp_calls),
+        }
+    }
+
+    /// Register a runtime extension handler for one or more RPC methods.
+    /// Fails if any of its method names are already owned by another
+    /// registered extension (compiled-in methods always win regardless).
+    pub fn register_handler(&self, handler: Arc<dyn extensions::CommandHandler>) -> Result<()> {
+        self.extensions.register_handler(handler)
+    }
+
+    /// Unregister a single extension method by name
+    pub fn unregister_handler(&self, method: &str) -> bool {
+        self.extensions.unregister_handler(method)
+    }
+
+    /// Replay persisted module creation parameters and datastore snapshots,
+    /// reconstructing live `Module` handles from the on-disk store.
+    fn restore_from_store(&self) -> Result<()> {
+        let Some(store) = self.store.as_ref() else {
+            return Ok(());
+        };
+
+        let persisted_modules = store.load_modules().context("Failed to load persisted modules")?;
+        let mut max_id = 0u64;
+
+        for (module_id, module_path) in persisted_modules {
+            let module = match self.framework.create_module(&module_path) {
+                Ok(m) => m,
+                Err(e) => {
+                    warn!(
+                        "Failed to recreate persisted module {} ({}): {}",
+                        module_id, module_path, e
+                    );
+                    continue;
+                }
+            };
+
+            let datastore = module.datastore()?;
+            for (key, value) in store
+                .load_datastore(&module_id)
+                .context("Failed to load persisted datastore")?
+            {
+                if let Err(e) = datastore.set(&key, &value) {
+                    warn!(
+                        "Failed to restore datastore key {} on module {}: {}",
+                        key, module_id, e
+                    );
+                }
+            }
+
+            if let Ok(id) = module_id.parse::<u64>() {
+                max_id = max_id.max(id);
+            }
+
+            self.modules.lock().insert(module_id.clone(), module);
+            self.module_paths.lock().insert(module_id, module_path);
         }
+
+        let next_id = store
+            .load_next_module_id()
+            .context("Failed to load next_module_id")?
+            .unwrap_or(max_id + 1)
+            .max(max_id + 1);
+        self.next_module_id.store(next_id, Ordering::SeqCst);
+
+        info!(
+            "Restored {} module(s) from persisted state",
+            self.modules.lock().len()
+        );
+
+        Ok(())
     }
 
     /// Main event loop - processes IPC requests
@@ -83,40 +388,149 @@ impl Daemon {
         info!("Daemon started - waiting for requests");
         let mut last_stats_log = Instant::now();
         let stats_interval = Duration::from_secs(60);
+        let mut last_session_reap = Instant::now();
 
         // Adaptive backoff for efficient polling
         let mut backoff_micros = 1u64;
         const MIN_BACKOFF_MICROS: u64 = 1;
         const MAX_BACKOFF_MICROS: u64 = 100;
 
+        // Calls read off the channel's request segment but not yet responded
+        // to. Each is
+        // dispatched as soon as it's read instead of waiting for an earlier
+        // call's response to be written first - a client that queues many
+        // calls (e.g. `list_modules` across every module type) no longer
+        // pays for a full round trip per call. This is still one future
+        // polled at a time on this same thread, not a `tokio::spawn` per
+        // call: `dispatch_call` reaches into `Framework`, which wraps a
+        // non-`Send` Ruby `Value`, so genuine off-thread concurrency isn't
+        // available here - what this buys is not blocking the next read on
+        // the previous call's Ruby-side work finishing, plus interleaving
+        // with whatever of that work *is* naturally async (job scheduling,
+        // I/O). Bounded so a burst of reads can't grow this without limit
+        // ahead of work actually completing.
+        const MAX_INFLIGHT_CALLS: usize = 32;
+        let mut inflight: FuturesUnordered<PipelinedResponse> = FuturesUnordered::new();
+        // Finished response frames waiting to be written, in completion
+        // order - which, since calls can finish out of order, need not
+        // match the order their requests were read in. Each frame carries
+        // its own `call_id` so a client routes it correctly regardless.
+        let mut pending_responses: VecDeque<Vec<u8>> = VecDeque::new();
+
         while !self.shutdown.load(Ordering::Relaxed) {
-            match self.request_buffer.try_read() {
-                Ok(data) => {
-                    // Reset backoff on successful read
-                    backoff_micros = MIN_BACKOFF_MICROS;
-                    self.request_count.fetch_add(1, Ordering::Relaxed);
-
-                    match self.process_request(data).await {
-                        Ok(()) => {}
-                        Err(e) => {
-                            self.error_count.fetch_add(1, Ordering::Relaxed);
-                            error!("Failed to process request: {:#}", e);
-                        }
+            let mut made_progress = false;
+
+            if inflight.len() < MAX_INFLIGHT_CALLS {
+                match self.channel.try_read() {
+                    Ok(data) => {
+                        made_progress = true;
+                        backoff_micros = MIN_BACKOFF_MICROS;
+                        self.request_count.fetch_add(1, Ordering::Relaxed);
+                        self.ingest_request(data, &mut inflight);
+                    }
+                    Err(IpcError::RingBufferEmpty) => {
+                        // No data available right now - fall through to the
+                        // inflight/response draining below before deciding
+                        // whether to back off.
+                    }
+                    Err(e) => {
+                        self.error_count.fetch_add(1, Ordering::Relaxed);
+                        error!("Ring buffer read error: {:#}", e);
+                        sleep(Duration::from_millis(10)).await;
                     }
                 }
-                Err(IpcError::RingBufferEmpty) => {
-                    // No data available - use adaptive backoff
-                    tokio::task::yield_now().await;
-                    sleep(Duration::from_micros(backoff_micros)).await;
+            }
+
+            // Collect every call that has finished since the last
+            // iteration, without blocking on one that hasn't.
+            while let Some(Some(response)) = inflight.next().now_or_never() {
+                made_progress = true;
+                pending_responses.push_back(response);
+            }
 
-                    // Exponential backoff: double the wait time up to maximum
-                    backoff_micros = (backoff_micros * 2).min(MAX_BACKOFF_MICROS);
+            // Flush as many finished responses as the ring buffer will
+            // currently accept. A full buffer applies backpressure by
+            // leaving the rest queued for a later iteration instead of
+            // dropping them - the client is still waiting on that `call_id`.
+            while let Some(response) = pending_responses.front() {
+                match self.channel.try_write(response) {
+                    Ok(()) => {
+                        made_progress = true;
+                        pending_responses.pop_front();
+                    }
+                    Err(IpcError::RingBufferFull(_)) => break,
+                    Err(e) => {
+                        self.error_count.fetch_add(1, Ordering::Relaxed);
+                        error!("Failed to write response to ring buffer: {:#}", e);
+                        pending_responses.pop_front();
+                    }
                 }
-                Err(e) => {
-                    self.error_count.fetch_add(1, Ordering::Relaxed);
-                    error!("Ring buffer read error: {:#}", e);
-                    sleep(Duration::from_millis(10)).await;
+            }
+
+            if made_progress {
+                backoff_micros = MIN_BACKOFF_MICROS;
+            } else if inflight.len() < MAX_INFLIGHT_CALLS {
+                // Nothing read, nothing finished, nothing written - block on
+                // the futex guarding the request segment's write position
+                // instead of busy-polling; a writer's `try_write` wakes this
+                // the instant new data lands, rather than after up to
+                // `backoff_micros` of sleeping. Once woken (or if data was
+                // already there), drain the whole burst with
+                // `try_read_batch` before looping back around to re-park,
+                // so a batch of queued calls costs one wake/wait pair
+                // instead of one per call.
+                let timeout = Duration::from_micros(backoff_micros);
+                // `read_blocking` is a real (bounded) blocking syscall, not
+                // an async sleep - tell tokio so it can hand this worker
+                // thread's other work to another one for the duration.
+                match tokio::task::block_in_place(|| self.channel.read_blocking(timeout)) {
+                    Ok(first) => {
+                        backoff_micros = MIN_BACKOFF_MICROS;
+                        self.request_count.fetch_add(1, Ordering::Relaxed);
+                        self.ingest_request(first, &mut inflight);
+
+                        let mut rest = Vec::new();
+                        let drained = self.channel.try_read_batch(&mut rest);
+                        self.request_count.fetch_add(drained as u64, Ordering::Relaxed);
+                        for data in rest {
+                            self.ingest_request(data, &mut inflight);
+                        }
+                    }
+                    Err(IpcError::Timeout(_)) => {
+                        backoff_micros = (backoff_micros * 2).min(MAX_BACKOFF_MICROS);
+                    }
+                    Err(e) => {
+                        self.error_count.fetch_add(1, Ordering::Relaxed);
+                        error!("Ring buffer read error: {:#}", e);
+                    }
                 }
+            } else {
+                // `inflight` is already at capacity - back off without
+                // touching the request buffer at all.
+                tokio::task::yield_now().await;
+                sleep(Duration::from_micros(backoff_micros)).await;
+                backoff_micros = (backoff_micros * 2).min(MAX_BACKOFF_MICROS);
+            }
+
+            // Drain at most one queued async job per iteration, so module
+            // execution interleaves with request processing on this same
+            // thread instead of starving the ring buffer behind a long run.
+            if let Some(job) = self.async_jobs.next_runnable() {
+                self.execute_job(job);
+            }
+
+            // Drain at most one JSON-RPC call submitted over HTTP per
+            // iteration. Handled on this same thread for the same reason as
+            // everything else here: `dispatch_call` touches `Framework`,
+            // which wraps a non-`Send` Ruby `Value`.
+            if let Ok(call) = self.http_calls.lock().try_recv() {
+                self.handle_http_call(call).await;
+            }
+
+            // Drain at most one Cap'n Proto RPC call per iteration, for the
+            // same non-`Send`-Ruby-`Value` reason as the HTTP calls above.
+            if let Ok(call) = self.capnp_calls.lock().try_recv() {
+                self.handle_capnp_call(call);
             }
 
             // Periodically log statistics
@@ -124,128 +538,466 @@ impl Daemon {
                 self.log_statistics();
                 last_stats_log = Instant::now();
             }
+
+            // Periodically sweep for dead sessions. Run inline on this same
+            // thread rather than as a spawned task: `Session`/`Framework`
+            // wrap a Ruby `Value`, which is not `Send`, so the reaper has to
+            // share the poll loop the same way `execute_job` does.
+            if let Some(interval) = self.session_reap_interval {
+                if last_session_reap.elapsed() >= interval {
+                    if let Err(e) = self.reap_stale_sessions() {
+                        warn!("Session reaper sweep failed: {:#}", e);
+                    }
+                    last_session_reap = Instant::now();
+                }
+            }
         }
 
         info!("Daemon shutting down gracefully");
         Ok(())
     }
 
-    /// Process a single IPC request
-    async fn process_request(&self, data: &[u8]) -> Result<()> {
-        let start = Instant::now();
-        let request_size = data.len();
-
-        // Deserialize request
-        let (call_id, method, args) =
-            protocol::deserialize_call(data).context("Failed to deserialize request")?;
+    /// Deserialize one raw request frame and push its dispatch onto
+    /// `inflight`, so `Daemon::run`'s read paths (single `try_read` and the
+    /// `read_blocking`/`try_read_batch` pair) don't each repeat this.
+    fn ingest_request<'a>(&'a self, data: &[u8], inflight: &mut FuturesUnordered<PipelinedResponse<'a>>) {
+        match protocol::deserialize_call(data, protocol::Format::MessagePack) {
+            Ok((call_id, method, args)) => {
+                let args: Vec<serde_json::Value> = args.into_iter().map(protocol::Payload::into_json).collect();
+                inflight.push(Box::pin(self.build_pipelined_response(call_id, method, args)));
+            }
+            Err(e) => {
+                self.error_count.fetch_add(1, Ordering::Relaxed);
+                error!("Failed to deserialize request: {:#}", e);
+            }
+        }
+    }
 
+    /// Dispatch one already-deserialized RPC call and build its response
+    /// frame, tagged with `call_id` so the caller can route it once it's
+    /// done - the whole reason this returns bytes instead of writing them
+    /// itself is so `Daemon::run` can hold many of these as futures in an
+    /// `inflight` set and write their responses back in whatever order they
+    /// finish, not the order they were read in.
+    async fn build_pipelined_response(
+        &self,
+        call_id: protocol::CallId,
+        method: String,
+        args: Vec<serde_json::Value>,
+    ) -> Vec<u8> {
         let num_args = args.len();
         debug!(
-            call_id = call_id,
+            call_id = %call_id,
             method = %method,
             num_args = num_args,
-            request_size = request_size,
             "Processing RPC call"
         );
 
-        // Dispatch and measure
         let dispatch_start = Instant::now();
         let response = match self.dispatch_call(&method, args).await {
             Ok(result) => {
                 let dispatch_time = dispatch_start.elapsed();
                 debug!(
-                    call_id = call_id,
+                    call_id = %call_id,
                     method = %method,
                     dispatch_ms = dispatch_time.as_millis(),
                     "RPC call succeeded"
                 );
-                protocol::serialize_response(call_id, result)?
+                self.metrics
+                    .record_success(&method, dispatch_time.as_secs_f64() * 1000.0);
+                protocol::serialize_response(
+                    call_id.clone(),
+                    protocol::Payload::Json(result),
+                    protocol::Format::MessagePack,
+                )
             }
             Err(e) => {
                 let dispatch_time = dispatch_start.elapsed();
                 let error_msg = format!("{:#}", e);
                 warn!(
-                    call_id = call_id,
+                    call_id = %call_id,
                     method = %method,
                     error = %error_msg,
                     dispatch_ms = dispatch_time.as_millis(),
                     "RPC call failed"
                 );
-                protocol::serialize_error(call_id, "CallFailed", &error_msg)?
+                self.metrics
+                    .record_error(&method, dispatch_time.as_secs_f64() * 1000.0);
+                protocol::serialize_error(call_id.clone(), "CallFailed", &error_msg, protocol::Format::MessagePack)
             }
         };
 
-        let response_size = response.len();
+        response.unwrap_or_else(|e| {
+            error!(call_id = %call_id, method = %method, error = %e, "Failed to serialize RPC response");
+            protocol::serialize_error(call_id, "SerializationFailed", &e.to_string(), protocol::Format::MessagePack)
+                .unwrap_or_default()
+        })
+    }
 
-        // Send response
-        self.response_buffer
-            .try_write(&response)
-            .context("Failed to write response to ring buffer")?;
+    /// Run one JSON-RPC call submitted over HTTP through the same dispatch
+    /// and metrics path as a ring-buffer request, then reply on its oneshot.
+    async fn handle_http_call(&self, call: http_transport::PendingCall) {
+        let dispatch_start = Instant::now();
+        let result = self.dispatch_call(&call.method, call.args).await;
+        let dispatch_ms = dispatch_start.elapsed().as_secs_f64() * 1000.0;
 
-        let total_time = start.elapsed();
-        debug!(
-            call_id = call_id,
-            method = %method,
-            total_ms = total_time.as_millis(),
-            response_size = response_size,
-            "Request completed"
-        );
+        match &result {
+            Ok(_) => self.metrics.record_success(&call.method, dispatch_ms),
+            Err(_) => self.metrics.record_error(&call.method, dispatch_ms),
+        }
 
-        Ok(())
+        // The HTTP connection may have been dropped before we finished
+        let _ = call.responder.send(result);
     }
 
-    /// Dispatch method call to MSF framework
-    async fn dispatch_call(
+    /// Resolve and run one Cap'n Proto RPC call, then reply on its oneshot.
+    /// Synchronous (no `.await` touches Ruby): every `bridge::*` wrapper call
+    /// is a blocking `funcall` into the VM, same as everywhere else in this file.
+    fn handle_capnp_call(&self, call: capnp_server::BridgeCall) {
+        let result = self.dispatch_capnp_call(call.receiver, call.method, call.args);
+        let _ = call.responder.send(result);
+    }
+
+    /// Allocate a handle for a newly-created capnp object and store it
+    fn insert_capnp_object(&self, object: CapnpObject) -> ObjectHandle {
+        let handle = self.next_capnp_handle.fetch_add(1, Ordering::Relaxed);
+        self.capnp_objects.lock().insert(handle, object);
+        handle
+    }
+
+    /// Route one Cap'n Proto method call to the bridge object it names. The
+    /// `method` strings here are `capnp_server`'s own vocabulary (chosen to
+    /// match the schema's field names), not Ruby method names.
+    fn dispatch_capnp_call(
+        &self,
+        receiver: ObjectHandle,
+        method: &'static str,
+        args: Vec<serde_json::Value>,
+    ) -> Result<BridgeReply> {
+        if receiver == ROOT_FRAMEWORK_HANDLE {
+            return self.dispatch_capnp_framework(method, args);
+        }
+
+        let object = self
+            .capnp_objects
+            .lock()
+            .remove(&receiver)
+            .ok_or_else(|| anyhow!("unknown Cap'n Proto object handle: {}", receiver))?;
+
+        let result = match &object {
+            CapnpObject::ModuleManager => self.dispatch_capnp_module_manager(method, args),
+            CapnpObject::Module(m) => self.dispatch_capnp_module(m, method, args),
+            CapnpObject::DataStore(d) => self.dispatch_capnp_datastore(d, method, args),
+            CapnpObject::SessionManager(s) => self.dispatch_capnp_session_manager(s, method, args),
+            CapnpObject::Jobs(j) => self.dispatch_capnp_jobs(j, method, args),
+            CapnpObject::Db(d) => self.dispatch_capnp_db(d, method, args),
+        };
+
+        // Objects are addressed by a stable handle for the life of the
+        // connection, so put it back regardless of whether this call failed.
+        self.capnp_objects.lock().insert(receiver, object);
+        result
+    }
+
+    fn dispatch_capnp_framework(&self, method: &str, args: Vec<serde_json::Value>) -> Result<BridgeReply> {
+        match method {
+            "version" => Ok(BridgeReply::Value(self.framework.version()?.into())),
+            "modules" => Ok(BridgeReply::Handle(self.insert_capnp_object(CapnpObject::ModuleManager))),
+            "sessions" => {
+                let sessions = self.framework.sessions()?;
+                Ok(BridgeReply::Handle(
+                    self.insert_capnp_object(CapnpObject::SessionManager(sessions)),
+                ))
+            }
+            "jobs" => {
+                let jobs = self.framework.jobs()?;
+                Ok(BridgeReply::Handle(self.insert_capnp_object(CapnpObject::Jobs(jobs))))
+            }
+            "db" => {
+                let db = self.framework.db()?;
+                Ok(BridgeReply::Handle(self.insert_capnp_object(CapnpObject::Db(db))))
+            }
+            "datastore" => {
+                let datastore = self.framework.datastore()?;
+                Ok(BridgeReply::Handle(
+                    self.insert_capnp_object(CapnpObject::DataStore(datastore)),
+                ))
+            }
+            "search" => {
+                let query = capnp_string_arg(&args, 0)?;
+                Ok(BridgeReply::Value(self.framework.search(query)?.into()))
+            }
+            other => Err(anyhow!("unknown Framework method: {}", other)),
+        }
+    }
+
+    fn dispatch_capnp_module_manager(&self, method: &str, args: Vec<serde_json::Value>) -> Result<BridgeReply> {
+        match method {
+            "exploits" => Ok(BridgeReply::Value(self.framework.list_modules("exploit")?.into())),
+            "auxiliary" => Ok(BridgeReply::Value(self.framework.list_modules("auxiliary")?.into())),
+            "payloads" => Ok(BridgeReply::Value(self.framework.list_modules("payload")?.into())),
+            "create" => self.dispatch_capnp_create_module(args),
+            other => Err(anyhow!("unknown ModuleManager method: {}", other)),
+        }
+    }
+
+    fn dispatch_capnp_create_module(&self, args: Vec<serde_json::Value>) -> Result<BridgeReply> {
+        let fullname = capnp_string_arg(&args, 0)?;
+        let module = self.framework.create_module(fullname)?;
+        Ok(BridgeReply::Handle(self.insert_capnp_object(CapnpObject::Module(module))))
+    }
+
+    fn dispatch_capnp_module(&self, module: &Module, method: &str, _args: Vec<serde_json::Value>) -> Result<BridgeReply> {
+        match method {
+            "name" => Ok(BridgeReply::Value(module.name()?.into())),
+            "fullname" => Ok(BridgeReply::Value(module.fullname()?.into())),
+            "description" => Ok(BridgeReply::Value(module.description()?.into())),
+            "module_type" => Ok(BridgeReply::Value(module.module_type()?.into())),
+            "rank" => Ok(BridgeReply::Value(module.rank()?.into())),
+            "privileged" => Ok(BridgeReply::Value(module.privileged()?.into())),
+            "datastore" => {
+                let datastore = module.datastore()?;
+                Ok(BridgeReply::Handle(
+                    self.insert_capnp_object(CapnpObject::DataStore(datastore)),
+                ))
+            }
+            other => Err(anyhow!("unknown Module method: {}", other)),
+        }
+    }
+
+    fn dispatch_capnp_datastore(&self, datastore: &DataStore, method: &str, args: Vec<serde_json::Value>) -> Result<BridgeReply> {
+        match method {
+            "get" => {
+                let key = capnp_string_arg(&args, 0)?;
+                Ok(BridgeReply::Value(match datastore.get(key)? {
+                    Some(value) => value.into(),
+                    None => serde_json::Value::Null,
+                }))
+            }
+            "set" => {
+                let key = capnp_string_arg(&args, 0)?;
+                let value = capnp_string_arg(&args, 1)?;
+                datastore.set(key, value)?;
+                Ok(BridgeReply::Value(serde_json::Value::Null))
+            }
+            "delete" => {
+                let key = capnp_string_arg(&args, 0)?;
+                datastore.delete(key)?;
+                Ok(BridgeReply::Value(serde_json::Value::Null))
+            }
+            "keys" => Ok(BridgeReply::Value(datastore.keys()?.into())),
+            "clear" => {
+                datastore.clear()?;
+                Ok(BridgeReply::Value(serde_json::Value::Null))
+            }
+            other => Err(anyhow!("unknown DataStore method: {}", other)),
+        }
+    }
+
+    fn dispatch_capnp_session_manager(
         &self,
+        sessions: &SessionManager,
         method: &str,
         _args: Vec<serde_json::Value>,
-    ) -> Result<serde_json::Value> {
+    ) -> Result<BridgeReply> {
         match method {
+            "keys" => Ok(BridgeReply::Value(sessions.list()?.into())),
+            other => Err(anyhow!("unknown SessionManager method: {}", other)),
+        }
+    }
+
+    fn dispatch_capnp_jobs(&self, jobs: &JobManager, method: &str, _args: Vec<serde_json::Value>) -> Result<BridgeReply> {
+        match method {
+            "keys" => Ok(BridgeReply::Value(jobs.list()?.into())),
+            other => Err(anyhow!("unknown Jobs method: {}", other)),
+        }
+    }
+
+    fn dispatch_capnp_db(&self, db: &DbManager, method: &str, _args: Vec<serde_json::Value>) -> Result<BridgeReply> {
+        match method {
+            "hosts" => Ok(BridgeReply::Value(db.hosts()?.into())),
+            other => Err(anyhow!("unknown Db method: {}", other)),
+        }
+    }
+
+    /// Dispatch method call to MSF framework via the declarative registry
+    /// built by `build_registry`. Each method's argument schema and handler
+    /// are looked up in `self.registry` instead of a hand-maintained match.
+    async fn dispatch_call(
+        &self,
+        method: &str,
+        args: Vec<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        if method == "describe_methods" {
+            return Ok(self.describe_methods());
+        }
+
+        match self.registry.iter().find(|spec| spec.name == method) {
+            Some(spec) => (spec.handler)(self, args).await,
+            None => match self.extensions.dispatch(method, &args) {
+                Some(result) => result,
+                None => {
+                    warn!("Unknown method called: {}", method);
+                    anyhow::bail!("Unknown method: {}", method)
+                }
+            },
+        }
+    }
+
+    /// Catalogue every registered method and its declared argument schema,
+    /// so clients can auto-generate bindings and validate calls up front
+    /// instead of discovering the surface by trial and error.
+    fn describe_methods(&self) -> serde_json::Value {
+        let methods: Vec<serde_json::Value> = self
+            .registry
+            .iter()
+            .map(|spec| {
+                let args: Vec<serde_json::Value> = spec
+                    .args
+                    .iter()
+                    .map(|arg| {
+                        serde_json::json!({
+                            "name": arg.name,
+                            "type": arg.ty.as_str(),
+                            "required": arg.required,
+                        })
+                    })
+                    .collect();
+                serde_json::json!({ "name": spec.name, "args": args })
+            })
+            .collect();
+
+        serde_json::json!({ "methods": methods })
+    }
+
+    /// Run a single queued job to completion and record its outcome.
+    ///
+    /// Called inline from `run`'s poll loop - never spawned - since `Module`
+    /// wraps a Ruby `Value` that can only be touched from this thread.
+    fn execute_job(&self, job: jobs::QueuedJob) {
+        self.async_jobs.mark_running(&job.job_id);
+
+        let modules = self.modules.lock();
+        let Some(module) = modules.get(&job.module_id) else {
+            drop(modules);
+            self.async_jobs
+                .mark_failed(&job.job_id, "Module not found".to_string());
+            return;
+        };
+
+        let result = if let Some(payload) = &job.payload {
+            module
+                .exploit(payload, job.options.clone())
+                .map(|session_id| serde_json::json!({ "session_id": session_id }))
+        } else {
+            module
+                .run(job.options.clone())
+                .map(|success| serde_json::json!({ "success": success }))
+        };
+        drop(modules);
+
+        match result {
+            Ok(output) => self.async_jobs.mark_completed(&job.job_id, output),
+            Err(e) => self.async_jobs.mark_failed(&job.job_id, e.to_string()),
+        }
+    }
+
+    /// Sweep every known session, pruning ones the MSF bridge reports dead.
+    ///
+    /// Called inline from `run`'s poll loop on the session-reap interval -
+    /// same constraint as `execute_job`, since `Session` is not `Send`.
+    fn reap_stale_sessions(&self) -> Result<()> {
+        let sessions = self.framework.sessions().context("Failed to get session manager")?;
+        let session_ids = sessions.list().context("Failed to list sessions")?;
+
+        let mut stale = 0u64;
+        for session_id in session_ids {
+            let Some(sess_val) = sessions.get_raw(session_id)? else {
+                continue;
+            };
+            let session = bridge::Session::from_raw(sess_val, session_id);
+            if !session.alive()? {
+                sessions.kill_raw(session_id)?;
+                stale += 1;
+            }
+        }
+
+        if stale > 0 {
+            info!("Session reaper pruned {} stale session(s)", stale);
+            self.metrics.add_stale_sessions(stale);
+        }
+
+        Ok(())
+    }
+
+    /// Log daemon statistics
+    fn log_statistics(&self) {
+        let requests = self.request_count.load(Ordering::Relaxed);
+        let errors = self.error_count.load(Ordering::Relaxed);
+        let req_util = self.channel.inbound_utilization();
+        let resp_util = self.channel.outbound_utilization();
+
+        self.metrics.set_modules_gauge(self.modules.lock().len() as u64);
+        self.metrics.set_ring_utilization(req_util, resp_util);
+
+        info!(
+            "Stats: {} requests, {} errors, req: {:.1}%, resp: {:.1}%",
+            requests,
+            errors,
+            req_util * 100.0,
+            resp_util * 100.0
+        );
+    }
+}
+
+/// Build the declarative RPC method table used by `Daemon::dispatch_call`
+/// and `Daemon::describe_methods`. See `dispatch::rpc_methods!` for the
+/// macro that expands each entry into a `MethodSpec`.
+fn build_registry() -> Vec<MethodSpec<Daemon>> {
+    rpc_methods! {
+        for Daemon;
             // === Framework Core Methods ===
-            "framework_version" => {
-                let version = self
+            "framework_version" [] => |daemon, _args| {
+                let version = daemon
                     .framework
                     .version()
                     .context("Failed to get framework version")?;
                 Ok(serde_json::json!({ "version": version }))
-            }
-
-            "list_modules" => {
+            },
+            "list_modules" [module_type: Str required] => |daemon, _args| {
                 let module_type = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing or invalid module_type argument")?;
 
-                let modules = self
+                let modules = daemon
                     .framework
                     .list_modules(module_type)
                     .context("Failed to list modules")?;
 
                 Ok(serde_json::json!({ "modules": modules }))
-            }
-
+            },
             // === Module Search and Discovery ===
-            "search" => {
+            "search" [query: Str required] => |daemon, _args| {
                 let query = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing or invalid search query")?;
 
-                let results = self
+                let results = daemon
                     .framework
                     .search(query)
                     .context("Failed to search modules")?;
 
                 Ok(serde_json::json!({ "results": results }))
-            }
-
-            "get_module_info" => {
+            },
+            "get_module_info" [module_name: Str required] => |daemon, _args| {
                 let module_name = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing or invalid module_name argument")?;
 
-                let module = self
+                let module = daemon
                     .framework
                     .create_module(module_name)
                     .context("Failed to create module")?;
@@ -258,19 +1010,23 @@ impl Daemon {
                     "disclosure_date": module.disclosure_date()?,
                     "description": module.description()?,
                 }))
-            }
-
-            "threads" => {
-                let threads = self
+            },
+            "threads" [] => |daemon, _args| {
+                let threads = daemon
                     .framework
                     .threads()
                     .context("Failed to get thread count")?;
                 Ok(serde_json::json!({ "threads": threads }))
-            }
-
+            },
+            // Full metrics snapshot as JSON - the same data `/metrics` serves in
+            // Prometheus text exposition format, for clients that would rather
+            // poll over the ring buffer than scrape an HTTP endpoint.
+            "stats" [] => |daemon, _args| {
+                Ok(daemon.metrics.snapshot())
+            },
             // === Session Listing ===
-            "list_sessions" => {
-                let session_manager = self
+            "list_sessions" [] => |daemon, _args| {
+                let session_manager = daemon
                     .framework
                     .sessions()
                     .context("Failed to get session manager")?;
@@ -278,39 +1034,146 @@ impl Daemon {
                 let session_ids = session_manager.list().context("Failed to list sessions")?;
 
                 Ok(serde_json::json!({ "session_ids": session_ids }))
-            }
+            },
+            // Enriched session listing: type/host/desc/alive per session, with
+            // optional creation-order sorting and live-only filtering. Distinct
+            // from "list_sessions" above, which only returns bare ids.
+            "session_list" [sort_by_id: Bool optional, live_only: Bool optional] => |daemon, _args| {
+                let sort_by_id = _args.get(0).and_then(|v| v.as_bool()).unwrap_or(false);
+                let live_only = _args.get(1).and_then(|v| v.as_bool()).unwrap_or(false);
+
+                let session_manager = daemon
+                    .framework
+                    .sessions()
+                    .context("Failed to get session manager")?;
+                let mut session_ids = session_manager.list().context("Failed to list sessions")?;
+                if sort_by_id {
+                    session_ids.sort_unstable();
+                }
 
+                let mut sessions = Vec::with_capacity(session_ids.len());
+                for session_id in session_ids {
+                    let Some(sess_val) = session_manager.get_raw(session_id)? else {
+                        continue;
+                    };
+                    let session = bridge::Session::from_raw(sess_val, session_id);
+                    let alive = session.alive()?;
+                    if live_only && !alive {
+                        continue;
+                    }
+                    sessions.push(serde_json::json!({
+                        "session_id": session_id,
+                        "type": session.session_type()?,
+                        "host": session.session_host()?,
+                        "desc": session.desc()?,
+                        "alive": alive,
+                    }));
+                }
+
+                Ok(serde_json::json!({ "sessions": sessions }))
+            },
+            // === Batch/Pipelined RPC ===
+            // Executes an ordered array of sub-calls against the same locked module/framework
+            // state, amortizing ring-buffer round trips for workflows like configuring a
+            // module's datastore one key at a time.
+            "batch" [calls: Array required, continue_on_error: Bool optional] => |daemon, _args| {
+                let calls = _args
+                    .get(0)
+                    .and_then(|v| v.as_array())
+                    .context("Missing or invalid batch call list")?;
+                // false (default) aborts on the first failing sub-call and reports its
+                // index; true runs every sub-call and embeds a result-or-error per entry.
+                let continue_on_error = _args.get(1).and_then(|v| v.as_bool()).unwrap_or(false);
+
+                let mut results = Vec::with_capacity(calls.len());
+                for (index, call) in calls.iter().enumerate() {
+                    let sub_method = call
+                        .get("method")
+                        .and_then(|v| v.as_str())
+                        .with_context(|| format!("Batch sub-call {} missing method", index))?;
+
+                    // Nesting would let a client recurse the ring buffer into itself
+                    // with no depth limit - reject it outright rather than bound it.
+                    if sub_method == "batch" {
+                        anyhow::bail!("Batch sub-call {} may not itself be \"batch\"", index);
+                    }
+
+                    let sub_args: Vec<serde_json::Value> = call
+                        .get("args")
+                        .and_then(|v| v.as_array())
+                        .cloned()
+                        .unwrap_or_default();
+
+                    daemon.request_count.fetch_add(1, Ordering::Relaxed);
+
+                    // Box the recursive call - dispatch_call can't call itself directly
+                    // without boxing the future (it would otherwise have infinite size).
+                    let sub_result = Box::pin(daemon.dispatch_call(sub_method, sub_args)).await;
+
+                    match sub_result {
+                        Ok(value) if continue_on_error => {
+                            results.push(serde_json::json!({ "ok": value }))
+                        }
+                        Ok(value) => results.push(value),
+                        Err(e) if continue_on_error => {
+                            daemon.error_count.fetch_add(1, Ordering::Relaxed);
+                            results.push(serde_json::json!({ "error": format!("{:#}", e) }));
+                        }
+                        Err(e) => {
+                            daemon.error_count.fetch_add(1, Ordering::Relaxed);
+                            return Ok(serde_json::json!({
+                                "batch_error_index": index,
+                                "error": format!("{:#}", e),
+                                "results": results,
+                            }));
+                        }
+                    }
+                }
+
+                Ok(serde_json::json!({ "results": results }))
+            },
             // === Module Instance Management ===
-            "create_module" => {
+            "create_module" [module_path: Str required] => |daemon, _args| {
                 let module_path = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing or invalid module_path argument")?;
 
                 // Create the module
-                let module = self
+                let module = daemon
                     .framework
                     .create_module(module_path)
                     .context("Failed to create module")?;
 
                 // Generate unique ID and store module
-                let module_id = self
+                let module_id = daemon
                     .next_module_id
                     .fetch_add(1, Ordering::SeqCst)
                     .to_string();
-                self.modules.lock().insert(module_id.clone(), module);
+                daemon.modules.lock().insert(module_id.clone(), module);
+                daemon.module_paths
+                    .lock()
+                    .insert(module_id.clone(), module_path.to_string());
+
+                if let Some(store) = &daemon.store {
+                    store
+                        .save_module(&module_id, module_path)
+                        .context("Failed to persist module")?;
+                    store
+                        .save_next_module_id(daemon.next_module_id.load(Ordering::SeqCst))
+                        .context("Failed to persist next_module_id")?;
+                }
 
                 Ok(serde_json::json!({ "module_id": module_id }))
-            }
-
+            },
             // === Module Information and Options ===
-            "module_info" => {
+            "module_info" [module_id: Str required] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing or invalid module_id argument")?;
 
-                let modules = self.modules.lock();
+                let modules = daemon.modules.lock();
                 let module = modules.get(module_id).context("Module not found")?;
 
                 Ok(serde_json::json!({
@@ -327,9 +1190,8 @@ impl Daemon {
                     "privileged": module.privileged().ok(),
                     "license": module.license().ok(),
                 }))
-            }
-
-            "module_set_option" => {
+            },
+            "module_set_option" [module_id: Str required, key: Str required, value: Str required] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
@@ -343,15 +1205,72 @@ impl Daemon {
                     .and_then(|v| v.as_str())
                     .context("Missing value")?;
 
-                let modules = self.modules.lock();
+                let modules = daemon.modules.lock();
                 let module = modules.get(module_id).context("Module not found")?;
                 let datastore = module.datastore()?;
                 datastore.set(key, value)?;
+                drop(modules);
+
+                if let Some(store) = &daemon.store {
+                    store
+                        .save_datastore_key(module_id, key, value)
+                        .context("Failed to persist datastore key")?;
+                }
 
                 Ok(serde_json::json!({}))
-            }
+            },
+            // Batch form of `module_set_option`: sets every key in `options`,
+            // coercing each one through the `Conversion` named for it in
+            // `conversions` (e.g. `{"RPORT": "port"}`) before it's pushed
+            // into the datastore as a string. Keys with no declared
+            // conversion are taken as they are (see `parse_typed_options`).
+            "module_set_options" [module_id: Str required, options: Object required, conversions: Object optional] => |daemon, _args| {
+                let module_id = _args
+                    .get(0)
+                    .and_then(|v| v.as_str())
+                    .context("Missing module_id")?;
+                let options = parse_typed_options(_args.get(1), _args.get(2))?
+                    .context("Missing options")?;
+
+                let modules = daemon.modules.lock();
+                let module = modules.get(module_id).context("Module not found")?;
+                let datastore = module.datastore()?;
+                for (key, value) in &options {
+                    datastore.set(key, value)?;
+                }
+                drop(modules);
+
+                if let Some(store) = &daemon.store {
+                    for (key, value) in &options {
+                        store
+                            .save_datastore_key(module_id, key, value)
+                            .context("Failed to persist datastore key")?;
+                    }
+                }
+
+                Ok(serde_json::json!({ "set": options.len() }))
+            },
+            // Typed alternative to `module_set_options`: options are kept as
+            // `bridge::OptionValue`s and pushed into the datastore in one
+            // `RHash` update (`Module::apply_options`) instead of one
+            // `[]=` call per key, and required options missing from the
+            // batch fail up front as `ModuleValidationError` instead of
+            // only surfacing once the module actually runs.
+            "module_apply_options" [module_id: Str required, options: Object required, conversions: Object optional] => |daemon, _args| {
+                let module_id = _args
+                    .get(0)
+                    .and_then(|v| v.as_str())
+                    .context("Missing module_id")?;
+                let options_arg = _args.get(1).context("Missing options")?;
+                let options = build_module_options(options_arg, _args.get(2))?;
+
+                let modules = daemon.modules.lock();
+                let module = modules.get(module_id).context("Module not found")?;
+                module.apply_options(&options)?;
 
-            "module_get_option" => {
+                Ok(serde_json::json!({ "applied": true }))
+            },
+            "module_get_option" [module_id: Str required, key: Str required] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
@@ -361,130 +1280,157 @@ impl Daemon {
                     .and_then(|v| v.as_str())
                     .context("Missing key")?;
 
-                let modules = self.modules.lock();
+                let modules = daemon.modules.lock();
                 let module = modules.get(module_id).context("Module not found")?;
                 let datastore = module.datastore()?;
                 let value = datastore.get(key)?;
 
                 Ok(serde_json::json!({ "value": value }))
-            }
-
-            "module_validate" => {
+            },
+            "module_validate" [module_id: Str required] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing module_id")?;
 
-                let modules = self.modules.lock();
+                let modules = daemon.modules.lock();
                 let module = modules.get(module_id).context("Module not found")?;
                 let valid = module.validate()?;
 
                 Ok(serde_json::json!({ "valid": valid }))
-            }
-
-            "module_compatible_payloads" => {
+            },
+            "module_compatible_payloads" [module_id: Str required] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing module_id")?;
 
-                let modules = self.modules.lock();
+                let modules = daemon.modules.lock();
                 let module = modules.get(module_id).context("Module not found")?;
                 let payloads = module.compatible_payloads()?;
 
                 Ok(serde_json::json!({ "payloads": payloads }))
-            }
-
-            "module_has_check" => {
+            },
+            "module_has_check" [module_id: Str required] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing module_id")?;
 
-                let modules = self.modules.lock();
+                let modules = daemon.modules.lock();
                 let module = modules.get(module_id).context("Module not found")?;
                 let has_check = module.has_check()?;
 
                 Ok(serde_json::json!({ "has_check": has_check }))
-            }
-
-            "module_check" => {
+            },
+            "module_check" [module_id: Str required] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing module_id")?;
 
-                let modules = self.modules.lock();
+                let modules = daemon.modules.lock();
                 let module = modules.get(module_id).context("Module not found")?;
                 let check_result = module.check()?;
 
                 Ok(serde_json::json!({ "check_result": check_result }))
-            }
-
-            "module_options" => {
+            },
+            "module_options" [module_id: Str required] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing module_id")?;
 
-                let modules = self.modules.lock();
+                let modules = daemon.modules.lock();
                 let module = modules.get(module_id).context("Module not found")?;
                 let options = module.options()?;
 
                 Ok(serde_json::json!({ "options": options }))
-            }
-
-            "module_targets" => {
+            },
+            "module_targets" [module_id: Str required] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing module_id")?;
 
-                let modules = self.modules.lock();
+                let modules = daemon.modules.lock();
                 let module = modules.get(module_id).context("Module not found")?;
                 let targets = module.targets()?;
 
                 Ok(serde_json::json!({ "targets": targets }))
-            }
-
-            "module_aliases" => {
+            },
+            "module_aliases" [module_id: Str required] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing module_id")?;
 
-                let modules = self.modules.lock();
+                let modules = daemon.modules.lock();
                 let module = modules.get(module_id).context("Module not found")?;
                 let aliases = module.aliases()?;
 
                 Ok(serde_json::json!({ "aliases": aliases }))
-            }
-
-            "module_notes" => {
+            },
+            "module_notes" [module_id: Str required] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing module_id")?;
 
-                let modules = self.modules.lock();
+                let modules = daemon.modules.lock();
                 let module = modules.get(module_id).context("Module not found")?;
                 let notes = module.notes()?;
 
                 Ok(serde_json::json!({ "notes": notes }))
-            }
+            },
+            // Structured options/targets/compatible_payloads in one call; see
+            // `Module::describe`. Supersedes stitching together
+            // `module_options`/`module_targets`/`module_compatible_payloads`
+            // by hand when a caller wants the advanced/evasion split or a
+            // target's index.
+            "module_describe" [module_id: Str required] => |daemon, _args| {
+                let module_id = _args
+                    .get(0)
+                    .and_then(|v| v.as_str())
+                    .context("Missing module_id")?;
+
+                let modules = daemon.modules.lock();
+                let module = modules.get(module_id).context("Module not found")?;
+                let description = module.describe()?;
+
+                let option_json = |opt: &bridge::OptionDescription| {
+                    serde_json::json!({
+                        "name": opt.name,
+                        "type": opt.option_type,
+                        "required": opt.required,
+                        "default": opt.default,
+                        "description": opt.description,
+                    })
+                };
 
+                Ok(serde_json::json!({
+                    "fullname": description.fullname,
+                    "options": description.options.iter().map(option_json).collect::<Vec<_>>(),
+                    "advanced_options": description.advanced_options.iter().map(option_json).collect::<Vec<_>>(),
+                    "evasion_options": description.evasion_options.iter().map(option_json).collect::<Vec<_>>(),
+                    "targets": description.targets.iter().map(|t| serde_json::json!({
+                        "index": t.index,
+                        "name": t.name,
+                    })).collect::<Vec<_>>(),
+                    "compatible_payloads": description.compatible_payloads,
+                }))
+            },
             // === Framework-level DataStore Operations ===
-            "framework_get_option" => {
+            "framework_get_option" [key: Str required] => |daemon, _args| {
                 let key = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing key")?;
-                let datastore = self.framework.datastore()?;
+                let datastore = daemon.framework.datastore()?;
                 let value = datastore.get(key)?;
                 Ok(serde_json::json!({ "value": value }))
-            }
-
-            "framework_set_option" => {
+            },
+            "framework_set_option" [key: Str required, value: Str required] => |daemon, _args| {
                 let key = _args
                     .get(0)
                     .and_then(|v| v.as_str())
@@ -493,47 +1439,42 @@ impl Daemon {
                     .get(1)
                     .and_then(|v| v.as_str())
                     .context("Missing value")?;
-                let datastore = self.framework.datastore()?;
+                let datastore = daemon.framework.datastore()?;
                 datastore.set(key, value)?;
                 Ok(serde_json::json!({}))
-            }
-
-            "framework_datastore_to_dict" => {
-                let datastore = self.framework.datastore()?;
+            },
+            "framework_datastore_to_dict" [] => |daemon, _args| {
+                let datastore = daemon.framework.datastore()?;
                 let dict = datastore.to_dict()?;
                 Ok(serde_json::json!({ "datastore": dict }))
-            }
-
-            "framework_delete_option" => {
+            },
+            "framework_delete_option" [key: Str required] => |daemon, _args| {
                 let key = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing key")?;
-                let datastore = self.framework.datastore()?;
+                let datastore = daemon.framework.datastore()?;
                 datastore.delete(key)?;
                 Ok(serde_json::json!({}))
-            }
-
-            "framework_clear_datastore" => {
-                let datastore = self.framework.datastore()?;
+            },
+            "framework_clear_datastore" [] => |daemon, _args| {
+                let datastore = daemon.framework.datastore()?;
                 datastore.clear()?;
                 Ok(serde_json::json!({}))
-            }
-
+            },
             // === Module-level DataStore Operations ===
-            "module_datastore_to_dict" => {
+            "module_datastore_to_dict" [module_id: Str required] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing module_id")?;
-                let modules = self.modules.lock();
+                let modules = daemon.modules.lock();
                 let module = modules.get(module_id).context("Module not found")?;
                 let datastore = module.datastore()?;
                 let dict = datastore.to_dict()?;
                 Ok(serde_json::json!({ "datastore": dict }))
-            }
-
-            "module_delete_option" => {
+            },
+            "module_delete_option" [module_id: Str required, key: Str required] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
@@ -542,41 +1483,84 @@ impl Daemon {
                     .get(1)
                     .and_then(|v| v.as_str())
                     .context("Missing key")?;
-                let modules = self.modules.lock();
+                let modules = daemon.modules.lock();
                 let module = modules.get(module_id).context("Module not found")?;
                 let datastore = module.datastore()?;
                 datastore.delete(key)?;
-                Ok(serde_json::json!({}))
-            }
+                drop(modules);
+
+                if let Some(store) = &daemon.store {
+                    store
+                        .delete_datastore_key(module_id, key)
+                        .context("Failed to remove persisted datastore key")?;
+                }
 
-            "module_clear_datastore" => {
+                Ok(serde_json::json!({}))
+            },
+            "module_clear_datastore" [module_id: Str required] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing module_id")?;
-                let modules = self.modules.lock();
+                let modules = daemon.modules.lock();
                 let module = modules.get(module_id).context("Module not found")?;
                 let datastore = module.datastore()?;
+                let cleared_keys = datastore.to_dict()?;
                 datastore.clear()?;
-                Ok(serde_json::json!({}))
-            }
+                drop(modules);
 
+                if let Some(store) = &daemon.store {
+                    for key in cleared_keys.keys() {
+                        store
+                            .delete_datastore_key(module_id, key)
+                            .context("Failed to remove persisted datastore key")?;
+                    }
+                }
+
+                Ok(serde_json::json!({}))
+            },
+            // === Module Metadata Cache ===
+            // Reads `daemon.module_cache`, built once at startup from
+            // `--module-cache-dir` instead of walking Ruby per call; see
+            // `module_cache.rs`.
+            "module_cache_search" [module_type: Str optional, platform: Str optional, text: Str optional] => |daemon, _args| {
+                let cache = daemon.module_cache.as_ref().context("Module cache not enabled (pass --module-cache-dir)")?;
+                let filters = SearchFilters {
+                    module_type: _args.get(0).and_then(|v| v.as_str()).map(|s| s.to_string()),
+                    platform: _args.get(1).and_then(|v| v.as_str()).map(|s| s.to_string()),
+                    text: _args.get(2).and_then(|v| v.as_str()).map(|s| s.to_string()),
+                };
+                let results = cache.search(&filters);
+                Ok(serde_json::json!({ "modules": results }))
+            },
+            "module_cache_info" [fullname: Str required] => |daemon, _args| {
+                let cache = daemon.module_cache.as_ref().context("Module cache not enabled (pass --module-cache-dir)")?;
+                let fullname = _args
+                    .get(0)
+                    .and_then(|v| v.as_str())
+                    .context("Missing fullname")?;
+                let info = cache.module_info(fullname).context("Module not found in cache")?;
+                Ok(serde_json::to_value(info)?)
+            },
+            "module_cache_diagnostics" [] => |daemon, _args| {
+                let cache = daemon.module_cache.as_ref().context("Module cache not enabled (pass --module-cache-dir)")?;
+                Ok(serde_json::json!({ "cached": cache.len(), "skipped": cache.skipped() }))
+            },
             // === PayloadGenerator Operations ===
-            "payload_generate" => {
+            "payload_generate" [payload_name: Str required, options: Object optional] => |daemon, _args| {
                 let payload_name = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing payload_name")?;
                 let options = parse_options(_args.get(1));
 
-                let pg = bridge::PayloadGenerator::new(&self.framework)?;
+                let pg = bridge::PayloadGenerator::new(&daemon.framework)?;
                 let payload_bytes = pg.generate(payload_name, options)?;
                 let payload_b64 = BASE64.encode(&payload_bytes);
 
                 Ok(serde_json::json!({ "payload": payload_b64 }))
-            }
-
-            "payload_generate_encoded" => {
+            },
+            "payload_generate_encoded" [payload_name: Str required, encoder: Str optional, iterations: I64 optional, options: Object optional] => |daemon, _args| {
                 let payload_name = _args
                     .get(0)
                     .and_then(|v| v.as_str())
@@ -585,21 +1569,19 @@ impl Daemon {
                 let iterations = _args.get(2).and_then(|v| v.as_i64()).map(|i| i as i32);
                 let options = parse_options(_args.get(3));
 
-                let pg = bridge::PayloadGenerator::new(&self.framework)?;
+                let pg = bridge::PayloadGenerator::new(&daemon.framework)?;
                 let payload_bytes =
                     pg.generate_encoded(payload_name, encoder, iterations, options)?;
                 let payload_b64 = BASE64.encode(&payload_bytes);
 
                 Ok(serde_json::json!({ "payload": payload_b64 }))
-            }
-
-            "payload_list_payloads" => {
-                let pg = bridge::PayloadGenerator::new(&self.framework)?;
+            },
+            "payload_list_payloads" [] => |daemon, _args| {
+                let pg = bridge::PayloadGenerator::new(&daemon.framework)?;
                 let payloads = pg.list_payloads()?;
                 Ok(serde_json::json!({ "payloads": payloads }))
-            }
-
-            "payload_generate_executable" => {
+            },
+            "payload_generate_executable" [payload_name: Str required, platform: Str required, arch: Str required, options: Object optional] => |daemon, _args| {
                 let payload_name = _args
                     .get(0)
                     .and_then(|v| v.as_str())
@@ -614,131 +1596,137 @@ impl Daemon {
                     .context("Missing arch")?;
                 let options = parse_options(_args.get(3));
 
-                let pg = bridge::PayloadGenerator::new(&self.framework)?;
+                let pg = bridge::PayloadGenerator::new(&daemon.framework)?;
                 let exe_bytes = pg.generate_executable(payload_name, platform, arch, options)?;
                 let exe_b64 = BASE64.encode(&exe_bytes);
 
                 Ok(serde_json::json!({ "executable": exe_b64 }))
-            }
+            },
+            "payload_generate_format" [payload_name: Str required, format: Str required, options: Object optional] => |daemon, _args| {
+                let payload_name = _args
+                    .get(0)
+                    .and_then(|v| v.as_str())
+                    .context("Missing payload_name")?;
+                let format = _args
+                    .get(1)
+                    .and_then(|v| v.as_str())
+                    .context("Missing format")?;
+                let format = bridge::PayloadFormat::from_str(format)?;
+                let options = parse_options(_args.get(2));
 
+                let pg = bridge::PayloadGenerator::new(&daemon.framework)?;
+                let rendered = pg.generate_payload(payload_name, options, format)?;
+
+                if format == bridge::PayloadFormat::Summary {
+                    let summary = String::from_utf8(rendered).context("Payload summary was not valid UTF-8")?;
+                    Ok(serde_json::json!({ "summary": summary }))
+                } else {
+                    Ok(serde_json::json!({ "payload": BASE64.encode(&rendered) }))
+                }
+            },
             // === Database Manager Operations ===
-            "db_hosts" => {
-                let db = self.framework.db()?;
+            "db_hosts" [] => |daemon, _args| {
+                let db = daemon.framework.db()?;
                 let hosts = db.hosts()?;
                 Ok(serde_json::json!({ "hosts": hosts }))
-            }
-
-            "db_services" => {
-                let db = self.framework.db()?;
+            },
+            "db_services" [] => |daemon, _args| {
+                let db = daemon.framework.db()?;
                 let services = db.services()?;
                 Ok(serde_json::json!({ "services": services }))
-            }
-
-            "db_report_host" => {
-                let db = self.framework.db()?;
+            },
+            "db_report_host" [options: Object optional] => |daemon, _args| {
+                let db = daemon.framework.db()?;
                 let host_id = db.report_host_raw(parse_options(_args.get(0)))?;
                 Ok(serde_json::json!({ "host_id": host_id }))
-            }
-
-            "db_report_service" => {
-                let db = self.framework.db()?;
+            },
+            "db_report_service" [options: Object optional] => |daemon, _args| {
+                let db = daemon.framework.db()?;
                 let service_id = db.report_service_raw(parse_options(_args.get(0)))?;
                 Ok(serde_json::json!({ "service_id": service_id }))
-            }
-
-            "db_report_vuln" => {
-                let db = self.framework.db()?;
+            },
+            "db_report_vuln" [options: Object optional] => |daemon, _args| {
+                let db = daemon.framework.db()?;
                 let vuln_id = db.report_vuln_raw(parse_options(_args.get(0)))?;
                 Ok(serde_json::json!({ "vuln_id": vuln_id }))
-            }
-
-            "db_report_cred" => {
-                let db = self.framework.db()?;
+            },
+            "db_report_cred" [options: Object optional] => |daemon, _args| {
+                let db = daemon.framework.db()?;
                 let cred_id = db.report_cred_raw(parse_options(_args.get(0)))?;
                 Ok(serde_json::json!({ "cred_id": cred_id }))
-            }
-
-            "db_vulns" => {
-                let db = self.framework.db()?;
+            },
+            "db_vulns" [] => |daemon, _args| {
+                let db = daemon.framework.db()?;
                 let vulns = db.vulns()?;
                 Ok(serde_json::json!({ "vulns": vulns }))
-            }
-
-            "db_creds" => {
-                let db = self.framework.db()?;
+            },
+            "db_creds" [] => |daemon, _args| {
+                let db = daemon.framework.db()?;
                 let creds = db.creds()?;
                 Ok(serde_json::json!({ "creds": creds }))
-            }
-
-            "db_loot" => {
-                let db = self.framework.db()?;
+            },
+            "db_loot" [] => |daemon, _args| {
+                let db = daemon.framework.db()?;
                 let loot = db.loot()?;
                 Ok(serde_json::json!({ "loot": loot }))
-            }
-
+            },
             // === Job Manager Operations ===
-            "job_list" => {
-                let jobs = self.framework.jobs()?;
+            "job_list" [] => |daemon, _args| {
+                let jobs = daemon.framework.jobs()?;
                 let job_ids = jobs.list()?;
                 Ok(serde_json::json!({ "job_ids": job_ids }))
-            }
-
-            "job_get" => {
+            },
+            "job_get" [job_id: Str required] => |daemon, _args| {
                 let job_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing job_id")?;
-                let jobs = self.framework.jobs()?;
+                let jobs = daemon.framework.jobs()?;
                 let job_info = jobs.get_raw(job_id)?;
                 Ok(serde_json::json!({ "job_info": job_info }))
-            }
-
-            "job_kill" => {
+            },
+            "job_kill" [job_id: Str required] => |daemon, _args| {
                 let job_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing job_id")?;
-                let jobs = self.framework.jobs()?;
+                let jobs = daemon.framework.jobs()?;
                 let success = jobs.kill_raw(job_id)?;
                 Ok(serde_json::json!({ "success": success }))
-            }
-
+            },
             // === Plugin Manager Operations ===
-            "plugins_list" => {
-                let plugins = self.framework.plugins()?;
+            "plugins_list" [] => |daemon, _args| {
+                let plugins = daemon.framework.plugins()?;
                 let plugin_names = plugins.list_raw()?;
                 Ok(serde_json::json!({ "plugins": plugin_names }))
-            }
-
-            "plugins_load" => {
+            },
+            "plugins_load" [path: Str required, options: Object optional] => |daemon, _args| {
                 let path = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing path")?;
                 let options = parse_options(_args.get(1));
 
-                let plugins = self.framework.plugins()?;
+                let plugins = daemon.framework.plugins()?;
                 let plugin_name = plugins.load_raw(path, options)?;
                 Ok(serde_json::json!({ "plugin_name": plugin_name }))
-            }
-
-            "plugins_unload" => {
+            },
+            "plugins_unload" [plugin_name: Str required] => |daemon, _args| {
                 let plugin_name = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing plugin_name")?;
-                let plugins = self.framework.plugins()?;
+                let plugins = daemon.framework.plugins()?;
                 let success = plugins.unload_raw(plugin_name)?;
                 Ok(serde_json::json!({ "success": success }))
-            }
-
+            },
             // === Session Manager Operations ===
-            "session_get" => {
+            "session_get" [session_id: I64 required] => |daemon, _args| {
                 let session_id = _args
                     .get(0)
                     .and_then(|v| v.as_i64())
                     .context("Missing session_id")?;
-                let sessions = self.framework.sessions()?;
+                let sessions = daemon.framework.sessions()?;
                 let session_val = sessions.get_raw(session_id)?;
 
                 if let Some(sess_val) = session_val {
@@ -754,24 +1742,22 @@ impl Daemon {
                 } else {
                     Ok(serde_json::json!({ "session": null }))
                 }
-            }
-
-            "session_kill" => {
+            },
+            "session_kill" [session_id: I64 required] => |daemon, _args| {
                 let session_id = _args
                     .get(0)
                     .and_then(|v| v.as_i64())
                     .context("Missing session_id")?;
-                let sessions = self.framework.sessions()?;
+                let sessions = daemon.framework.sessions()?;
                 let success = sessions.kill_raw(session_id)?;
                 Ok(serde_json::json!({ "success": success }))
-            }
-
-            "session_info" => {
+            },
+            "session_info" [session_id: I64 required] => |daemon, _args| {
                 let session_id = _args
                     .get(0)
                     .and_then(|v| v.as_i64())
                     .context("Missing session_id")?;
-                let sessions = self.framework.sessions()?;
+                let sessions = daemon.framework.sessions()?;
                 if let Some(sess_val) = sessions.get_raw(session_id)? {
                     let session = bridge::Session::from_raw(sess_val, session_id);
                     let info = session.info()?;
@@ -779,14 +1765,13 @@ impl Daemon {
                 } else {
                     Ok(serde_json::json!({ "info": null }))
                 }
-            }
-
-            "session_type" => {
+            },
+            "session_type" [session_id: I64 required] => |daemon, _args| {
                 let session_id = _args
                     .get(0)
                     .and_then(|v| v.as_i64())
                     .context("Missing session_id")?;
-                let sessions = self.framework.sessions()?;
+                let sessions = daemon.framework.sessions()?;
                 if let Some(sess_val) = sessions.get_raw(session_id)? {
                     let session = bridge::Session::from_raw(sess_val, session_id);
                     let session_type = session.session_type()?;
@@ -794,14 +1779,13 @@ impl Daemon {
                 } else {
                     Ok(serde_json::json!({ "type": null }))
                 }
-            }
-
-            "session_alive" => {
+            },
+            "session_alive" [session_id: I64 required] => |daemon, _args| {
                 let session_id = _args
                     .get(0)
                     .and_then(|v| v.as_i64())
                     .context("Missing session_id")?;
-                let sessions = self.framework.sessions()?;
+                let sessions = daemon.framework.sessions()?;
                 if let Some(sess_val) = sessions.get_raw(session_id)? {
                     let session = bridge::Session::from_raw(sess_val, session_id);
                     let alive = session.alive()?;
@@ -809,16 +1793,15 @@ impl Daemon {
                 } else {
                     Ok(serde_json::json!({ "alive": false }))
                 }
-            }
-
-            "session_read" => {
+            },
+            "session_read" [session_id: I64 required, length: U64 optional] => |daemon, _args| {
                 let session_id = _args
                     .get(0)
                     .and_then(|v| v.as_i64())
                     .context("Missing session_id")?;
                 let length = _args.get(1).and_then(|v| v.as_u64()).map(|v| v as usize);
 
-                let sessions = self.framework.sessions()?;
+                let sessions = daemon.framework.sessions()?;
                 if let Some(sess_val) = sessions.get_raw(session_id)? {
                     let session = bridge::Session::from_raw(sess_val, session_id);
                     let data = session.read_raw(length)?;
@@ -826,9 +1809,8 @@ impl Daemon {
                 } else {
                     anyhow::bail!("Session not found")
                 }
-            }
-
-            "session_write" => {
+            },
+            "session_write" [session_id: I64 required, data: Str required] => |daemon, _args| {
                 let session_id = _args
                     .get(0)
                     .and_then(|v| v.as_i64())
@@ -838,7 +1820,7 @@ impl Daemon {
                     .and_then(|v| v.as_str())
                     .context("Missing data")?;
 
-                let sessions = self.framework.sessions()?;
+                let sessions = daemon.framework.sessions()?;
                 if let Some(sess_val) = sessions.get_raw(session_id)? {
                     let session = bridge::Session::from_raw(sess_val, session_id);
                     let bytes_written = session.write_raw(data)?;
@@ -846,9 +1828,8 @@ impl Daemon {
                 } else {
                     anyhow::bail!("Session not found")
                 }
-            }
-
-            "session_execute" => {
+            },
+            "session_execute" [session_id: I64 required, command: Str required] => |daemon, _args| {
                 let session_id = _args
                     .get(0)
                     .and_then(|v| v.as_i64())
@@ -858,7 +1839,7 @@ impl Daemon {
                     .and_then(|v| v.as_str())
                     .context("Missing command")?;
 
-                let sessions = self.framework.sessions()?;
+                let sessions = daemon.framework.sessions()?;
                 if let Some(sess_val) = sessions.get_raw(session_id)? {
                     let session = bridge::Session::from_raw(sess_val, session_id);
                     let output = session.execute_raw(command)?;
@@ -866,9 +1847,8 @@ impl Daemon {
                 } else {
                     anyhow::bail!("Session not found")
                 }
-            }
-
-            "session_run_cmd" => {
+            },
+            "session_run_cmd" [session_id: I64 required, command: Str required] => |daemon, _args| {
                 let session_id = _args
                     .get(0)
                     .and_then(|v| v.as_i64())
@@ -878,7 +1858,7 @@ impl Daemon {
                     .and_then(|v| v.as_str())
                     .context("Missing command")?;
 
-                let sessions = self.framework.sessions()?;
+                let sessions = daemon.framework.sessions()?;
                 if let Some(sess_val) = sessions.get_raw(session_id)? {
                     let session = bridge::Session::from_raw(sess_val, session_id);
                     let output = session.run_cmd_raw(command)?;
@@ -886,14 +1866,13 @@ impl Daemon {
                 } else {
                     anyhow::bail!("Session not found")
                 }
-            }
-
-            "session_desc" => {
+            },
+            "session_desc" [session_id: I64 required] => |daemon, _args| {
                 let session_id = _args
                     .get(0)
                     .and_then(|v| v.as_i64())
                     .context("Missing session_id")?;
-                let sessions = self.framework.sessions()?;
+                let sessions = daemon.framework.sessions()?;
                 if let Some(sess_val) = sessions.get_raw(session_id)? {
                     let session = bridge::Session::from_raw(sess_val, session_id);
                     let desc = session.desc()?;
@@ -901,14 +1880,13 @@ impl Daemon {
                 } else {
                     Ok(serde_json::json!({ "desc": null }))
                 }
-            }
-
-            "session_host" => {
+            },
+            "session_host" [session_id: I64 required] => |daemon, _args| {
                 let session_id = _args
                     .get(0)
                     .and_then(|v| v.as_i64())
                     .context("Missing session_id")?;
-                let sessions = self.framework.sessions()?;
+                let sessions = daemon.framework.sessions()?;
                 if let Some(sess_val) = sessions.get_raw(session_id)? {
                     let session = bridge::Session::from_raw(sess_val, session_id);
                     let host = session.session_host()?;
@@ -916,14 +1894,13 @@ impl Daemon {
                 } else {
                     Ok(serde_json::json!({ "host": null }))
                 }
-            }
-
-            "session_port" => {
+            },
+            "session_port" [session_id: I64 required] => |daemon, _args| {
                 let session_id = _args
                     .get(0)
                     .and_then(|v| v.as_i64())
                     .context("Missing session_id")?;
-                let sessions = self.framework.sessions()?;
+                let sessions = daemon.framework.sessions()?;
                 if let Some(sess_val) = sessions.get_raw(session_id)? {
                     let session = bridge::Session::from_raw(sess_val, session_id);
                     let port = session.session_port()?;
@@ -931,14 +1908,13 @@ impl Daemon {
                 } else {
                     Ok(serde_json::json!({ "port": null }))
                 }
-            }
-
-            "session_tunnel_peer" => {
+            },
+            "session_tunnel_peer" [session_id: I64 required] => |daemon, _args| {
                 let session_id = _args
                     .get(0)
                     .and_then(|v| v.as_i64())
                     .context("Missing session_id")?;
-                let sessions = self.framework.sessions()?;
+                let sessions = daemon.framework.sessions()?;
                 if let Some(sess_val) = sessions.get_raw(session_id)? {
                     let session = bridge::Session::from_raw(sess_val, session_id);
                     let tunnel_peer = session.tunnel_peer()?;
@@ -946,14 +1922,13 @@ impl Daemon {
                 } else {
                     Ok(serde_json::json!({ "tunnel_peer": null }))
                 }
-            }
-
-            "session_target_host" => {
+            },
+            "session_target_host" [session_id: I64 required] => |daemon, _args| {
                 let session_id = _args
                     .get(0)
                     .and_then(|v| v.as_i64())
                     .context("Missing session_id")?;
-                let sessions = self.framework.sessions()?;
+                let sessions = daemon.framework.sessions()?;
                 if let Some(sess_val) = sessions.get_raw(session_id)? {
                     let session = bridge::Session::from_raw(sess_val, session_id);
                     let target_host = session.target_host()?;
@@ -961,14 +1936,13 @@ impl Daemon {
                 } else {
                     Ok(serde_json::json!({ "target_host": null }))
                 }
-            }
-
-            "session_via_exploit" => {
+            },
+            "session_via_exploit" [session_id: I64 required] => |daemon, _args| {
                 let session_id = _args
                     .get(0)
                     .and_then(|v| v.as_i64())
                     .context("Missing session_id")?;
-                let sessions = self.framework.sessions()?;
+                let sessions = daemon.framework.sessions()?;
                 if let Some(sess_val) = sessions.get_raw(session_id)? {
                     let session = bridge::Session::from_raw(sess_val, session_id);
                     let via_exploit = session.via_exploit()?;
@@ -976,14 +1950,13 @@ impl Daemon {
                 } else {
                     Ok(serde_json::json!({ "via_exploit": null }))
                 }
-            }
-
-            "session_via_payload" => {
+            },
+            "session_via_payload" [session_id: I64 required] => |daemon, _args| {
                 let session_id = _args
                     .get(0)
                     .and_then(|v| v.as_i64())
                     .context("Missing session_id")?;
-                let sessions = self.framework.sessions()?;
+                let sessions = daemon.framework.sessions()?;
                 if let Some(sess_val) = sessions.get_raw(session_id)? {
                     let session = bridge::Session::from_raw(sess_val, session_id);
                     let via_payload = session.via_payload()?;
@@ -991,10 +1964,9 @@ impl Daemon {
                 } else {
                     Ok(serde_json::json!({ "via_payload": null }))
                 }
-            }
-
+            },
             // === Module Execution ===
-            "module_exploit" => {
+            "module_exploit" [module_id: Str required, payload: Str required, options: Object optional] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
@@ -1005,60 +1977,121 @@ impl Daemon {
                     .context("Missing payload")?;
                 let options = parse_options(_args.get(2));
 
-                let modules = self.modules.lock();
+                let modules = daemon.modules.lock();
                 let module = modules.get(module_id).context("Module not found")?;
                 let session_id = module.exploit(payload, options)?;
 
                 Ok(serde_json::json!({ "session_id": session_id }))
-            }
-
-            "module_run" => {
+            },
+            "module_run" [module_id: Str required, options: Object optional] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing module_id")?;
                 let options = parse_options(_args.get(1));
 
-                let modules = self.modules.lock();
+                let modules = daemon.modules.lock();
                 let module = modules.get(module_id).context("Module not found")?;
                 let success = module.run(options)?;
 
                 Ok(serde_json::json!({ "success": success }))
-            }
-
-            "delete_module" => {
+            },
+            "delete_module" [module_id: Str required] => |daemon, _args| {
                 let module_id = _args
                     .get(0)
                     .and_then(|v| v.as_str())
                     .context("Missing module_id")?;
 
-                let mut modules = self.modules.lock();
+                let mut modules = daemon.modules.lock();
                 let existed = modules.remove(module_id).is_some();
+                drop(modules);
+                daemon.module_paths.lock().remove(module_id);
+
+                if existed {
+                    if let Some(store) = &daemon.store {
+                        store
+                            .delete_module(module_id)
+                            .context("Failed to remove persisted module")?;
+                    }
+                }
 
                 Ok(serde_json::json!({ "deleted": existed }))
-            }
+            },
+            // === State Persistence ===
+            // Flushes the full in-memory session (module instances + datastore) to disk,
+            // recovering a consistent on-disk snapshot if the store and the live session
+            // have drifted apart (e.g. after a restart without --state-dir).
+            "state_rebuild" [] => |daemon, _args| {
+                let store = daemon
+                    .store
+                    .as_ref()
+                    .context("No --state-dir configured; nothing to rebuild")?;
+
+                let modules = daemon.modules.lock();
+                let module_paths = daemon.module_paths.lock();
+                let mut snapshot = Vec::with_capacity(modules.len());
+                for (module_id, module) in modules.iter() {
+                    let path = module_paths.get(module_id).cloned().unwrap_or_default();
+                    let datastore = module.datastore()?.to_dict()?.into_iter().collect();
+                    snapshot.push((module_id.clone(), path, datastore));
+                }
+                let next_id = daemon.next_module_id.load(Ordering::SeqCst);
+                drop(module_paths);
+                drop(modules);
+
+                store
+                    .rebuild(next_id, &snapshot)
+                    .context("Failed to rebuild persisted state")?;
+
+                Ok(serde_json::json!({ "rebuilt_modules": snapshot.len() }))
+            },
+            // === Asynchronous Job Subsystem ===
+            // Distinct from the "job_*" methods above, which address Metasploit's
+            // own framework-level job manager - these address Rust-side module
+            // executions queued by `run_module` (see `jobs::AsyncJobManager`).
+            "run_module" [module_id: Str required, payload: Str optional, options: Object optional, conversions: Object optional] => |daemon, _args| {
+                let module_id = _args
+                    .get(0)
+                    .and_then(|v| v.as_str())
+                    .context("Missing module_id")?;
 
-            _ => {
-                warn!("Unknown method called: {}", method);
-                anyhow::bail!("Unknown method: {}", method)
-            }
-        }
-    }
+                {
+                    let modules = daemon.modules.lock();
+                    modules.get(module_id).context("Module not found")?;
+                }
 
-    /// Log daemon statistics
-    fn log_statistics(&self) {
-        let requests = self.request_count.load(Ordering::Relaxed);
-        let errors = self.error_count.load(Ordering::Relaxed);
-        let req_util = self.request_buffer.utilization();
-        let resp_util = self.response_buffer.utilization();
+                let payload = _args.get(1).and_then(|v| v.as_str()).map(|s| s.to_string());
+                let options = parse_typed_options(_args.get(2), _args.get(3))?;
+                let job_id = daemon.async_jobs.submit(module_id, payload, options);
 
-        info!(
-            "Stats: {} requests, {} errors, req: {:.1}%, resp: {:.1}%",
-            requests,
-            errors,
-            req_util * 100.0,
-            resp_util * 100.0
-        );
+                Ok(serde_json::json!({ "job_id": job_id }))
+            },
+            "async_job_status" [job_id: Str required] => |daemon, _args| {
+                let job_id = _args
+                    .get(0)
+                    .and_then(|v| v.as_str())
+                    .context("Missing job_id")?;
+
+                let status = daemon
+                    .async_jobs
+                    .status(job_id)
+                    .context("Job not found")?;
+
+                Ok(status)
+            },
+            "async_job_list" [] => |daemon, _args| {
+                Ok(serde_json::json!({ "jobs": daemon.async_jobs.list() }))
+            },
+            "async_job_cancel" [job_id: Str required] => |daemon, _args| {
+                let job_id = _args
+                    .get(0)
+                    .and_then(|v| v.as_str())
+                    .context("Missing job_id")?;
+
+                let cancelled = daemon.async_jobs.cancel(job_id);
+
+                Ok(serde_json::json!({ "cancelled": cancelled }))
+            },
     }
 }
 
@@ -1093,30 +2126,47 @@ async fn main() -> Result<()> {
     info!("Assassinate Daemon starting...");
     info!("Shared memory: {}", args.shm_name);
     info!("Buffer size: {} bytes", args.buffer_size);
+    match args.metrics_addr {
+        Some(addr) => info!("Metrics address: {}", addr),
+        None => info!("Metrics HTTP exporter disabled (pass --metrics-addr to enable)"),
+    }
+    match args.session_reap_interval_secs {
+        Some(secs) => info!("Session reaper enabled, sweeping every {}s", secs),
+        None => info!("Session reaper disabled (pass --session-reap-interval-secs to enable)"),
+    }
+    match args.http_addr {
+        Some(addr) => info!("JSON-RPC HTTP address: {}", addr),
+        None => info!("JSON-RPC HTTP transport disabled (pass --http-addr to enable)"),
+    }
+    match args.capnp_addr {
+        Some(addr) => info!("Cap'n Proto RPC address: {}", addr),
+        None => info!("Cap'n Proto RPC transport disabled (pass --capnp-addr to enable)"),
+    }
 
     // Initialize Metasploit Framework
     info!("Initializing Metasploit Framework...");
-    let msf_root = args
-        .msf_root
-        .as_ref()
-        .and_then(|p| p.to_str())
-        .unwrap_or("/usr/share/metasploit-framework");
-    bridge::init_metasploit(msf_root)
+    let ruby_init_config = bridge::RubyInitConfig {
+        framework_path: args.msf_root.as_ref().and_then(|p| p.to_str()).map(|s| s.to_string()),
+        extra_load_paths: args
+            .msf_extra_load_paths
+            .iter()
+            .filter_map(|p| p.to_str())
+            .map(|s| s.to_string())
+            .collect(),
+        verbose: matches!(args.log_level.as_str(), "debug" | "trace"),
+    };
+    bridge::init_metasploit_with_config(&ruby_init_config)
         .context("Failed to initialize Metasploit Ruby environment")?;
 
     let framework = Framework::new(None).context("Failed to create MSF framework instance")?;
     info!("MSF Framework initialized: {}", framework.version()?);
 
-    // Create ring buffers for bidirectional IPC
-    info!("Creating IPC ring buffers...");
-    let request_buffer_name = format!("{}_req", args.shm_name);
-    let response_buffer_name = format!("{}_resp", args.shm_name);
-
-    let request_buffer = RingBuffer::create(&request_buffer_name, args.buffer_size)
-        .context("Failed to create request ring buffer")?;
-    let response_buffer = RingBuffer::create(&response_buffer_name, args.buffer_size)
-        .context("Failed to create response ring buffer")?;
-    info!("Ring buffers created successfully");
+    // Create the full-duplex IPC channel (separate ring buffer segments for
+    // client->daemon and daemon->client traffic)
+    info!("Creating IPC channel...");
+    let channel = IpcChannel::create(&args.shm_name, args.buffer_size)
+        .context("Failed to create IPC channel")?;
+    info!("IPC channel created successfully");
 
     // Setup signal handling
     let shutdown = Arc::new(AtomicBool::new(false));
@@ -1128,8 +2178,90 @@ async fn main() -> Result<()> {
         handle_signals(signals, shutdown_clone).await;
     });
 
+    // Start the metrics HTTP exporter, if enabled
+    let metrics = Metrics::new();
+    if let Some(metrics_addr) = args.metrics_addr {
+        let metrics_for_server = Arc::clone(&metrics);
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve_metrics(metrics_addr, metrics_for_server).await {
+                error!("Metrics server failed: {:#}", e);
+            }
+        });
+    }
+
+    // Open persisted state, if configured
+    let store = match &args.state_dir {
+        Some(dir) => {
+            info!("Opening persisted state at {}", dir.display());
+            Some(Arc::new(
+                ModuleStore::open(dir).context("Failed to open module state store")?,
+            ))
+        }
+        None => None,
+    };
+
+    // Load (or build and persist) the scanned module metadata cache, if configured
+    let mut module_cache = match &args.module_cache_dir {
+        Some(dir) => {
+            let cache_path = module_cache::default_cache_path(dir);
+            info!("Loading module cache from {}...", cache_path.display());
+            let cache = ModuleCache::load_or_build(&framework, &cache_path)
+                .context("Failed to load/build module cache")?;
+            if !cache.skipped().is_empty() {
+                warn!("Module cache: {} module(s) skipped during scan", cache.skipped().len());
+            }
+            info!("Module cache ready: {} module(s) cached", cache.len());
+            Some(cache)
+        }
+        None => None,
+    };
+
+    // Scan external (non-Ruby) modules and fold them into the module cache,
+    // if both are configured - a cache-less daemon has nowhere to put them.
+    if let Some(dir) = &args.external_module_dir {
+        match (&mut module_cache, discover_external_modules(dir)) {
+            (Some(cache), Ok(modules)) => {
+                info!("Scanning {} external module(s) from {}...", modules.len(), dir.display());
+                cache.merge_external(&modules);
+            }
+            (None, _) => {
+                warn!("--external-module-dir was passed without --module-cache-dir; external modules have nowhere to be merged into, skipping");
+            }
+            (_, Err(e)) => {
+                warn!("Failed to scan external module directory {}: {}", dir.display(), e);
+            }
+        }
+    }
+
+    let (http_bridge, http_calls) = HttpBridge::new();
+    if let Some(http_addr) = args.http_addr {
+        tokio::spawn(async move {
+            if let Err(e) = http_transport::serve(http_addr, http_bridge).await {
+                error!("JSON-RPC HTTP server error: {}", e);
+            }
+        });
+    }
+
+    let (capnp_bridge, capnp_calls) = CapnpBridge::new();
+    if let Some(capnp_addr) = args.capnp_addr {
+        capnp_server::spawn_on_own_thread(capnp_addr, capnp_bridge);
+    }
+
     // Create and run daemon
-    let daemon = Daemon::new(framework, request_buffer, response_buffer, shutdown);
+    let daemon = Daemon::new(
+        framework,
+        channel,
+        shutdown,
+        metrics,
+        store,
+        module_cache,
+        args.session_reap_interval_secs.map(Duration::from_secs),
+        http_calls,
+        capnp_calls,
+    );
+    daemon
+        .restore_from_store()
+        .context("Failed to restore persisted module state")?;
     let result = daemon.run().await;
 
     // Cleanup
@@ -0,0 +1,279 @@
+/// Prometheus-style metrics registry for the daemon
+///
+/// Tracks per-method call counters and dispatch latency histograms, plus
+/// gauges for live module count and ring buffer occupancy. Rendered in
+/// Prometheus text exposition format and served over a small HTTP listener
+/// so operators can scrape the daemon instead of grepping logs.
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tracing::{debug, error, info};
+
+/// Histogram bucket upper bounds for dispatch latency, in milliseconds.
+/// The final (implicit) bucket is `+Inf`.
+const LATENCY_BUCKETS_MS: &[f64] = &[
+    1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 5000.0,
+];
+
+/// Per-method counters and latency histogram
+struct MethodStats {
+    success_total: AtomicU64,
+    error_total: AtomicU64,
+    // One bucket count per entry in LATENCY_BUCKETS_MS, plus a final +Inf bucket
+    bucket_counts: Vec<AtomicU64>,
+    sum_ms: Mutex<f64>,
+    count: AtomicU64,
+}
+
+impl MethodStats {
+    fn new() -> Self {
+        Self {
+            success_total: AtomicU64::new(0),
+            error_total: AtomicU64::new(0),
+            bucket_counts: (0..=LATENCY_BUCKETS_MS.len())
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+            sum_ms: Mutex::new(0.0),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    fn observe(&self, dispatch_ms: f64) {
+        // Count the observation once, in the first bucket whose bound holds
+        // it, or in the final +Inf bucket if none does; `render` and
+        // `quantile` accumulate these into cumulative counts.
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| dispatch_ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.bucket_counts[bucket].fetch_add(1, Ordering::Relaxed);
+        *self.sum_ms.lock() += dispatch_ms;
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Approximate the given quantile (e.g. 0.95) as the upper bound of the
+    /// first bucket whose cumulative count reaches it. Bucket-granularity
+    /// only, same tradeoff as Prometheus's own `histogram_quantile`.
+    fn quantile(&self, q: f64) -> Option<f64> {
+        let total = self.count.load(Ordering::Relaxed);
+        if total == 0 {
+            return None;
+        }
+        let target = (total as f64 * q).ceil() as u64;
+        let mut cumulative = 0u64;
+        for (i, bound) in LATENCY_BUCKETS_MS.iter().enumerate() {
+            cumulative += self.bucket_counts[i].load(Ordering::Relaxed);
+            if cumulative >= target {
+                return Some(*bound);
+            }
+        }
+        Some(f64::INFINITY)
+    }
+}
+
+/// Daemon-wide metrics registry
+pub struct Metrics {
+    methods: Mutex<HashMap<String, Arc<MethodStats>>>,
+    modules_gauge: AtomicU64,
+    request_buffer_util: Mutex<f64>,
+    response_buffer_util: Mutex<f64>,
+    stale_sessions_total: AtomicU64,
+}
+
+impl Metrics {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            methods: Mutex::new(HashMap::new()),
+            modules_gauge: AtomicU64::new(0),
+            request_buffer_util: Mutex::new(0.0),
+            response_buffer_util: Mutex::new(0.0),
+            stale_sessions_total: AtomicU64::new(0),
+        })
+    }
+
+    fn stats_for(&self, method: &str) -> Arc<MethodStats> {
+        let mut methods = self.methods.lock();
+        methods
+            .entry(method.to_string())
+            .or_insert_with(|| Arc::new(MethodStats::new()))
+            .clone()
+    }
+
+    /// Record a successful dispatch, timed in milliseconds
+    pub fn record_success(&self, method: &str, dispatch_ms: f64) {
+        let stats = self.stats_for(method);
+        stats.success_total.fetch_add(1, Ordering::Relaxed);
+        stats.observe(dispatch_ms);
+    }
+
+    /// Record a failed dispatch, timed in milliseconds
+    pub fn record_error(&self, method: &str, dispatch_ms: f64) {
+        let stats = self.stats_for(method);
+        stats.error_total.fetch_add(1, Ordering::Relaxed);
+        stats.observe(dispatch_ms);
+    }
+
+    /// Update the live module-instance gauge
+    pub fn set_modules_gauge(&self, count: u64) {
+        self.modules_gauge.store(count, Ordering::Relaxed);
+    }
+
+    /// Update the ring buffer occupancy gauges (0.0 - 1.0)
+    pub fn set_ring_utilization(&self, request_util: f64, response_util: f64) {
+        *self.request_buffer_util.lock() = request_util;
+        *self.response_buffer_util.lock() = response_util;
+    }
+
+    /// Add to the running total of sessions pruned by the background reaper
+    pub fn add_stale_sessions(&self, count: u64) {
+        self.stale_sessions_total.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Build a JSON snapshot of every metric, for the `"stats"` RPC method.
+    /// Carries the same data as `render`'s Prometheus text, but with
+    /// bucket-approximated p50/p95/p99 latencies instead of raw histograms.
+    pub fn snapshot(&self) -> serde_json::Value {
+        let methods = self.methods.lock();
+        let method_stats: serde_json::Map<String, serde_json::Value> = methods
+            .iter()
+            .map(|(method, stats)| {
+                let count = stats.count.load(Ordering::Relaxed);
+                (
+                    method.clone(),
+                    serde_json::json!({
+                        "success_total": stats.success_total.load(Ordering::Relaxed),
+                        "error_total": stats.error_total.load(Ordering::Relaxed),
+                        "count": count,
+                        "sum_ms": *stats.sum_ms.lock(),
+                        "p50_ms": stats.quantile(0.50),
+                        "p95_ms": stats.quantile(0.95),
+                        "p99_ms": stats.quantile(0.99),
+                    }),
+                )
+            })
+            .collect();
+        drop(methods);
+
+        serde_json::json!({
+            "methods": method_stats,
+            "modules_live": self.modules_gauge.load(Ordering::Relaxed),
+            "ring_buffer_utilization": {
+                "request": *self.request_buffer_util.lock(),
+                "response": *self.response_buffer_util.lock(),
+            },
+            "stale_sessions_total": self.stale_sessions_total.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Render all metrics in Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP assassinate_calls_total Total RPC calls by method and outcome\n");
+        out.push_str("# TYPE assassinate_calls_total counter\n");
+        out.push_str("# HELP assassinate_dispatch_ms Dispatch latency in milliseconds by method\n");
+        out.push_str("# TYPE assassinate_dispatch_ms histogram\n");
+
+        let methods = self.methods.lock();
+        for (method, stats) in methods.iter() {
+            let success = stats.success_total.load(Ordering::Relaxed);
+            let errors = stats.error_total.load(Ordering::Relaxed);
+            out.push_str(&format!(
+                "assassinate_calls_total{{method=\"{}\",outcome=\"success\"}} {}\n",
+                method, success
+            ));
+            out.push_str(&format!(
+                "assassinate_calls_total{{method=\"{}\",outcome=\"error\"}} {}\n",
+                method, errors
+            ));
+
+            let mut cumulative = 0u64;
+            for (i, bound) in LATENCY_BUCKETS_MS.iter().enumerate() {
+                cumulative += stats.bucket_counts[i].load(Ordering::Relaxed);
+                out.push_str(&format!(
+                    "assassinate_dispatch_ms_bucket{{method=\"{}\",le=\"{}\"}} {}\n",
+                    method, bound, cumulative
+                ));
+            }
+            cumulative += stats.bucket_counts[LATENCY_BUCKETS_MS.len()].load(Ordering::Relaxed);
+            out.push_str(&format!(
+                "assassinate_dispatch_ms_bucket{{method=\"{}\",le=\"+Inf\"}} {}\n",
+                method, cumulative
+            ));
+            out.push_str(&format!(
+                "assassinate_dispatch_ms_sum{{method=\"{}\"}} {}\n",
+                method,
+                *stats.sum_ms.lock()
+            ));
+            out.push_str(&format!(
+                "assassinate_dispatch_ms_count{{method=\"{}\"}} {}\n",
+                method,
+                stats.count.load(Ordering::Relaxed)
+            ));
+        }
+        drop(methods);
+
+        out.push_str("# HELP assassinate_modules_live Number of live module instances\n");
+        out.push_str("# TYPE assassinate_modules_live gauge\n");
+        out.push_str(&format!(
+            "assassinate_modules_live {}\n",
+            self.modules_gauge.load(Ordering::Relaxed)
+        ));
+
+        out.push_str("# HELP assassinate_ring_buffer_utilization Ring buffer occupancy (0.0-1.0)\n");
+        out.push_str("# TYPE assassinate_ring_buffer_utilization gauge\n");
+        out.push_str(&format!(
+            "assassinate_ring_buffer_utilization{{buffer=\"request\"}} {}\n",
+            *self.request_buffer_util.lock()
+        ));
+        out.push_str(&format!(
+            "assassinate_ring_buffer_utilization{{buffer=\"response\"}} {}\n",
+            *self.response_buffer_util.lock()
+        ));
+
+        out.push_str("# HELP assassinate_stale_sessions_total Dead sessions pruned by the background reaper\n");
+        out.push_str("# TYPE assassinate_stale_sessions_total counter\n");
+        out.push_str(&format!(
+            "assassinate_stale_sessions_total {}\n",
+            self.stale_sessions_total.load(Ordering::Relaxed)
+        ));
+
+        out
+    }
+}
+
+/// Serve `/metrics` in Prometheus text exposition format over plain HTTP
+pub async fn serve_metrics(addr: SocketAddr, metrics: Arc<Metrics>) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!("Metrics server listening on {}", addr);
+
+    loop {
+        let (mut stream, peer) = listener.accept().await?;
+        let metrics = metrics.clone();
+
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1024];
+            // We don't care about the request beyond draining it - this is a
+            // single-endpoint scrape target, not a general HTTP server.
+            if let Err(e) = stream.read(&mut buf).await {
+                debug!("Failed to read metrics request from {}: {}", peer, e);
+                return;
+            }
+
+            let body = metrics.render();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+
+            if let Err(e) = stream.write_all(response.as_bytes()).await {
+                error!("Failed to write metrics response to {}: {}", peer, e);
+            }
+        });
+    }
+}
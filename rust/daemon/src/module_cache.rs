@@ -0,0 +1,236 @@
+/// Cached module metadata, built once from the live framework instead of
+/// re-walking Ruby on every lookup
+///
+/// Naively iterating `framework.list_modules(type)` and instantiating each
+/// one forces MSF to `require` every module file up front - a module with a
+/// missing gem dependency (a known MSF failure mode) raises a Ruby
+/// `LoadError` right there, and doing that for every module at every lookup
+/// means one broken module spams load errors on every `search`. `ModuleCache`
+/// walks the module set exactly once per `build()`, records what it could
+/// read as a plain Rust struct, and records what it couldn't as a
+/// `SkippedModule` instead of propagating the failure - so one broken module
+/// never takes down browsing the rest of the catalog.
+use crate::external_module::ExternalModule;
+use bridge::{AssassinateError, Framework};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleMetadata {
+    pub fullname: String,
+    pub module_type: String,
+    pub rank: String,
+    pub disclosure_date: Option<String>,
+    pub description: String,
+    pub platform: Vec<String>,
+    pub arch: Vec<String>,
+    pub references: Vec<String>,
+}
+
+/// A module that couldn't be read into `ModuleMetadata` - most commonly a
+/// Ruby `LoadError` from a missing gem dependency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedModule {
+    pub fullname: String,
+    pub reason: String,
+}
+
+/// Filters for `ModuleCache::search`; a `None` field matches anything
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilters {
+    pub module_type: Option<String>,
+    pub platform: Option<String>,
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheFile {
+    entries: Vec<ModuleMetadata>,
+    skipped: Vec<SkippedModule>,
+}
+
+pub struct ModuleCache {
+    entries: HashMap<String, ModuleMetadata>,
+    skipped: Vec<SkippedModule>,
+}
+
+const SCANNED_MODULE_TYPES: &[&str] = &["exploit", "auxiliary", "post", "payload", "encoder", "nop"];
+
+impl ModuleCache {
+    /// Walk every module type in `SCANNED_MODULE_TYPES`, extracting metadata
+    /// for each ref name the framework reports. A module that fails to
+    /// instantiate (e.g. a missing dependency raising `LoadError` in Ruby) is
+    /// recorded in `skipped` instead of aborting the scan.
+    pub fn build(framework: &Framework) -> bridge::Result<Self> {
+        let mut entries = HashMap::new();
+        let mut skipped = Vec::new();
+
+        for &module_type in SCANNED_MODULE_TYPES {
+            let refnames = match framework.list_modules(module_type) {
+                Ok(names) => names,
+                Err(e) => {
+                    skipped.push(SkippedModule {
+                        fullname: format!("<{}:*>", module_type),
+                        reason: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            for fullname in refnames {
+                match extract_metadata(framework, &fullname, module_type) {
+                    Ok(metadata) => {
+                        entries.insert(fullname, metadata);
+                    }
+                    Err(e) => skipped.push(SkippedModule {
+                        fullname,
+                        reason: e.to_string(),
+                    }),
+                }
+            }
+        }
+
+        Ok(Self { entries, skipped })
+    }
+
+    /// Load a previously-saved cache from `path`, if one exists
+    pub fn load(path: &Path) -> bridge::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(path)
+            .map_err(|e| AssassinateError::ConfigError(format!("failed to read module cache at {}: {}", path.display(), e)))?;
+        let file: CacheFile = serde_json::from_str(&contents)
+            .map_err(|e| AssassinateError::ConfigError(format!("failed to parse module cache at {}: {}", path.display(), e)))?;
+
+        let entries = file.entries.into_iter().map(|m| (m.fullname.clone(), m)).collect();
+        Ok(Some(Self {
+            entries,
+            skipped: file.skipped,
+        }))
+    }
+
+    /// Serialize this cache to `path`, creating parent directories as needed
+    pub fn save(&self, path: &Path) -> bridge::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| AssassinateError::ConfigError(format!("failed to create {}: {}", parent.display(), e)))?;
+        }
+        let file = CacheFile {
+            entries: self.entries.values().cloned().collect(),
+            skipped: self.skipped.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| AssassinateError::ConfigError(format!("failed to serialize module cache: {}", e)))?;
+        fs::write(path, json)
+            .map_err(|e| AssassinateError::ConfigError(format!("failed to write {}: {}", path.display(), e)))?;
+        Ok(())
+    }
+
+    /// Load the cache at `path` if present, otherwise build it from
+    /// `framework` and save the result for next time.
+    pub fn load_or_build(framework: &Framework, path: &Path) -> bridge::Result<Self> {
+        if let Some(cache) = Self::load(path)? {
+            return Ok(cache);
+        }
+        let cache = Self::build(framework)?;
+        cache.save(path)?;
+        Ok(cache)
+    }
+
+    /// Scan the given external (non-Ruby) modules and merge their metadata
+    /// in the same way `build` does for native ones, so a `search`/
+    /// `module_info` caller can't tell whether a hit backs onto Ruby or a
+    /// subprocess. A module that fails to describe itself (missing
+    /// interpreter or dependency) is recorded in `skipped`, same as a
+    /// native module that fails to load.
+    pub fn merge_external(&mut self, modules: &[ExternalModule]) {
+        for module in modules {
+            let fallback_name = module.module_path.display().to_string();
+            match module.describe() {
+                Ok(desc) => {
+                    self.entries.insert(
+                        desc.fullname.clone(),
+                        ModuleMetadata {
+                            fullname: desc.fullname,
+                            module_type: desc.module_type,
+                            rank: "external".to_string(),
+                            disclosure_date: None,
+                            description: desc.description,
+                            platform: vec![],
+                            arch: vec![],
+                            references: vec![],
+                        },
+                    );
+                }
+                Err(e) => self.skipped.push(SkippedModule {
+                    fullname: fallback_name,
+                    reason: e.to_string(),
+                }),
+            }
+        }
+    }
+
+    pub fn module_info(&self, fullname: &str) -> Option<&ModuleMetadata> {
+        self.entries.get(fullname)
+    }
+
+    pub fn search(&self, filters: &SearchFilters) -> Vec<&ModuleMetadata> {
+        self.entries
+            .values()
+            .filter(|m| {
+                filters.module_type.as_deref().map_or(true, |t| m.module_type == t)
+            })
+            .filter(|m| {
+                filters
+                    .platform
+                    .as_deref()
+                    .map_or(true, |p| m.platform.iter().any(|mp| mp.eq_ignore_ascii_case(p)))
+            })
+            .filter(|m| {
+                filters.text.as_deref().map_or(true, |needle| {
+                    let needle = needle.to_ascii_lowercase();
+                    m.fullname.to_ascii_lowercase().contains(&needle)
+                        || m.description.to_ascii_lowercase().contains(&needle)
+                })
+            })
+            .collect()
+    }
+
+    /// Modules that couldn't be read into the cache, most often because of a
+    /// missing dependency - surfaced so a caller can report them rather than
+    /// the cache silently pretending they don't exist.
+    pub fn skipped(&self) -> &[SkippedModule] {
+        &self.skipped
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn extract_metadata(framework: &Framework, fullname: &str, module_type: &str) -> bridge::Result<ModuleMetadata> {
+    let module = framework.create_module(fullname)?;
+
+    Ok(ModuleMetadata {
+        fullname: fullname.to_string(),
+        module_type: module_type.to_string(),
+        rank: module.rank()?,
+        disclosure_date: module.disclosure_date()?,
+        description: module.description()?,
+        platform: module.platform()?,
+        arch: module.arch()?,
+        references: module.references()?,
+    })
+}
+
+/// Where the on-disk cache lives by default: `{config_dir}/module_cache.json`
+pub fn default_cache_path(config_dir: &Path) -> PathBuf {
+    config_dir.join("module_cache.json")
+}
@@ -0,0 +1,120 @@
+/// Persistent storage for module instances and datastore state
+///
+/// Backed by an embedded `sled` database so module creation parameters and
+/// datastore values survive daemon restarts instead of evaporating with the
+/// in-memory `modules` map. Keys are composite and prefixed:
+///   `module:{id}`            -> module creation path
+///   `module:{id}:ds:{key}`   -> a single datastore entry
+///   `next_module_id`         -> the module ID counter, big-endian u64
+use sled::Db;
+use std::path::Path;
+
+pub struct ModuleStore {
+    db: Db,
+}
+
+impl ModuleStore {
+    /// Open (or create) the on-disk store at `state_dir`
+    pub fn open(state_dir: &Path) -> sled::Result<Self> {
+        let db = sled::open(state_dir)?;
+        Ok(Self { db })
+    }
+
+    /// Persist a newly created module's creation path
+    pub fn save_module(&self, module_id: &str, module_path: &str) -> sled::Result<()> {
+        self.db
+            .insert(format!("module:{}", module_id), module_path.as_bytes())?;
+        Ok(())
+    }
+
+    /// Persist a single datastore key for a module
+    pub fn save_datastore_key(&self, module_id: &str, key: &str, value: &str) -> sled::Result<()> {
+        self.db.insert(
+            format!("module:{}:ds:{}", module_id, key),
+            value.as_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// Remove a single persisted datastore key
+    pub fn delete_datastore_key(&self, module_id: &str, key: &str) -> sled::Result<()> {
+        self.db.remove(format!("module:{}:ds:{}", module_id, key))?;
+        Ok(())
+    }
+
+    /// Remove a module's creation entry and all of its datastore entries
+    pub fn delete_module(&self, module_id: &str) -> sled::Result<()> {
+        self.db.remove(format!("module:{}", module_id))?;
+        let ds_prefix = format!("module:{}:ds:", module_id);
+        for entry in self.db.scan_prefix(&ds_prefix) {
+            let (key, _) = entry?;
+            self.db.remove(key)?;
+        }
+        Ok(())
+    }
+
+    /// Persist the next-module-ID counter
+    pub fn save_next_module_id(&self, id: u64) -> sled::Result<()> {
+        self.db.insert("next_module_id", &id.to_be_bytes())?;
+        Ok(())
+    }
+
+    /// Load the next-module-ID counter, if one was ever persisted
+    pub fn load_next_module_id(&self) -> sled::Result<Option<u64>> {
+        Ok(self.db.get("next_module_id")?.map(|ivec| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&ivec);
+            u64::from_be_bytes(bytes)
+        }))
+    }
+
+    /// Enumerate persisted module creation entries as `(module_id, module_path)`
+    pub fn load_modules(&self) -> sled::Result<Vec<(String, String)>> {
+        let mut out = Vec::new();
+        for entry in self.db.scan_prefix("module:") {
+            let (key, value) = entry?;
+            let key_str = String::from_utf8_lossy(&key);
+            // A creation entry is exactly "module:{id}" - datastore entries have a
+            // third ":ds:{key}" segment and are skipped here.
+            let parts: Vec<&str> = key_str.splitn(3, ':').collect();
+            if parts.len() == 2 {
+                out.push((parts[1].to_string(), String::from_utf8_lossy(&value).to_string()));
+            }
+        }
+        Ok(out)
+    }
+
+    /// Enumerate persisted datastore entries for a module as `(key, value)`
+    pub fn load_datastore(&self, module_id: &str) -> sled::Result<Vec<(String, String)>> {
+        let prefix = format!("module:{}:ds:", module_id);
+        let mut out = Vec::new();
+        for entry in self.db.scan_prefix(&prefix) {
+            let (key, value) = entry?;
+            let key_str = String::from_utf8_lossy(&key);
+            let ds_key = key_str.trim_start_matches(prefix.as_str()).to_string();
+            out.push((ds_key, String::from_utf8_lossy(&value).to_string()));
+        }
+        Ok(out)
+    }
+
+    /// Wipe and re-flush the entire store from a live in-memory snapshot.
+    ///
+    /// Used by the `state_rebuild` dispatch method to recover a consistent
+    /// on-disk state if the store and the in-memory session have drifted apart.
+    pub fn rebuild(
+        &self,
+        next_module_id: u64,
+        modules: &[(String, String, Vec<(String, String)>)],
+    ) -> sled::Result<()> {
+        self.db.clear()?;
+        for (module_id, module_path, datastore) in modules {
+            self.save_module(module_id, module_path)?;
+            for (key, value) in datastore {
+                self.save_datastore_key(module_id, key, value)?;
+            }
+        }
+        self.save_next_module_id(next_module_id)?;
+        self.db.flush()?;
+        Ok(())
+    }
+}
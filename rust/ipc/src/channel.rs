@@ -0,0 +1,86 @@
+/// A full-duplex IPC channel: two independent `RingBuffer` segments, one per
+/// direction, instead of one segment doing double duty for both.
+///
+/// `RingBuffer` is documented as a single-producer/single-consumer queue;
+/// sharing one segment for both directions would mean both ends sometimes
+/// write to it and sometimes read from it, which isn't SPSC at all - it's
+/// only safe today because the daemon and its peer each stick to a fixed
+/// role on two separately-created segments. `IpcChannel` makes that
+/// two-segment pairing the actual type instead of two same-named local
+/// variables a caller has to remember to keep in sync.
+use crate::error::Result;
+use crate::ring_buffer::RingBuffer;
+use std::time::Duration;
+
+pub struct IpcChannel {
+    /// The segment this side reads from
+    inbound: RingBuffer,
+    /// The segment this side writes to
+    outbound: RingBuffer,
+}
+
+impl IpcChannel {
+    /// Create both segments of a channel as its server side.
+    ///
+    /// `name` is a shared prefix; the two directions get distinct
+    /// shared-memory segment names (`{name}_c2s`, `{name}_s2c`) so they
+    /// can't collide with each other or with a single-buffer `RingBuffer`
+    /// opened directly under `name`.
+    pub fn create(name: &str, capacity: usize) -> Result<Self> {
+        Ok(Self {
+            inbound: RingBuffer::create(&format!("{}_c2s", name), capacity)?,
+            outbound: RingBuffer::create(&format!("{}_s2c", name), capacity)?,
+        })
+    }
+
+    /// Open both segments of a channel as its client side - the mirror
+    /// image of `create`: what the server created as its inbound (`_c2s`)
+    /// segment is this side's outbound one, and vice versa. Each
+    /// `RingBuffer::open` call independently validates the handshake header
+    /// the server's `create` stamped, so a capacity or header-version
+    /// mismatch on either segment fails here rather than silently
+    /// misinterpreting the other side's frames.
+    pub fn open(name: &str, capacity: usize) -> Result<Self> {
+        Ok(Self {
+            inbound: RingBuffer::open(&format!("{}_s2c", name), capacity)?,
+            outbound: RingBuffer::open(&format!("{}_c2s", name), capacity)?,
+        })
+    }
+
+    /// Try to read a message from the inbound segment (non-blocking, zero-copy)
+    pub fn try_read(&self) -> Result<&[u8]> {
+        self.inbound.try_read()
+    }
+
+    /// Block until a message is available on the inbound segment or `timeout` elapses
+    pub fn read_blocking(&self, timeout: Duration) -> Result<&[u8]> {
+        self.inbound.read_blocking(timeout)
+    }
+
+    /// Drain every message currently available on the inbound segment
+    pub fn try_read_batch<'a>(&'a self, out: &mut Vec<&'a [u8]>) -> usize {
+        self.inbound.try_read_batch(out)
+    }
+
+    /// Try to write a message to the outbound segment (non-blocking)
+    pub fn try_write(&self, data: &[u8]) -> Result<()> {
+        self.outbound.try_write(data)
+    }
+
+    /// Inbound segment utilization (0.0 = empty, 1.0 = full)
+    pub fn inbound_utilization(&self) -> f64 {
+        self.inbound.utilization()
+    }
+
+    /// Outbound segment utilization (0.0 = empty, 1.0 = full)
+    pub fn outbound_utilization(&self) -> f64 {
+        self.outbound.utilization()
+    }
+
+    /// Feature bits negotiated for the inbound segment - both segments are
+    /// always created together with the same feature set, so either side
+    /// reflects the channel as a whole.
+    pub fn negotiated_features(&self) -> u64 {
+        self.inbound.negotiated_features()
+    }
+}
@@ -32,6 +32,24 @@ pub enum IpcError {
 
     #[error("Cap'n Proto error: {0}")]
     CapnProto(#[from] capnp::Error),
+
+    #[error("Handshake failed: {0}")]
+    HandshakeFailed(String),
+
+    #[error("Peer public key is not on the allow-list")]
+    UntrustedPeer,
+
+    #[error("Frame rejected: no authenticated session established")]
+    Unauthenticated,
+
+    #[error("Replayed or out-of-order nonce: expected > {expected}, got {got}")]
+    ReplayDetected { expected: u64, got: u64 },
+
+    #[error("Unsupported protocol version: {0}")]
+    UnsupportedVersion(String),
+
+    #[error("Ring buffer header version mismatch: expected {expected}, found {found}")]
+    VersionMismatch { expected: u16, found: u16 },
 }
 
 pub type Result<T> = std::result::Result<T, IpcError>;
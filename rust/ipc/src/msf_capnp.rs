@@ -0,0 +1,7 @@
+/// Generated Cap'n Proto bindings for `schema/msf.capnp`, compiled by `build.rs`
+///
+/// Re-exported from here so `assassinate_daemon`'s capnp-rpc server has a
+/// single place to import the generated `Framework`/`ModuleManager`/`Module`/
+/// `DataStore`/`SessionManager`/`Jobs`/`Db` client and server traits from,
+/// instead of depending on `OUT_DIR` layout directly.
+include!(concat!(env!("OUT_DIR"), "/msf_capnp.rs"));
@@ -1,94 +1,415 @@
 /// Protocol layer for MessagePack message handling
 ///
-/// Using MessagePack for high-performance binary serialization.
-/// ~5-10x faster than JSON with smaller message sizes.
+/// MessagePack remains the default for its size and speed (~5-10x faster
+/// than JSON with smaller message sizes), but the wire format is no longer
+/// fixed at compile time: `Format` is stamped into every frame header so a
+/// reader can pick the matching codec without out-of-band configuration.
+/// JSON is invaluable for debugging/interop over a packet capture; bincode
+/// trades that readability for an even tighter encoding of fixed-shape
+/// messages. JSON and bincode support are gated behind their own cargo
+/// features (`format-json`, `format-bincode`) so a build that never uses
+/// them doesn't pay for the extra dependency.
 
 use crate::error::{IpcError, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{BufRead, Write};
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Request {
-    method: String,
-    args: Vec<serde_json::Value>,
+/// Wire serialization backend, selectable at runtime and stamped into the
+/// frame header so a reader can dispatch to the matching codec without
+/// needing to be told out of band which one the sender used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    MessagePack = 0,
+    Json = 1,
+    Bincode = 2,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Response {
-    result: serde_json::Value,
+impl Format {
+    fn from_u8(tag: u8) -> Result<Self> {
+        match tag {
+            0 => Ok(Format::MessagePack),
+            1 => Ok(Format::Json),
+            2 => Ok(Format::Bincode),
+            other => Err(IpcError::Deserialization(format!(
+                "Unknown format tag: {}",
+                other
+            ))),
+        }
+    }
+
+    fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>> {
+        match self {
+            Format::MessagePack => {
+                rmp_serde::to_vec_named(value).map_err(|e| IpcError::Serialization(e.to_string()))
+            }
+            #[cfg(feature = "format-json")]
+            Format::Json => {
+                serde_json::to_vec(value).map_err(|e| IpcError::Serialization(e.to_string()))
+            }
+            #[cfg(not(feature = "format-json"))]
+            Format::Json => Err(IpcError::Serialization(
+                "JSON format not compiled in; enable the `format-json` feature".to_string(),
+            )),
+            #[cfg(feature = "format-bincode")]
+            Format::Bincode => {
+                bincode::serialize(value).map_err(|e| IpcError::Serialization(e.to_string()))
+            }
+            #[cfg(not(feature = "format-bincode"))]
+            Format::Bincode => Err(IpcError::Serialization(
+                "Bincode format not compiled in; enable the `format-bincode` feature".to_string(),
+            )),
+        }
+    }
+
+    fn decode<T: DeserializeOwned>(self, data: &[u8]) -> Result<T> {
+        match self {
+            Format::MessagePack => {
+                rmp_serde::from_slice(data).map_err(|e| IpcError::Deserialization(e.to_string()))
+            }
+            #[cfg(feature = "format-json")]
+            Format::Json => {
+                serde_json::from_slice(data).map_err(|e| IpcError::Deserialization(e.to_string()))
+            }
+            #[cfg(not(feature = "format-json"))]
+            Format::Json => Err(IpcError::Deserialization(
+                "JSON format not compiled in; enable the `format-json` feature".to_string(),
+            )),
+            #[cfg(feature = "format-bincode")]
+            Format::Bincode => {
+                bincode::deserialize(data).map_err(|e| IpcError::Deserialization(e.to_string()))
+            }
+            #[cfg(not(feature = "format-bincode"))]
+            Format::Bincode => Err(IpcError::Deserialization(
+                "Bincode format not compiled in; enable the `format-bincode` feature".to_string(),
+            )),
+        }
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Error {
-    code: String,
-    message: String,
+/// A correlation id that may be either numeric or an opaque string, so the
+/// crate can round-trip peers (including MSF's own JSON-RPC surface) that
+/// use string ids without a lossy numeric conversion.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(untagged)]
+pub enum CallId {
+    U64(u64),
+    String(String),
+}
+
+impl From<u64> for CallId {
+    fn from(id: u64) -> Self {
+        CallId::U64(id)
+    }
+}
+
+impl From<String> for CallId {
+    fn from(id: String) -> Self {
+        CallId::String(id)
+    }
+}
+
+impl From<&str> for CallId {
+    fn from(id: &str) -> Self {
+        CallId::String(id.to_string())
+    }
+}
+
+impl fmt::Display for CallId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallId::U64(id) => write!(f, "{}", id),
+            CallId::String(id) => write!(f, "{}", id),
+        }
+    }
+}
+
+/// An argument or result value carried by a `Request`/`Response`.
+///
+/// Plain JSON covers everything the daemon's dispatch table already speaks,
+/// but a loot file, screenshot, or raw shellcode buffer has no business
+/// round-tripping through a base64 string just to fit in a `serde_json::Value`
+/// - that's a ~33% size penalty paid on every such call. `Blob` carries bytes
+/// via `serde_bytes` instead, so MessagePack encodes them with its native
+/// `bin` type (and JSON/bincode fall back to their own byte representations)
+/// end to end on the wire.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", content = "value")]
+pub enum Payload {
+    Json(serde_json::Value),
+    Blob(#[serde(with = "serde_bytes")] Vec<u8>),
+}
+
+impl Payload {
+    /// Collapse to a `serde_json::Value` for callers (like the compiled-in
+    /// dispatch table) that only understand JSON. A `Blob` has no lossless
+    /// JSON representation, so it's base64-encoded here - the same overhead
+    /// this type exists to avoid on the wire, just pushed to the one place
+    /// that still requires JSON.
+    pub fn into_json(self) -> serde_json::Value {
+        match self {
+            Payload::Json(value) => value,
+            Payload::Blob(bytes) => serde_json::Value::String(BASE64.encode(bytes)),
+        }
+    }
+}
+
+impl From<serde_json::Value> for Payload {
+    fn from(value: serde_json::Value) -> Self {
+        Payload::Json(value)
+    }
+}
+
+impl From<Vec<u8>> for Payload {
+    fn from(bytes: Vec<u8>) -> Self {
+        Payload::Blob(bytes)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub args: Vec<Payload>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Response {
+    pub result: Payload,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Error {
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub method: String,
+    pub params: Vec<serde_json::Value>,
+}
+
+/// Exactly one kind of payload a message can carry, internally tagged by
+/// `kind` on the wire. Unlike three parallel `Option` fields, a peer can no
+/// longer send a message with none (or more than one) set - deserialization
+/// itself enforces the invariant instead of leaving it to every call site.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind")]
+pub enum MessageBody {
+    Request(Request),
+    Response(Response),
+    Error(Error),
+    /// Server-pushed event with no correlating request (job completion,
+    /// session opened, console output)
+    Notification(Notification),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 struct Message {
-    call_id: u64,
-    request: Option<Request>,
-    response: Option<Response>,
-    error: Option<Error>,
+    call_id: CallId,
+    #[serde(flatten)]
+    body: MessageBody,
 }
 
-/// Serialize an MSF call to bytes using MessagePack
+/// Serialize an MSF call to bytes using `format`
 pub fn serialize_call(
-    call_id: u64,
+    call_id: impl Into<CallId>,
     method: &str,
-    args: Vec<serde_json::Value>,
+    args: Vec<Payload>,
+    format: Format,
 ) -> Result<Vec<u8>> {
     let message = Message {
-        call_id,
-        request: Some(Request {
+        call_id: call_id.into(),
+        body: MessageBody::Request(Request {
             method: method.to_string(),
             args,
         }),
-        response: None,
-        error: None,
     };
 
-    rmp_serde::to_vec_named(&message).map_err(|e| IpcError::Serialization(e.to_string()))
+    format.encode(&message)
 }
 
-/// Deserialize an MSF call from bytes
-pub fn deserialize_call(data: &[u8]) -> Result<(u64, String, Vec<serde_json::Value>)> {
-    let message: Message =
-        rmp_serde::from_slice(data).map_err(|e| IpcError::Deserialization(e.to_string()))?;
-
-    if let Some(request) = message.request {
-        Ok((message.call_id, request.method, request.args))
-    } else {
-        Err(IpcError::Deserialization(
+/// Deserialize an MSF call from bytes encoded in `format`
+pub fn deserialize_call(data: &[u8], format: Format) -> Result<(CallId, String, Vec<Payload>)> {
+    let (call_id, body) = decode(data, format)?;
+    match body {
+        MessageBody::Request(request) => Ok((call_id, request.method, request.args)),
+        _ => Err(IpcError::Deserialization(
             "Expected request message".to_string(),
-        ))
+        )),
     }
 }
 
 /// Serialize a response
-pub fn serialize_response(call_id: u64, result: serde_json::Value) -> Result<Vec<u8>> {
+pub fn serialize_response(
+    call_id: impl Into<CallId>,
+    result: Payload,
+    format: Format,
+) -> Result<Vec<u8>> {
     let message = Message {
-        call_id,
-        request: None,
-        response: Some(Response { result }),
-        error: None,
+        call_id: call_id.into(),
+        body: MessageBody::Response(Response { result }),
     };
 
-    rmp_serde::to_vec_named(&message).map_err(|e| IpcError::Serialization(e.to_string()))
+    format.encode(&message)
 }
 
 /// Serialize an error
-pub fn serialize_error(call_id: u64, code: &str, message: &str) -> Result<Vec<u8>> {
+pub fn serialize_error(
+    call_id: impl Into<CallId>,
+    code: &str,
+    message: &str,
+    format: Format,
+) -> Result<Vec<u8>> {
     let msg = Message {
-        call_id,
-        request: None,
-        response: None,
-        error: Some(Error {
+        call_id: call_id.into(),
+        body: MessageBody::Error(Error {
             code: code.to_string(),
             message: message.to_string(),
         }),
     };
 
-    rmp_serde::to_vec(&msg).map_err(|e| IpcError::Serialization(e.to_string()))
+    format.encode(&msg)
+}
+
+/// Serialize a fire-and-forget notification. Has no `call_id` to correlate
+/// against a request, so the field is left at 0 by convention.
+pub fn serialize_notification(
+    method: &str,
+    params: Vec<serde_json::Value>,
+    format: Format,
+) -> Result<Vec<u8>> {
+    let message = Message {
+        call_id: CallId::U64(0),
+        body: MessageBody::Notification(Notification {
+            method: method.to_string(),
+            params,
+        }),
+    };
+
+    format.encode(&message)
+}
+
+/// Decode any incoming frame - request, response, error, or notification -
+/// into its call id and exactly one `MessageBody` variant. The single entry
+/// point every caller should use instead of matching `Option` fields by hand.
+pub fn decode(data: &[u8], format: Format) -> Result<(CallId, MessageBody)> {
+    let message: Message = format.decode(data)?;
+    Ok((message.call_id, message.body))
+}
+
+/// Frame header layout:
+/// [length: u32 BE][type: u8][format: u8][version: u8 x3][call_id echo: u16 BE]
+const HEADER_SIZE: usize = 4 + 1 + 1 + 3 + 2;
+
+/// Protocol version this build speaks, as `[major, minor, patch]`. Stamped
+/// into every frame header so a peer on a different major version fails
+/// fast on the first frame instead of feeding a format it doesn't expect
+/// into MessagePack/JSON/bincode and getting back an opaque decode error.
+/// Bump the major component for wire-incompatible changes (new required
+/// header fields, a changed `MessageBody` shape); minor/patch are for
+/// backwards-compatible additions and aren't checked.
+pub const PROTOCOL_VERSION: [u8; 3] = [1, 0, 0];
+
+fn version_string(version: [u8; 3]) -> String {
+    format!("{}.{}.{}", version[0], version[1], version[2])
+}
+
+/// Default cap on a single frame's declared payload length, guarding reads
+/// on an untrusted stream against a hostile or garbled length prefix
+pub const DEFAULT_MAX_FRAME_SIZE: u32 = 64 * 1024 * 1024;
+
+/// Message kind carried in a frame's type tag, so a reader can route
+/// without fully decoding the MessagePack payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameType {
+    Request = 0,
+    Response = 1,
+    Error = 2,
+    /// Reserved for fire-and-forget async events; not yet emitted.
+    Notification = 3,
+}
+
+impl FrameType {
+    fn from_u8(tag: u8) -> Result<Self> {
+        match tag {
+            0 => Ok(FrameType::Request),
+            1 => Ok(FrameType::Response),
+            2 => Ok(FrameType::Error),
+            3 => Ok(FrameType::Notification),
+            other => Err(IpcError::Deserialization(format!(
+                "Unknown frame type tag: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Write a length-delimited frame: header followed by the encoded payload.
+///
+/// `call_id` is truncated to its low 16 bits purely as a routing hint for
+/// readers that want to dispatch before fully decoding the payload - the
+/// authoritative call id always lives in the decoded `Message`. `format`
+/// records which codec `payload` was encoded with, so a reader never has to
+/// guess or be told out of band. Every frame is stamped with `PROTOCOL_VERSION`.
+pub fn write_message<W: Write>(
+    writer: &mut W,
+    frame_type: FrameType,
+    format: Format,
+    call_id: u64,
+    payload: &[u8],
+) -> Result<()> {
+    let mut header = [0u8; HEADER_SIZE];
+    header[0..4].copy_from_slice(&(payload.len() as u32).to_be_bytes());
+    header[4] = frame_type as u8;
+    header[5] = format as u8;
+    header[6..9].copy_from_slice(&PROTOCOL_VERSION);
+    header[9..11].copy_from_slice(&(call_id as u16).to_be_bytes());
+
+    writer.write_all(&header)?;
+    writer.write_all(payload)?;
+    Ok(())
+}
+
+/// Read one length-delimited frame from a stream, validating its declared
+/// length against `max_len` before allocating a buffer for it, and its
+/// stamped version against `PROTOCOL_VERSION` before allocating a buffer for
+/// the payload - a major version mismatch fails fast with
+/// `IpcError::UnsupportedVersion` instead of handing a foreign wire format to
+/// the configured codec and getting back an opaque decode error.
+pub fn read_message<R: BufRead>(
+    reader: &mut R,
+    max_len: u32,
+) -> Result<(FrameType, Format, u16, Vec<u8>)> {
+    let mut header = [0u8; HEADER_SIZE];
+    reader.read_exact(&mut header)?;
+
+    let len = u32::from_be_bytes(header[0..4].try_into().unwrap());
+    if len > max_len {
+        return Err(IpcError::MessageTooLarge {
+            size: len as usize,
+            max: max_len as usize,
+        });
+    }
+    let frame_type = FrameType::from_u8(header[4])?;
+    let format = Format::from_u8(header[5])?;
+    let peer_version: [u8; 3] = header[6..9].try_into().unwrap();
+    if peer_version[0] != PROTOCOL_VERSION[0] {
+        return Err(IpcError::UnsupportedVersion(format!(
+            "peer speaks protocol v{}, we speak v{} (major version mismatch)",
+            version_string(peer_version),
+            version_string(PROTOCOL_VERSION)
+        )));
+    }
+    let call_id_echo = u16::from_be_bytes(header[9..11].try_into().unwrap());
+
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload)?;
+
+    Ok((frame_type, format, call_id_echo, payload))
 }
 
 #[cfg(test)]
@@ -97,15 +418,160 @@ mod tests {
 
     #[test]
     fn test_serialize_deserialize_call() {
-        let call_id = 42;
+        let call_id: u64 = 42;
         let method = "framework_version";
         let args = vec![];
 
-        let bytes = serialize_call(call_id, method, args.clone()).unwrap();
-        let (parsed_id, parsed_method, parsed_args) = deserialize_call(&bytes).unwrap();
+        let bytes = serialize_call(call_id, method, args.clone(), Format::MessagePack).unwrap();
+        let (parsed_id, parsed_method, parsed_args) =
+            deserialize_call(&bytes, Format::MessagePack).unwrap();
 
-        assert_eq!(parsed_id, call_id);
+        assert_eq!(parsed_id, CallId::from(call_id));
         assert_eq!(parsed_method, method);
         assert_eq!(parsed_args, args);
     }
+
+    #[test]
+    fn test_write_read_message_round_trip() {
+        let payload = serialize_call(7, "framework_version", vec![], Format::MessagePack).unwrap();
+        let mut buf = Vec::new();
+        write_message(&mut buf, FrameType::Request, Format::MessagePack, 7, &payload).unwrap();
+
+        let mut cursor = std::io::Cursor::new(buf);
+        let (frame_type, format, call_id_echo, read_payload) =
+            read_message(&mut cursor, DEFAULT_MAX_FRAME_SIZE).unwrap();
+
+        assert_eq!(frame_type, FrameType::Request);
+        assert_eq!(format, Format::MessagePack);
+        assert_eq!(call_id_echo, 7);
+        assert_eq!(read_payload, payload);
+    }
+
+    #[test]
+    fn test_serialize_notification_decodes_as_notification() {
+        let bytes = serialize_notification(
+            "session_opened",
+            vec![serde_json::json!(3)],
+            Format::MessagePack,
+        )
+        .unwrap();
+
+        match decode(&bytes, Format::MessagePack).unwrap() {
+            (_, MessageBody::Notification(notification)) => {
+                assert_eq!(notification.method, "session_opened");
+                assert_eq!(notification.params, vec![serde_json::json!(3)]);
+            }
+            other => panic!("expected Notification, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_distinguishes_request_and_response() {
+        let request_bytes =
+            serialize_call(1u64, "framework_version", vec![], Format::MessagePack).unwrap();
+        assert!(matches!(
+            decode(&request_bytes, Format::MessagePack).unwrap(),
+            (CallId::U64(1), MessageBody::Request(_))
+        ));
+
+        let response_bytes = serialize_response(
+            1u64,
+            Payload::Json(serde_json::json!("6.3")),
+            Format::MessagePack,
+        )
+        .unwrap();
+        assert!(matches!(
+            decode(&response_bytes, Format::MessagePack).unwrap(),
+            (CallId::U64(1), MessageBody::Response(_))
+        ));
+    }
+
+    #[test]
+    fn test_blob_payload_round_trips_and_beats_base64_json() {
+        let shellcode = vec![0x90u8; 256];
+
+        let bytes = serialize_call(
+            1u64,
+            "payload_generate",
+            vec![Payload::Blob(shellcode.clone())],
+            Format::MessagePack,
+        )
+        .unwrap();
+        let (_call_id, _method, args) = deserialize_call(&bytes, Format::MessagePack).unwrap();
+
+        assert_eq!(args, vec![Payload::Blob(shellcode.clone())]);
+
+        let base64_bytes = serialize_call(
+            1u64,
+            "payload_generate",
+            vec![Payload::Json(serde_json::Value::String(BASE64.encode(&shellcode)))],
+            Format::MessagePack,
+        )
+        .unwrap();
+        assert!(
+            bytes.len() < base64_bytes.len(),
+            "native blob encoding ({} bytes) should beat base64-in-JSON ({} bytes)",
+            bytes.len(),
+            base64_bytes.len()
+        );
+    }
+
+    #[test]
+    fn test_payload_into_json_base64_encodes_blobs() {
+        let json = Payload::Blob(vec![1, 2, 3]).into_json();
+        assert_eq!(json, serde_json::Value::String(BASE64.encode([1, 2, 3])));
+        assert_eq!(Payload::Json(serde_json::json!(5)).into_json(), serde_json::json!(5));
+    }
+
+    #[test]
+    fn test_string_call_id_round_trips() {
+        let bytes =
+            serialize_call("req-42", "framework_version", vec![], Format::MessagePack).unwrap();
+        let (call_id, method, _args) = deserialize_call(&bytes, Format::MessagePack).unwrap();
+
+        assert_eq!(call_id, CallId::String("req-42".to_string()));
+        assert_eq!(method, "framework_version");
+        assert_eq!(call_id.to_string(), "req-42");
+    }
+
+    #[test]
+    fn test_decode_rejects_garbage_bytes() {
+        let result = decode(b"not a valid message", Format::MessagePack);
+        assert!(matches!(result, Err(IpcError::Deserialization(_))));
+    }
+
+    #[test]
+    fn test_read_message_rejects_oversized_frame() {
+        let mut buf = Vec::new();
+        write_message(&mut buf, FrameType::Request, Format::MessagePack, 1, &[0u8; 100]).unwrap();
+
+        let mut cursor = std::io::Cursor::new(buf);
+        let result = read_message(&mut cursor, 50);
+        assert!(matches!(result, Err(IpcError::MessageTooLarge { .. })));
+    }
+
+    #[test]
+    fn test_read_message_rejects_major_version_mismatch() {
+        let mut buf = Vec::new();
+        write_message(&mut buf, FrameType::Request, Format::MessagePack, 1, &[]).unwrap();
+        // Corrupt the stamped major version byte to simulate a peer on an
+        // incompatible release.
+        buf[6] = PROTOCOL_VERSION[0] + 1;
+
+        let mut cursor = std::io::Cursor::new(buf);
+        let result = read_message(&mut cursor, DEFAULT_MAX_FRAME_SIZE);
+        assert!(matches!(result, Err(IpcError::UnsupportedVersion(_))));
+    }
+
+    #[test]
+    fn test_non_default_format_not_compiled_in_errors_cleanly() {
+        // Without the `format-json`/`format-bincode` features enabled, asking
+        // for either format should fail loudly rather than silently falling
+        // back to MessagePack.
+        let result = serialize_call(1u64, "framework_version", vec![], Format::Json);
+        assert!(matches!(result, Err(IpcError::Serialization(_))));
+
+        let result = serialize_call(1u64, "framework_version", vec![], Format::Bincode);
+        assert!(matches!(result, Err(IpcError::Serialization(_))));
+    }
 }
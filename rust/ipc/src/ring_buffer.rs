@@ -7,32 +7,132 @@ use crate::error::{IpcError, Result};
 use crate::shm::SharedMemory;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Message header in ring buffer
 /// Layout: [length: u32][data: [u8; length]]
 const HEADER_SIZE: usize = 4;
 
+/// A cross-process futex parked on `write_pos`, so a reader can block
+/// without spinning and a writer can wake it the instant new data lands.
+/// `write_pos` lives in the shared segment itself, so the futex word is
+/// visible to both processes - unlike `std::thread::park`/`Condvar`, which
+/// only coordinate threads within one process.
+///
+/// Linux's `SYS_futex` operates on a 32-bit word; `write_pos` is a `usize`
+/// (64-bit on every target this crate supports). The low 32 bits of a
+/// little-endian `usize` live at the same starting address as the `usize`
+/// itself, so `word_ptr` below just reinterprets that prefix - no separate
+/// futex word needs to be threaded through the header. A producer wrapping
+/// past `u32::MAX` writes could in principle make a waiter's stale
+/// `expected` value collide with a newer one, but that only costs a spurious
+/// wake, never a missed one: `read_blocking` always re-checks `write_pos`
+/// vs. `read_pos` itself after returning from the wait.
+#[cfg(target_os = "linux")]
+mod futex {
+    use std::time::Duration;
+
+    fn word_ptr(write_pos: *const AtomicUsize) -> *mut u32 {
+        write_pos as *mut u32
+    }
+
+    /// Block until `*word_ptr` no longer equals `expected`, a spurious wake
+    /// occurs, or `timeout` elapses - whichever is first. The caller is
+    /// always expected to re-check the real condition afterward.
+    pub fn wait(write_pos: *const AtomicUsize, expected: u32, timeout: Duration) {
+        let ts = libc::timespec {
+            tv_sec: timeout.as_secs() as libc::time_t,
+            tv_nsec: timeout.subsec_nanos() as i64,
+        };
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                word_ptr(write_pos),
+                libc::FUTEX_WAIT,
+                expected,
+                &ts as *const libc::timespec,
+                std::ptr::null::<u32>(),
+                0i32,
+            );
+        }
+        // The return value is ignored: EAGAIN (value already changed),
+        // EINTR, and a timeout are all indistinguishable from a legitimate
+        // wake at this level, and `read_blocking` handles all of them the
+        // same way - loop back around and re-check.
+    }
+
+    /// Wake every reader parked on `write_pos`, called right after a
+    /// successful `try_write` makes new data visible.
+    pub fn wake(write_pos: *const AtomicUsize) {
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                word_ptr(write_pos),
+                libc::FUTEX_WAKE,
+                i32::MAX,
+                std::ptr::null::<libc::timespec>(),
+                std::ptr::null::<u32>(),
+                0i32,
+            );
+        }
+    }
+}
+
+/// Feature bits a ring buffer segment can advertise in its header, beyond
+/// the base framing every header version supports.
+pub mod features {
+    /// The segment may carry `protocol::Payload::Blob` frames as-is, rather
+    /// than requiring callers to downgrade them to a base64 JSON string -
+    /// every segment since this header field was introduced supports it.
+    pub const BLOB_PAYLOADS: u64 = 1 << 0;
+}
+
 /// Lock-free ring buffer for IPC
 ///
 /// Memory layout:
-/// [write_pos: 8 bytes][read_pos: 8 bytes][padding: 48 bytes][data: capacity bytes]
+/// [write_pos: 8 bytes][read_pos: 8 bytes][handshake header: 48 bytes][data: capacity bytes]
 ///
-/// The 48-byte padding ensures write_pos and read_pos are on separate cache lines (64 bytes)
-/// to prevent false sharing and maximize performance.
+/// `write_pos`/`read_pos` share the first cache line (64 bytes) with the
+/// handshake header, which fills the rest of that line with
+/// `create`-stamped metadata that `open`
+/// validates before trusting the segment at all - a magic constant (catches
+/// a stale or garbage segment before it's mistaken for a ring buffer), a
+/// header layout version, the capacity the segment was created with, and a
+/// feature-flags bitfield:
+///
+/// [magic: 4 bytes][header version: 2 bytes][reserved: 2 bytes][capacity: 8 bytes][features: 8 bytes][reserved: 24 bytes]
 pub struct RingBuffer {
     shm: Arc<SharedMemory>,
     capacity: usize,
     data_offset: usize,
     write_pos: *mut AtomicUsize,
     read_pos: *mut AtomicUsize,
+    features: u64,
 }
 
 impl RingBuffer {
     /// Offset for atomic counters in shared memory
     const WRITE_POS_OFFSET: usize = 0;
     const READ_POS_OFFSET: usize = 8;
+
+    /// Handshake header, stamped by `create` and checked by `open`
+    const MAGIC_OFFSET: usize = 16;
+    const HEADER_VERSION_OFFSET: usize = 20;
+    const CAPACITY_OFFSET: usize = 24;
+    const FEATURES_OFFSET: usize = 32;
+
     const DATA_OFFSET: usize = 64; // Start after cache line to avoid false sharing
 
+    /// Identifies a segment as an Assassinate ring buffer at all, before its
+    /// `write_pos`/`read_pos` atomics are trusted - ASCII "MSFR".
+    const MAGIC: u32 = 0x4D53_4652;
+
+    /// Version of this header's layout (field offsets/sizes above), distinct
+    /// from `protocol::PROTOCOL_VERSION`, which governs the messages carried
+    /// inside the buffer rather than the shared-memory transport itself.
+    /// Bump on any handshake-header shape change.
+    const HEADER_VERSION: u16 = 1;
+
     /// Create a new ring buffer in shared memory
     ///
     /// # Arguments
@@ -56,6 +156,20 @@ impl RingBuffer {
         write_pos.store(0, Ordering::Release);
         read_pos.store(0, Ordering::Release);
 
+        let negotiated_features = features::BLOB_PAYLOADS;
+
+        // Stamp the handshake header so a later `open` can validate this
+        // segment before trusting it.
+        unsafe {
+            std::ptr::write_unaligned(shm.as_ptr().add(Self::MAGIC_OFFSET) as *mut u32, Self::MAGIC);
+            std::ptr::write_unaligned(
+                shm.as_ptr().add(Self::HEADER_VERSION_OFFSET) as *mut u16,
+                Self::HEADER_VERSION,
+            );
+            std::ptr::write_unaligned(shm.as_ptr().add(Self::CAPACITY_OFFSET) as *mut u64, capacity as u64);
+            std::ptr::write_unaligned(shm.as_ptr().add(Self::FEATURES_OFFSET) as *mut u64, negotiated_features);
+        }
+
         let write_pos_ptr = unsafe { shm.as_ptr().add(Self::WRITE_POS_OFFSET) as *mut AtomicUsize };
         let read_pos_ptr = unsafe { shm.as_ptr().add(Self::READ_POS_OFFSET) as *mut AtomicUsize };
 
@@ -65,14 +179,47 @@ impl RingBuffer {
             data_offset: Self::DATA_OFFSET,
             write_pos: write_pos_ptr,
             read_pos: read_pos_ptr,
+            features: negotiated_features,
         })
     }
 
-    /// Open an existing ring buffer
+    /// Open an existing ring buffer, validating the handshake header `create`
+    /// stamped before trusting anything else about the segment.
     pub fn open(name: &str, capacity: usize) -> Result<Self> {
         let total_size = Self::DATA_OFFSET + capacity;
         let shm = Arc::new(SharedMemory::open(name, total_size)?);
 
+        let magic = unsafe { std::ptr::read_unaligned(shm.as_ptr().add(Self::MAGIC_OFFSET) as *const u32) };
+        if magic != Self::MAGIC {
+            return Err(IpcError::SharedMemory(format!(
+                "'{}' does not look like a ring buffer segment (expected magic {:#x}, found {:#x})",
+                name,
+                Self::MAGIC,
+                magic
+            )));
+        }
+
+        let found_version =
+            unsafe { std::ptr::read_unaligned(shm.as_ptr().add(Self::HEADER_VERSION_OFFSET) as *const u16) };
+        if found_version != Self::HEADER_VERSION {
+            return Err(IpcError::VersionMismatch {
+                expected: Self::HEADER_VERSION,
+                found: found_version,
+            });
+        }
+
+        let found_capacity =
+            unsafe { std::ptr::read_unaligned(shm.as_ptr().add(Self::CAPACITY_OFFSET) as *const u64) } as usize;
+        if found_capacity != capacity {
+            return Err(IpcError::SharedMemory(format!(
+                "'{}' was created with capacity {} bytes, but opened expecting {} bytes",
+                name, found_capacity, capacity
+            )));
+        }
+
+        let negotiated_features =
+            unsafe { std::ptr::read_unaligned(shm.as_ptr().add(Self::FEATURES_OFFSET) as *const u64) };
+
         let write_pos = unsafe { shm.as_ptr().add(Self::WRITE_POS_OFFSET) as *mut AtomicUsize };
         let read_pos = unsafe { shm.as_ptr().add(Self::READ_POS_OFFSET) as *mut AtomicUsize };
 
@@ -82,9 +229,16 @@ impl RingBuffer {
             data_offset: Self::DATA_OFFSET,
             write_pos,
             read_pos,
+            features: negotiated_features,
         })
     }
 
+    /// Feature bits `create` stamped into this segment's header, confirmed
+    /// (not re-negotiated) when this handle was opened.
+    pub fn negotiated_features(&self) -> u64 {
+        self.features
+    }
+
     /// Try to write a message to the ring buffer (non-blocking)
     ///
     /// Returns Ok(()) if successful, Err(RingBufferFull) if buffer is full.
@@ -122,6 +276,11 @@ impl RingBuffer {
             (*self.write_pos).store(write_pos_val + msg_size, Ordering::Release);
         }
 
+        // Wake any reader parked in `read_blocking` on this write position -
+        // a no-op (and free) if nobody's waiting.
+        #[cfg(target_os = "linux")]
+        futex::wake(self.write_pos);
+
         Ok(())
     }
 
@@ -158,6 +317,72 @@ impl RingBuffer {
         Ok(slice)
     }
 
+    /// Block the calling thread until a message is available or `timeout`
+    /// elapses, parking on a futex over `write_pos` instead of busy-polling
+    /// `try_read` - a `try_write` on the other end wakes this immediately,
+    /// so latency no longer depends on a fixed poll interval.
+    ///
+    /// On non-Linux targets (no `SYS_futex`), falls back to a short sleep
+    /// between `try_read` attempts so callers still make progress, just
+    /// without the immediate wakeup.
+    pub fn read_blocking(&self, timeout: Duration) -> Result<&[u8]> {
+        let deadline = std::time::Instant::now() + timeout;
+
+        loop {
+            if self.has_data() {
+                return self.try_read();
+            }
+
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            if remaining.is_zero() {
+                return Err(IpcError::Timeout(timeout.as_millis() as u64));
+            }
+
+            self.park_until_write(remaining);
+            // Loop back around regardless of why `park_until_write`
+            // returned (real wake, spurious wake, or timeout) and check
+            // `has_data()` for real - that's the only thing that can tell a
+            // genuine wake from a stale one, and the deadline check above
+            // still applies either way.
+        }
+    }
+
+    /// Whether a reader would currently find a message waiting
+    fn has_data(&self) -> bool {
+        let write_pos_val = unsafe { (*self.write_pos).load(Ordering::Acquire) };
+        let read_pos_val = unsafe { (*self.read_pos).load(Ordering::Acquire) };
+        write_pos_val != read_pos_val
+    }
+
+    /// Drain every message currently available into `out`, in order,
+    /// without blocking - the same "buffer RPC send packets" trick ARTIQ's
+    /// runtime uses, so a burst of writes costs one futex wake/wait pair
+    /// instead of one per message. Returns the number of messages drained.
+    ///
+    /// Like `try_read`, each slice is zero-copy and borrows from the shared
+    /// segment; callers should finish with them (or copy out what they
+    /// need) before this `RingBuffer` is read from again.
+    pub fn try_read_batch<'a>(&'a self, out: &mut Vec<&'a [u8]>) -> usize {
+        let mut drained = 0;
+        while let Ok(msg) = self.try_read() {
+            out.push(msg);
+            drained += 1;
+        }
+        drained
+    }
+
+    /// Park the calling thread on `write_pos`'s futex for up to `timeout`.
+    #[cfg(target_os = "linux")]
+    fn park_until_write(&self, timeout: Duration) {
+        let expected = unsafe { (*self.write_pos).load(Ordering::Acquire) } as u32;
+        futex::wait(self.write_pos, expected, timeout);
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn park_until_write(&self, timeout: Duration) {
+        std::thread::sleep(timeout.min(Duration::from_millis(1)));
+    }
+
     /// Get current buffer utilization (0.0 = empty, 1.0 = full)
     pub fn utilization(&self) -> f64 {
         let write_pos_val = unsafe { (*self.write_pos).load(Ordering::Acquire) };
@@ -215,4 +440,90 @@ mod tests {
         let result = rb.try_write(&large_msg);
         assert!(matches!(result, Err(IpcError::RingBufferFull(_))));
     }
+
+    #[test]
+    fn test_open_negotiates_features_stamped_by_create() {
+        let rb = RingBuffer::create("test_ring_handshake", 4096).unwrap();
+        assert_eq!(rb.negotiated_features(), features::BLOB_PAYLOADS);
+
+        let opened = RingBuffer::open("test_ring_handshake", 4096).unwrap();
+        assert_eq!(opened.negotiated_features(), features::BLOB_PAYLOADS);
+    }
+
+    #[test]
+    fn test_open_rejects_capacity_mismatch() {
+        let _rb = RingBuffer::create("test_ring_cap_mismatch", 4096).unwrap();
+
+        let result = RingBuffer::open("test_ring_cap_mismatch", 8192);
+        assert!(matches!(result, Err(IpcError::SharedMemory(_))));
+    }
+
+    #[test]
+    fn test_open_rejects_header_version_mismatch() {
+        let rb = RingBuffer::create("test_ring_version_mismatch", 4096).unwrap();
+
+        // Simulate a segment stamped by a future, incompatible build.
+        unsafe {
+            std::ptr::write_unaligned(
+                rb.shm.as_ptr().add(RingBuffer::HEADER_VERSION_OFFSET) as *mut u16,
+                RingBuffer::HEADER_VERSION + 1,
+            );
+        }
+
+        let result = RingBuffer::open("test_ring_version_mismatch", 4096);
+        assert!(matches!(
+            result,
+            Err(IpcError::VersionMismatch { expected, found })
+                if expected == RingBuffer::HEADER_VERSION && found == RingBuffer::HEADER_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn test_read_blocking_returns_immediately_when_data_already_present() {
+        let rb = RingBuffer::create("test_ring_blocking_ready", 4096).unwrap();
+        rb.try_write(b"hello").unwrap();
+
+        let msg = rb.read_blocking(Duration::from_secs(1)).unwrap();
+        assert_eq!(msg, b"hello");
+    }
+
+    #[test]
+    fn test_read_blocking_wakes_when_a_writer_catches_up() {
+        let rb = Arc::new(RingBuffer::create("test_ring_blocking_wake", 4096).unwrap());
+        let writer = rb.clone();
+
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            writer.try_write(b"delayed").unwrap();
+        });
+
+        let msg = rb.read_blocking(Duration::from_secs(1)).unwrap();
+        assert_eq!(msg, b"delayed");
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_read_blocking_times_out_when_nothing_arrives() {
+        let rb = RingBuffer::create("test_ring_blocking_timeout", 4096).unwrap();
+        let result = rb.read_blocking(Duration::from_millis(20));
+        assert!(matches!(result, Err(IpcError::Timeout(_))));
+    }
+
+    #[test]
+    fn test_try_read_batch_drains_everything_available() {
+        let rb = RingBuffer::create("test_ring_batch", 4096).unwrap();
+        for i in 0..5 {
+            rb.try_write(format!("msg{}", i).as_bytes()).unwrap();
+        }
+
+        let mut out = Vec::new();
+        let drained = rb.try_read_batch(&mut out);
+
+        assert_eq!(drained, 5);
+        assert_eq!(out.len(), 5);
+        for (i, msg) in out.iter().enumerate() {
+            assert_eq!(*msg, format!("msg{}", i).as_bytes());
+        }
+        assert_eq!(rb.try_read_batch(&mut Vec::new()), 0);
+    }
 }
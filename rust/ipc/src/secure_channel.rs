@@ -0,0 +1,335 @@
+/// Authenticated, encrypted session layer for the IPC ring buffers
+///
+/// Anyone who can map the shared-memory segment can currently write frames
+/// straight onto `RingBuffer` and have them dispatched - there is no
+/// authentication between the daemon and a client. `SecureChannel` adds an
+/// optional handshake on top: an ephemeral X25519 key exchange, each side's
+/// ephemeral public key signed by a long-term Ed25519 identity, derives a
+/// shared session key, and from then on every frame passed through
+/// `seal`/`open` is encrypted and authenticated with a strictly-increasing
+/// per-message nonce so replayed or reordered frames are rejected.
+///
+/// The server side checks the client against an `AllowList` of Ed25519
+/// public keys; a client pins the server's single long-term public key. Neither
+/// side trusts a handshake whose signature doesn't check out against the
+/// expected key(s).
+use crate::error::{IpcError, Result};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand_core::OsRng;
+use std::sync::atomic::{AtomicU64, Ordering};
+use x25519_dalek::{EphemeralSecret, PublicKey as X25519PublicKey};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Long-term Ed25519 identity used to authenticate a handshake
+pub struct Identity {
+    signing_key: SigningKey,
+}
+
+impl Identity {
+    /// Generate a new long-term identity
+    pub fn generate() -> Self {
+        Self {
+            signing_key: SigningKey::generate(&mut OsRng),
+        }
+    }
+
+    /// Load an identity from a previously-generated 32-byte seed
+    pub fn from_bytes(seed: &[u8; 32]) -> Self {
+        Self {
+            signing_key: SigningKey::from_bytes(seed),
+        }
+    }
+
+    pub fn public_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    fn sign(&self, message: &[u8]) -> Signature {
+        self.signing_key.sign(message)
+    }
+}
+
+/// Ed25519 public keys permitted to complete a handshake with us
+#[derive(Default)]
+pub struct AllowList {
+    keys: Vec<VerifyingKey>,
+}
+
+impl AllowList {
+    pub fn new(keys: Vec<VerifyingKey>) -> Self {
+        Self { keys }
+    }
+
+    fn contains(&self, key: &VerifyingKey) -> bool {
+        self.keys.iter().any(|k| k == key)
+    }
+}
+
+/// The ephemeral public key plus a signature binding it to the sender's
+/// long-term identity, exchanged by both sides to start a handshake
+pub struct HandshakeMessage {
+    pub ephemeral_public: [u8; 32],
+    pub identity_public: [u8; 32],
+    pub signature: [u8; 64],
+}
+
+impl HandshakeMessage {
+    fn sign(identity: &Identity, ephemeral_public: &X25519PublicKey) -> Self {
+        let signature = identity.sign(ephemeral_public.as_bytes());
+        Self {
+            ephemeral_public: *ephemeral_public.as_bytes(),
+            identity_public: identity.public_key().to_bytes(),
+            signature: signature.to_bytes(),
+        }
+    }
+
+    fn verify(&self) -> Result<VerifyingKey> {
+        let identity_public = VerifyingKey::from_bytes(&self.identity_public)
+            .map_err(|e| IpcError::HandshakeFailed(format!("bad identity key: {}", e)))?;
+        let signature = Signature::from_bytes(&self.signature);
+        identity_public
+            .verify(&self.ephemeral_public, &signature)
+            .map_err(|e| IpcError::HandshakeFailed(format!("bad handshake signature: {}", e)))?;
+        Ok(identity_public)
+    }
+}
+
+/// Session key derived from the handshake, zeroized on drop
+#[derive(Zeroize, ZeroizeOnDrop)]
+struct SessionKey([u8; 32]);
+
+/// Which side of the handshake derived a given `SecureChannel` - determines
+/// which of the two directional keys it sends with and which it receives
+/// with (see `from_shared_secret`).
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Client,
+    Server,
+}
+
+/// An established, authenticated, encrypted channel over the ring buffers.
+///
+/// Each side tracks its own outgoing nonce counter plus the highest nonce
+/// seen from the peer, so `open` rejects anything not strictly greater than
+/// the last accepted frame. Sending and receiving use separate keys derived
+/// from the shared secret (one for client->server, one for server->client)
+/// so the two directions never reuse the same (key, nonce) pair even though
+/// both sides' nonce counters start at 1 - see `from_shared_secret`.
+pub struct SecureChannel {
+    send_cipher: ChaCha20Poly1305,
+    recv_cipher: ChaCha20Poly1305,
+    send_nonce: AtomicU64,
+    highest_received_nonce: AtomicU64,
+    pub peer_identity: VerifyingKey,
+}
+
+impl SecureChannel {
+    /// Run the client side of the handshake: sign our ephemeral key, verify
+    /// the server's against our pinned expectation, derive the session key.
+    pub fn handshake_client(
+        identity: &Identity,
+        pinned_server_key: &VerifyingKey,
+        exchange: impl FnOnce(HandshakeMessage) -> Result<HandshakeMessage>,
+    ) -> Result<Self> {
+        let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+        let ephemeral_public = X25519PublicKey::from(&ephemeral_secret);
+        let outgoing = HandshakeMessage::sign(identity, &ephemeral_public);
+
+        let incoming = exchange(outgoing)?;
+        let peer_identity = incoming.verify()?;
+        if &peer_identity != pinned_server_key {
+            return Err(IpcError::UntrustedPeer);
+        }
+
+        let peer_ephemeral = X25519PublicKey::from(incoming.ephemeral_public);
+        let shared_secret = ephemeral_secret.diffie_hellman(&peer_ephemeral);
+        Self::from_shared_secret(shared_secret.as_bytes(), peer_identity, Role::Client)
+    }
+
+    /// Run the server side of the handshake: verify the client against the
+    /// allow-list, sign our own ephemeral key, derive the session key.
+    pub fn handshake_server(
+        identity: &Identity,
+        allow_list: &AllowList,
+        incoming: HandshakeMessage,
+        reply: impl FnOnce(HandshakeMessage) -> Result<()>,
+    ) -> Result<Self> {
+        let peer_identity = incoming.verify()?;
+        if !allow_list.contains(&peer_identity) {
+            return Err(IpcError::UntrustedPeer);
+        }
+
+        let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+        let ephemeral_public = X25519PublicKey::from(&ephemeral_secret);
+        let outgoing = HandshakeMessage::sign(identity, &ephemeral_public);
+
+        let peer_ephemeral = X25519PublicKey::from(incoming.ephemeral_public);
+        let shared_secret = ephemeral_secret.diffie_hellman(&peer_ephemeral);
+
+        reply(outgoing)?;
+        Self::from_shared_secret(shared_secret.as_bytes(), peer_identity, Role::Server)
+    }
+
+    /// Derive this side's send/receive ciphers from the shared secret.
+    ///
+    /// Both sides' `send_nonce` starts at 1 (see below), so deriving a
+    /// single session key and using it for both directions would mean
+    /// client frame #1 and server frame #1 encrypt under the same (key,
+    /// nonce) pair - catastrophic for ChaCha20Poly1305. Instead we derive
+    /// two directional keys, one per context string, and each side picks
+    /// which is "mine" vs. "theirs" based on its `Role`.
+    fn from_shared_secret(shared_secret: &[u8; 32], peer_identity: VerifyingKey, role: Role) -> Result<Self> {
+        let mut client_to_server_key =
+            SessionKey(*blake3::derive_key("assassinate-ipc-session-key-v1-c2s", shared_secret).as_bytes());
+        let mut server_to_client_key =
+            SessionKey(*blake3::derive_key("assassinate-ipc-session-key-v1-s2c", shared_secret).as_bytes());
+
+        let client_to_server_cipher = ChaCha20Poly1305::new(Key::from_slice(&client_to_server_key.0));
+        let server_to_client_cipher = ChaCha20Poly1305::new(Key::from_slice(&server_to_client_key.0));
+        client_to_server_key.zeroize();
+        server_to_client_key.zeroize();
+
+        let (send_cipher, recv_cipher) = match role {
+            Role::Client => (client_to_server_cipher, server_to_client_cipher),
+            Role::Server => (server_to_client_cipher, client_to_server_cipher),
+        };
+
+        Ok(Self {
+            send_cipher,
+            recv_cipher,
+            // Nonces start at 1 so 0 can mean "nothing accepted yet" on the
+            // receive side, without a separate has-received flag.
+            send_nonce: AtomicU64::new(1),
+            highest_received_nonce: AtomicU64::new(0),
+            peer_identity,
+        })
+    }
+
+    /// Encrypt and authenticate a frame, stamping it with the next nonce
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce_value = self.send_nonce.fetch_add(1, Ordering::SeqCst);
+        let nonce_bytes = nonce_to_bytes(nonce_value);
+        let ciphertext = self
+            .send_cipher
+            .encrypt(
+                Nonce::from_slice(&nonce_bytes),
+                Payload {
+                    msg: plaintext,
+                    aad: &nonce_value.to_be_bytes(),
+                },
+            )
+            .map_err(|e| IpcError::HandshakeFailed(format!("encryption failed: {}", e)))?;
+
+        let mut framed = Vec::with_capacity(8 + ciphertext.len());
+        framed.extend_from_slice(&nonce_value.to_be_bytes());
+        framed.extend_from_slice(&ciphertext);
+        Ok(framed)
+    }
+
+    /// Decrypt and authenticate a frame, rejecting replayed or out-of-order nonces
+    pub fn open(&self, framed: &[u8]) -> Result<Vec<u8>> {
+        if framed.len() < 8 {
+            return Err(IpcError::HandshakeFailed("frame too short".to_string()));
+        }
+        let (nonce_be, ciphertext) = framed.split_at(8);
+        let nonce_value = u64::from_be_bytes(nonce_be.try_into().unwrap());
+
+        let highest = self.highest_received_nonce.load(Ordering::SeqCst);
+        if nonce_value <= highest {
+            return Err(IpcError::ReplayDetected {
+                expected: highest,
+                got: nonce_value,
+            });
+        }
+
+        let nonce_bytes = nonce_to_bytes(nonce_value);
+        let plaintext = self
+            .recv_cipher
+            .decrypt(
+                Nonce::from_slice(&nonce_bytes),
+                Payload {
+                    msg: ciphertext,
+                    aad: &nonce_value.to_be_bytes(),
+                },
+            )
+            .map_err(|_| IpcError::Unauthenticated)?;
+
+        self.highest_received_nonce.store(nonce_value, Ordering::SeqCst);
+        Ok(plaintext)
+    }
+}
+
+/// ChaCha20Poly1305 takes a 12-byte nonce; we use the low 8 bytes for our
+/// counter and leave the top 4 bytes zeroed, since a single channel never
+/// sends more than 2^64 frames.
+fn nonce_to_bytes(nonce_value: u64) -> [u8; 12] {
+    let mut bytes = [0u8; 12];
+    bytes[4..].copy_from_slice(&nonce_value.to_be_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handshake_and_round_trip() {
+        let server_identity = Identity::generate();
+        let client_identity = Identity::generate();
+        let allow_list = AllowList::new(vec![client_identity.public_key()]);
+        let server_public = server_identity.public_key();
+
+        // Drive both handshakes through an in-memory exchange instead of the
+        // real ring buffer, since the goal here is just to exercise the
+        // crypto, not the transport: `handshake_client`'s `exchange` closure
+        // hands its real outgoing Hello straight to `handshake_server` and
+        // returns the server's real reply, so both sides derive the shared
+        // secret from the same pair of ephemerals.
+        let mut server_channel_slot: Option<SecureChannel> = None;
+        let client_channel = SecureChannel::handshake_client(&client_identity, &server_public, |outgoing| {
+            let mut server_reply_slot: Option<HandshakeMessage> = None;
+            let server_channel = SecureChannel::handshake_server(&server_identity, &allow_list, outgoing, |reply| {
+                server_reply_slot = Some(reply);
+                Ok(())
+            })?;
+            server_channel_slot = Some(server_channel);
+            Ok(server_reply_slot.unwrap())
+        })
+        .unwrap();
+        let server_channel = server_channel_slot.unwrap();
+
+        let sealed = client_channel.seal(b"module_exploit").unwrap();
+        let opened = server_channel.open(&sealed).unwrap();
+        assert_eq!(opened, b"module_exploit");
+
+        // Server -> client exercises the other direction's key, which must
+        // be independent of the client -> server key above (see
+        // `from_shared_secret`) even though both nonce counters start at 1.
+        let sealed_reply = server_channel.seal(b"module_result").unwrap();
+        let opened_reply = client_channel.open(&sealed_reply).unwrap();
+        assert_eq!(opened_reply, b"module_result");
+
+        // Replaying the same frame must be rejected
+        assert!(matches!(
+            server_channel.open(&sealed),
+            Err(IpcError::ReplayDetected { .. })
+        ));
+    }
+
+    #[test]
+    fn test_untrusted_peer_rejected() {
+        let server_identity = Identity::generate();
+        let client_identity = Identity::generate();
+        let other_identity = Identity::generate();
+        let allow_list = AllowList::new(vec![other_identity.public_key()]);
+
+        let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+        let ephemeral_public = X25519PublicKey::from(&ephemeral_secret);
+        let client_hello = HandshakeMessage::sign(&client_identity, &ephemeral_public);
+
+        let result = SecureChannel::handshake_server(&server_identity, &allow_list, client_hello, |_| Ok(()));
+        assert!(matches!(result, Err(IpcError::UntrustedPeer)));
+    }
+}